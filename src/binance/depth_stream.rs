@@ -0,0 +1,78 @@
+use binance::config::Config;
+use binance::model::DepthOrderBookEvent;
+use std::net::TcpStream;
+use std::time::Duration;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+/*
+Depth stream of one symbol (<symbol>@depth@100ms). Unlike binance WebSockets event loop, reading
+waits at most |read_timeout|, so orderbook executor notices the stream which is connected but
+silent (stale) and can reconnect or fall back to REST.
+ */
+pub struct DepthStream {
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+}
+
+pub enum DepthStreamRead {
+    Update(DepthOrderBookEvent),
+    // nothing came within read timeout (or a message which is not a depth update)
+    Idle,
+    // connection is broken - reconnect is needed
+    Closed(String),
+}
+
+impl DepthStream {
+    pub fn connect(
+        api_config: &Config,
+        endpoint: &str,
+        read_timeout: Duration,
+    ) -> Result<Self, String> {
+        let url = format!("{}/{}", api_config.ws_endpoint, endpoint);
+        let (socket, _) = tungstenite::connect(url.as_str()).map_err(|e| format!("{url}: {e}"))?;
+
+        set_read_timeout(&socket, read_timeout).map_err(|e| format!("{url}: {e}"))?;
+
+        Ok(Self { socket })
+    }
+
+    pub fn read(&mut self) -> DepthStreamRead {
+        match self.socket.read() {
+            Ok(Message::Text(msg)) => match serde_json::from_str::<DepthOrderBookEvent>(&msg) {
+                Ok(event) => DepthStreamRead::Update(event),
+                Err(_) => DepthStreamRead::Idle,
+            },
+            Ok(Message::Close(frame)) => DepthStreamRead::Closed(format!("closed: {frame:?}")),
+            // pings are answered by tungstenite
+            Ok(_) => DepthStreamRead::Idle,
+            Err(e) if is_read_timeout(&e) => DepthStreamRead::Idle,
+            Err(e) => DepthStreamRead::Closed(e.to_string()),
+        }
+    }
+
+    pub fn close(&mut self) {
+        let _ = self.socket.close(None);
+        let _ = self.socket.flush();
+    }
+}
+
+// socket read returns WouldBlock/TimedOut error when nothing came within |read_timeout|
+pub fn set_read_timeout(
+    socket: &WebSocket<MaybeTlsStream<TcpStream>>,
+    read_timeout: Duration,
+) -> std::io::Result<()> {
+    match socket.get_ref() {
+        MaybeTlsStream::Plain(s) => s.set_read_timeout(Some(read_timeout)),
+        MaybeTlsStream::NativeTls(s) => s.get_ref().set_read_timeout(Some(read_timeout)),
+        _ => Ok(()),
+    }
+}
+
+pub fn is_read_timeout(e: &tungstenite::Error) -> bool {
+    match e {
+        tungstenite::Error::Io(e) => {
+            e.kind() == std::io::ErrorKind::WouldBlock || e.kind() == std::io::ErrorKind::TimedOut
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,49 @@
+use crate::config::settings::ConfigStruct;
+use crate::core::types::ExchangeEnvironment;
+use binance::config::Config;
+
+pub const PRODUCTION_REST_API_ENDPOINT: &str = "https://api.binance.com";
+pub const PRODUCTION_WS_ENDPOINT: &str = "wss://stream.binance.com/ws";
+pub const TESTNET_REST_API_ENDPOINT: &str = "https://testnet.binance.vision";
+pub const TESTNET_WS_ENDPOINT: &str = "wss://testnet.binance.vision/ws";
+
+pub fn rest_api_endpoint(config: &ConfigStruct) -> String {
+    match config.exchange_environment {
+        ExchangeEnvironment::Production => PRODUCTION_REST_API_ENDPOINT.to_string(),
+        ExchangeEnvironment::Testnet => TESTNET_REST_API_ENDPOINT.to_string(),
+        ExchangeEnvironment::Custom => config
+            .rest_api_endpoint
+            .clone()
+            .unwrap_or_default()
+            .trim_end_matches('/')
+            .to_string(),
+    }
+}
+
+pub fn ws_endpoint(config: &ConfigStruct) -> String {
+    match config.exchange_environment {
+        ExchangeEnvironment::Production => PRODUCTION_WS_ENDPOINT.to_string(),
+        ExchangeEnvironment::Testnet => TESTNET_WS_ENDPOINT.to_string(),
+        ExchangeEnvironment::Custom => config
+            .ws_endpoint
+            .clone()
+            .unwrap_or_default()
+            .trim_end_matches('/')
+            .to_string(),
+    }
+}
+
+// |exchange_info_apis| are used only for production, other environments have single REST API
+pub fn exchange_info_apis(config: &ConfigStruct) -> Vec<String> {
+    match config.exchange_environment {
+        ExchangeEnvironment::Production => config.exchange_info_apis.clone(),
+        _ => vec![format!("{}/api/v3/exchangeInfo", rest_api_endpoint(config))],
+    }
+}
+
+// binance crate config with REST API and websocket addresses for our environment
+pub fn binance_api_config(config: &ConfigStruct) -> Config {
+    Config::default()
+        .set_rest_api_endpoint(rest_api_endpoint(config))
+        .set_ws_endpoint(ws_endpoint(config))
+}
@@ -1,22 +1,146 @@
-use std::collections::HashMap;
+use crate::binance::endpoints::exchange_info_apis;
 use crate::binance::filters::FiltersParser;
-use crate::config::settings::{ConfigStruct, CONFIG_FILENAME};
+use crate::config::settings::ConfigStruct;
 use crate::core::json::extract_json_data;
 use crate::core::types::{Asset, BaseAsset, QuoteAsset, Symbol, SymbolAction};
 use log::{error, info, warn};
 use rand::{seq::IteratorRandom, thread_rng};
 use rust_decimal::Decimal;
-use std::fs;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
 use std::sync::{Arc, RwLock};
 
+// why the symbol from exchangeInfo is not traded by the bot
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolExclusion {
+    NotSpot,
+    ExcludedAsset(String),
+    ExcludedSymbol,
+    NoBaseStartingAsset(String),
+    NotTrading(String),
+}
+
+impl fmt::Display for SymbolExclusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolExclusion::NotSpot => write!(f, "not a SPOT market pair"),
+            SymbolExclusion::ExcludedAsset(a) => {
+                write!(f, "asset {a} is excluded (|excluded_assets|)")
+            }
+            SymbolExclusion::ExcludedSymbol => write!(f, "symbol is excluded (|excluded_symbols|)"),
+            SymbolExclusion::NoBaseStartingAsset(a) => {
+                write!(f, "not paired with base starting asset {a}")
+            }
+            SymbolExclusion::NotTrading(status) => write!(f, "status is {status}, not TRADING"),
+        }
+    }
+}
+
+// symbol from exchangeInfo checked against the config, |exclusion| is None for traded symbols
+#[derive(Serialize, Debug, Clone)]
+pub struct SymbolInclusion {
+    pub symbol: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub status: String,
+    // default (first) action on the symbol
+    pub action: Option<SymbolAction>,
+    pub exclusion: Option<SymbolExclusion>,
+}
+
+impl SymbolInclusion {
+    pub fn is_included(&self) -> bool {
+        self.exclusion.is_none()
+    }
+}
+
+// the same checks (in the same order) as update_symbols_and_filters_list() does for every symbol
+pub fn check_symbol_inclusion(config: &ConfigStruct, s: &Value) -> SymbolInclusion {
+    let mut inclusion = SymbolInclusion {
+        symbol: s["symbol"].to_string().replace(&['\"'][..], ""),
+        base_asset: s["baseAsset"].to_string().replace(&['\"'][..], ""),
+        quote_asset: s["quoteAsset"].to_string().replace(&['\"'][..], ""),
+        status: s["status"].to_string().replace(&['\"'][..], ""),
+        action: None,
+        exclusion: None,
+    };
+
+    //
+    // BEGIN: "permissions": []
+    //
+    let this_is_spot_pair = match s["permissions"].as_array() {
+        Some(permissions) => permissions.iter().any(|p| p.eq("SPOT")),
+        None => false,
+    };
+
+    // we want only spot market
+    if !this_is_spot_pair {
+        inclusion.exclusion = Some(SymbolExclusion::NotSpot);
+        return inclusion;
+    }
+    //
+    // END: "permissions": []
+    //
+
+    let base_asset_val: BaseAsset = BaseAsset(inclusion.base_asset.clone());
+    let quote_asset_val: QuoteAsset = QuoteAsset(inclusion.quote_asset.clone());
+
+    // excluding asset
+    for asset in &config.excluded_assets {
+        if base_asset_val.clone() == Asset::BaseAsset(BaseAsset(asset.clone()))
+            || quote_asset_val.clone() == Asset::QuoteAsset(QuoteAsset(asset.clone()))
+        {
+            inclusion.exclusion = Some(SymbolExclusion::ExcludedAsset(asset.clone()));
+            return inclusion;
+        }
+    }
+
+    // excluding entire symbol and reversed symbol
+    if config
+        .excluded_symbols
+        .iter()
+        .any(|sym| sym.0 == inclusion.symbol)
+    {
+        inclusion.exclusion = Some(SymbolExclusion::ExcludedSymbol);
+        return inclusion;
+    }
+
+    // only symbols with main asset as base/quote are considered
+    for my_base_asset in &config.base_starting_assets {
+        let processed_asset = Asset::from(BaseAsset(my_base_asset.to_string()));
+        if base_asset_val != processed_asset && quote_asset_val != processed_asset {
+            inclusion.exclusion = Some(SymbolExclusion::NoBaseStartingAsset(
+                my_base_asset.to_string(),
+            ));
+            return inclusion;
+        }
+
+        // starting action for symbol
+        if quote_asset_val == QuoteAsset(my_base_asset.to_string()) {
+            inclusion.action = Some(SymbolAction::Buy);
+        }
+        if base_asset_val == BaseAsset(my_base_asset.to_string()) {
+            inclusion.action = Some(SymbolAction::Sell);
+        }
+    }
+
+    if inclusion.status != "TRADING" {
+        inclusion.exclusion = Some(SymbolExclusion::NotTrading(inclusion.status.clone()));
+    }
+
+    inclusion
+}
+
 pub async fn update_symbols_and_filters_list(
+    config: ConfigStruct,
     valid_trading_symbols: Arc<RwLock<HashMap<Symbol, bool>>>,
     symbol_actions: Arc<RwLock<HashMap<Symbol, SymbolAction>>>,
     filters_map: Arc<RwLock<HashMap<String, Decimal>>>,
 ) {
-    let config_data = fs::read_to_string(CONFIG_FILENAME).expect("Cannot read config file {}");
-    let config: ConfigStruct = toml::from_str(config_data.as_str()).unwrap();
-    let api_exchange_info_addr = randomly_select_api_address(config.clone().exchange_info_apis);
+    let api_exchange_info_addr = randomly_select_api_address(exchange_info_apis(&config));
     let json_string = fetch_exchange_info(api_exchange_info_addr.await)
         .await
         .expect("no json fetched");
@@ -34,225 +158,172 @@ pub async fn update_symbols_and_filters_list(
     ////////////////////////////////////////////////////////////////////////////////////////////
     // info!("loading exchangeInfo data for processing...");
 
-    'all_symbols_loop: for s in symbols_data.iter() {
-        let mut this_is_spot_pair = false;
+    for s in symbols_data.iter() {
+        let inclusion = check_symbol_inclusion(&config, s);
+        let symbol = Symbol(inclusion.symbol.clone());
 
-        //
-        // BEGIN: "permissions": []
-        //
-        if s["permissions"].is_array() {
-            let permission = s["permissions"].as_array().unwrap();
+        // starting action for symbol
+        if let Some(action) = inclusion.action {
+            symbol_actions
+                .write()
+                .unwrap()
+                .insert(symbol.clone(), action);
+        }
 
-            for p in permission.iter() {
-                if p.eq("SPOT") {
-                    this_is_spot_pair = true;
-                }
+        match inclusion.exclusion {
+            None => (),
+            Some(SymbolExclusion::ExcludedAsset(asset)) => {
+                warn!("asset {asset} is excluded from the config.");
+                continue;
             }
+            Some(SymbolExclusion::ExcludedSymbol) => {
+                warn!("symbol {symbol} is excluded by the config.");
+                continue;
+            }
+            Some(_) => continue,
         }
 
-        // we want only spot market
-        if !this_is_spot_pair {
-            continue 'all_symbols_loop;
-        }
         //
-        // END: "permissions": []
+        // BEGIN: filters: [] - Support for filters
         //
 
-        // Rest symbols data
-        let symbol: Symbol = Symbol(s["symbol"].to_string().replace(&['\"'][..], ""));
-        let status: String = s["status"].to_string().replace(&['\"'][..], "");
-        let b = s["baseAsset"].to_string().replace(&['\"'][..], "");
-        let q = s["quoteAsset"].to_string().replace(&['\"'][..], "");
+        // price-filter-min
+        // price-filter-max
+        // price-filter-tick-size
+        // pprice-multi-up
+        // pprice-multi-down
+        // pprice-avg-price-mins
+        // lot-size-min-qty
+        // lot-size-max-qty
+        // lot-size-step-size
+        // min-notional-min
+        // min-notional-apply-to-market
+        // min-notional-avg-price-mins
+        // market-lot-size-min-qty
+        // market-lot-size-max-qty
+        // market-lot-size-step-size
+
+        if s["filters"].is_array() {
+            let filters = s["filters"].as_array().unwrap();
+
+            let filter = FiltersParser::new(filters);
+
+            {
+                // PRICE_FILTER: min_price
+                let key = format!("{}-price-filter-min", symbol.clone());
+                let value = filter.min_price;
+
+                filters_map.write().unwrap().insert(key, value);
+            }
+            {
+                // PRICE_FILTER: max_price
+                let key = format!("{}-price-filter-max", symbol.clone());
+                let value = filter.max_price;
 
-        let base_asset_val: BaseAsset = BaseAsset(b);
-        let quote_asset_val: QuoteAsset = QuoteAsset(q);
+                filters_map.write().unwrap().insert(key, value);
+            }
+            {
+                // PRICE_FILTER: tick_size
+                let key = format!("{}-price-filter-tick-size", symbol.clone());
+                let value = filter.tick_size;
 
-        // excluding asset
-        for asset in &config.excluded_assets {
-            if base_asset_val.clone() == Asset::BaseAsset(BaseAsset(asset.clone())) {
-                warn!("asset {asset} is excluded from the config.");
-                continue 'all_symbols_loop;
+                filters_map.write().unwrap().insert(key, value);
             }
-            if quote_asset_val.clone() == Asset::QuoteAsset(QuoteAsset(asset.clone())) {
-                warn!("asset {asset} is excluded from the config.");
-                continue 'all_symbols_loop;
+            {
+                // PERCENT_PRICE: pprice-multi-up
+                let key = format!("{}-pprice-multi-up", symbol.clone());
+                let value = filter.percent_price_multi_up;
+
+                filters_map.write().unwrap().insert(key, value);
             }
-        }
+            {
+                // PERCENT_PRICE: pprice-multi-down
+                let key = format!("{}-pprice-multi-down", symbol.clone());
+                let value = filter.percent_price_multi_down;
 
-        // excluding entire symbol and reversed symbol
-        for sym in &config.excluded_symbols {
-            if *sym == symbol {
-                warn!("symbol {sym} is excluded by the config.");
-                continue 'all_symbols_loop;
+                filters_map.write().unwrap().insert(key, value);
             }
-        }
+            {
+                // PERCENT_PRICE: pprice-avg-price-mins
+                let key = format!("{}-pprice-avg-price-mins", symbol.clone());
+                let value = filter.percent_price_avg_min;
+
+                filters_map.write().unwrap().insert(key, value);
+            }
+            {
+                // LOT_SIZE: min-qty
+                let key = format!("{}-lot-size-min-qty", symbol.clone());
+                let value = filter.lot_min_qty;
 
-        // only symbols with main asset as base/quote are considered
-        for my_base_asset in &config.base_starting_assets {
-            let processed_asset = Asset::from(BaseAsset(my_base_asset.to_string()));
-            if base_asset_val != processed_asset && quote_asset_val != processed_asset {
-                // warn!("symbol: {symbol} does not have any base starting asset - skipping");
-                continue 'all_symbols_loop;
+                filters_map.write().unwrap().insert(key, value);
             }
+            {
+                // LOT_SIZE: max-qty
+                let key = format!("{}-lot-size-max-qty", symbol.clone());
+                let value = filter.lot_max_qty;
 
-            // starting action for symbol
-            if quote_asset_val == QuoteAsset(my_base_asset.to_string()) {
-                symbol_actions
-                    .write()
-                    .unwrap()
-                    .insert(symbol.clone(), SymbolAction::Buy);
+                filters_map.write().unwrap().insert(key, value);
             }
-            if base_asset_val == BaseAsset(my_base_asset.to_string()) {
-                symbol_actions
-                    .write()
-                    .unwrap()
-                    .insert(symbol.clone(), SymbolAction::Sell);
+            {
+                // LOT_SIZE: step-size
+                let key = format!("{}-lot-size-step-size", symbol.clone());
+                let value = filter.lot_step_size;
+
+                filters_map.write().unwrap().insert(key, value);
             }
-        }
+            {
+                // MIN_NOTIONAL: minNotional
+                let key = format!("{}-min-notional-min", symbol.clone());
+                let value = filter.min_notional_min;
 
-        if status.eq("TRADING") {
-            //
-            // BEGIN: filters: [] - Support for filters
-            //
-
-            // price-filter-min
-            // price-filter-max
-            // price-filter-tick-size
-            // pprice-multi-up
-            // pprice-multi-down
-            // pprice-avg-price-mins
-            // lot-size-min-qty
-            // lot-size-max-qty
-            // lot-size-step-size
-            // min-notional-min
-            // min-notional-apply-to-market
-            // min-notional-avg-price-mins
-            // market-lot-size-min-qty
-            // market-lot-size-max-qty
-            // market-lot-size-step-size
-
-            if s["filters"].is_array() {
-                let filters = s["filters"].as_array().unwrap();
-
-                let filter = FiltersParser::new(filters);
-
-                {
-                    // PRICE_FILTER: min_price
-                    let key = format!("{}-price-filter-min", symbol.clone());
-                    let value = filter.min_price;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
-                {
-                    // PRICE_FILTER: max_price
-                    let key = format!("{}-price-filter-max", symbol.clone());
-                    let value = filter.max_price;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
-                {
-                    // PRICE_FILTER: tick_size
-                    let key = format!("{}-price-filter-tick-size", symbol.clone());
-                    let value = filter.tick_size;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
-                {
-                    // PERCENT_PRICE: pprice-multi-up
-                    let key = format!("{}-pprice-multi-up", symbol.clone());
-                    let value = filter.percent_price_multi_up;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
-                {
-                    // PERCENT_PRICE: pprice-multi-down
-                    let key = format!("{}-pprice-multi-down", symbol.clone());
-                    let value = filter.percent_price_multi_down;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
-                {
-                    // PERCENT_PRICE: pprice-avg-price-mins
-                    let key = format!("{}-pprice-avg-price-mins", symbol.clone());
-                    let value = filter.percent_price_avg_min;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
-                {
-                    // LOT_SIZE: min-qty
-                    let key = format!("{}-lot-size-min-qty", symbol.clone());
-                    let value = filter.lot_min_qty;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
-                {
-                    // LOT_SIZE: max-qty
-                    let key = format!("{}-lot-size-max-qty", symbol.clone());
-                    let value = filter.lot_max_qty;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
-                {
-                    // LOT_SIZE: step-size
-                    let key = format!("{}-lot-size-step-size", symbol.clone());
-                    let value = filter.lot_step_size;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
-                {
-                    // MIN_NOTIONAL: minNotional
-                    let key = format!("{}-min-notional-min", symbol.clone());
-                    let value = filter.min_notional_min;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
-                {
-                    // MIN_NOTIONAL: applytomarket
-                    let key = format!("{}-min-notional-apply-to-market", symbol.clone());
-                    let value = filter.notional_apply_to_market;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
-                {
-                    // MIN_NOTIONAL: avgPriceMins
-                    let key = format!("{}-min-notional-avg-price-mins", symbol.clone());
-                    let value = filter.notional_avg_price_mins;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
-                {
-                    // MARKET_LOT_SIZE: min-qty
-                    let key = format!("{}-market-lot-size-min-qty", symbol.clone());
-                    let value = filter.market_lot_min_qty;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
-                {
-                    // MARKET_LOT_SIZE: max-qty
-                    let key = format!("{}-market-lot-size-max-qty", symbol.clone());
-                    let value = filter.market_lot_max_qty;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
-                {
-                    // MARKET_LOT_SIZE: step-size
-                    let key = format!("{}-market-lot-size-step-size", symbol.clone());
-                    let value = filter.market_lot_step_size;
-
-                    filters_map.write().unwrap().insert(key, value);
-                }
+                filters_map.write().unwrap().insert(key, value);
             }
-            //
-            // END: filters: []
-            //
+            {
+                // MIN_NOTIONAL: applytomarket
+                let key = format!("{}-min-notional-apply-to-market", symbol.clone());
+                let value = filter.notional_apply_to_market;
 
-            // valid_trading_symbols contains only legitimate (on Binance) symbols and these will
-            // be watched
-            valid_trading_symbols
-                .write()
-                .unwrap()
-                .insert(symbol.clone(), true);
+                filters_map.write().unwrap().insert(key, value);
+            }
+            {
+                // MIN_NOTIONAL: avgPriceMins
+                let key = format!("{}-min-notional-avg-price-mins", symbol.clone());
+                let value = filter.notional_avg_price_mins;
+
+                filters_map.write().unwrap().insert(key, value);
+            }
+            {
+                // MARKET_LOT_SIZE: min-qty
+                let key = format!("{}-market-lot-size-min-qty", symbol.clone());
+                let value = filter.market_lot_min_qty;
+
+                filters_map.write().unwrap().insert(key, value);
+            }
+            {
+                // MARKET_LOT_SIZE: max-qty
+                let key = format!("{}-market-lot-size-max-qty", symbol.clone());
+                let value = filter.market_lot_max_qty;
+
+                filters_map.write().unwrap().insert(key, value);
+            }
+            {
+                // MARKET_LOT_SIZE: step-size
+                let key = format!("{}-market-lot-size-step-size", symbol.clone());
+                let value = filter.market_lot_step_size;
+
+                filters_map.write().unwrap().insert(key, value);
+            }
         }
+        //
+        // END: filters: []
+        //
+
+        // valid_trading_symbols contains only legitimate (on Binance) symbols and these will
+        // be watched
+        valid_trading_symbols
+            .write()
+            .unwrap()
+            .insert(symbol.clone(), true);
     }
     ////////////////////////////////////////////////////////////////////////////////////////////
     // END: Iteration over exchangeInfo data (taking symbols)
@@ -290,5 +361,5 @@ pub async fn fetch_exchange_info(api: String) -> Option<String> {
 
 pub async fn randomly_select_api_address(list: Vec<String>) -> String {
     let mut rng = thread_rng();
-    return list.iter().choose_stable(&mut rng).unwrap().to_string();
+    list.iter().choose_stable(&mut rng).unwrap().to_string()
 }
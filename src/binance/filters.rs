@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::str::FromStr;
 
+/// Filters of one symbol parsed from exchangeInfo `filters` array.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct FiltersParser {
     pub data: Vec<Value>,
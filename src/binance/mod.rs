@@ -1,12 +1,17 @@
 pub mod api;
+pub mod depth_stream;
+pub mod endpoints;
 pub mod exchange_info;
-mod filters;
+pub mod filters;
 pub mod new;
 pub mod object;
 pub mod orderbook;
 pub mod prices;
+pub mod protection;
+pub mod record;
 pub mod start;
 pub mod state;
 pub mod symbols;
+pub mod symbols_list;
 pub mod symbols_monitor;
 pub mod trading;
@@ -1,16 +1,24 @@
-use std::collections::HashMap;
+use crate::binance::endpoints::{exchange_info_apis, rest_api_endpoint, ws_endpoint};
 use crate::binance::object::BinanceObj;
 use crate::binance::state::BinanceState;
-use crate::core::cli::determine_bot_trading_mode;
-use crate::core::types::{Symbol, SymbolAction, TradingMode};
-use crate::ConfigStruct;
-use log::info;
+use crate::config::reload::new_shared_config;
+use crate::config::settings::ConfigStruct;
+use crate::core::structs::StreamHealth;
+use crate::core::types::{ExchangeEnvironment, Symbol, SymbolAction, TradingMode};
+use log::{info, warn};
 use rust_decimal::Decimal;
+use std::collections::HashMap;
 use std::env;
 use std::sync::{Arc, Mutex, RwLock};
 
 impl BinanceObj {
-    pub async fn new(config: ConfigStruct) -> Self {
+    // real trading on Binance production has to be confirmed (|production_confirmed|), error if it's
+    // not or if API keys are missing
+    pub async fn new(
+        config: ConfigStruct,
+        trading_mode: TradingMode,
+        production_confirmed: bool,
+    ) -> Result<Self, String> {
         // this is exposed?
         let valid_trading_symbols: Arc<RwLock<HashMap<Symbol, bool>>> =
             Arc::new(RwLock::new(HashMap::new()));
@@ -30,42 +38,86 @@ impl BinanceObj {
             valid_trading_symbols.read().unwrap().len()
         );
 
-        let trading_mode = determine_bot_trading_mode();
+        show_exchange_environment(&config, &trading_mode);
+
+        if trading_mode == TradingMode::RealTrading
+            && config.exchange_environment == ExchangeEnvironment::Production
+            && !production_confirmed
+        {
+            return Err(
+                "real trading on Binance PRODUCTION requires \"run --mode real --confirm-production\""
+                    .to_string(),
+            );
+        }
         let mut api_key = String::from("");
         let mut secret_key = String::from("");
 
         if trading_mode == TradingMode::RealTrading {
             let name = "BOT_API_KEY";
             api_key = match env::var(name) {
-                Ok(v) => Some(v).unwrap(),
-                Err(e) => panic!(
-                    "env {} is not set ({}) - it's required to access Binance API",
-                    name, e
-                ),
+                Ok(v) => v,
+                Err(e) => {
+                    return Err(format!(
+                        "env {} is not set ({}) - it's required to access Binance API",
+                        name, e
+                    ))
+                }
             };
 
             let name = "BOT_SECRET_KEY";
             secret_key = match env::var(name) {
-                Ok(v) => Some(v).unwrap(),
-                Err(e) => panic!(
-                    "env {} is not set ({}) - it's required to access Binance API",
-                    name, e
-                ),
+                Ok(v) => v,
+                Err(e) => {
+                    return Err(format!(
+                        "env {} is not set ({}) - it's required to access Binance API",
+                        name, e
+                    ))
+                }
             };
         }
 
         info!("Binance object initialized.");
 
-        Self {
+        Ok(Self {
             state: Arc::new(Mutex::new(BinanceState {
-                config,
+                config: new_shared_config(config),
+                config_file: None,
                 valid_trading_symbols,
                 filters_map,
                 default_symbol_action: symbol_actions,
+                symbols_monitor_health: Arc::new(RwLock::new(StreamHealth::default())),
                 api_key,
                 secret_key,
                 trading_mode,
             })),
+        })
+    }
+
+    // config file watched for changes (hot reload) while the bot is running
+    pub fn watch_config_file(self, path: &str) -> Self {
+        self.state.lock().unwrap().config_file = Some(path.to_string());
+        self
+    }
+}
+
+// target environment has to be unmistakable in logs
+fn show_exchange_environment(config: &ConfigStruct, trading_mode: &TradingMode) {
+    let environment = &config.exchange_environment;
+    let line = format!("### ### EXCHANGE ENVIRONMENT: {environment} ### ###");
+
+    match environment {
+        ExchangeEnvironment::Production if *trading_mode == TradingMode::RealTrading => {
+            warn!("{line}");
+            warn!("### ### REAL TRADING WITH REAL MONEY ON BINANCE ### ###");
+            warn!("{line}");
         }
+        _ => info!("{line}"),
     }
+
+    info!("{environment}: REST API: {}", rest_api_endpoint(config));
+    info!("{environment}: websocket: {}", ws_endpoint(config));
+    info!(
+        "{environment}: exchangeInfo: {:?}",
+        exchange_info_apis(config)
+    );
 }
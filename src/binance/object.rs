@@ -1,7 +1,41 @@
+use crate::binance::prices::{process_symbol_price, process_symbol_qty};
 use crate::binance::state::BinanceState;
+use crate::core::types::Symbol;
+use crate::exchange::Exchange;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
+/// Binance exchange with shared state of the bot, [`BinanceObj::start`] runs all its parts.
 #[derive(Clone)]
 pub struct BinanceObj {
     pub state: Arc<Mutex<BinanceState>>,
 }
+
+impl Exchange for BinanceObj {
+    fn get_all_valid_symbols(&self) -> HashMap<Symbol, bool> {
+        let valid_trading_symbols = self.state.lock().unwrap().valid_trading_symbols.clone();
+        let symbols = valid_trading_symbols.read().unwrap().clone();
+        symbols
+    }
+
+    fn process_symbol_price_and_qty(
+        &self,
+        symbol: Symbol,
+        price: Decimal,
+        qty: Decimal,
+    ) -> (Decimal, Decimal) {
+        let filters_map = self.state.lock().unwrap().filters_map.clone();
+        let filters_map = filters_map.read().unwrap().clone();
+
+        // exchangeInfo doesn't have the symbol (or it's not fetched yet)
+        if !filters_map.contains_key(&format!("{symbol}-price-filter-tick-size")) {
+            return (Decimal::ZERO, Decimal::ZERO);
+        }
+
+        let qty = process_symbol_qty(symbol.clone(), qty, &filters_map).unwrap_or_default();
+        let price = process_symbol_price(symbol, price, filters_map).unwrap_or_default();
+
+        (price, qty)
+    }
+}
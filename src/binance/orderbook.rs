@@ -1,16 +1,25 @@
+use crate::binance::depth_stream::{DepthStream, DepthStreamRead};
+use crate::binance::endpoints::binance_api_config;
 use crate::binance::prices::{process_symbol_price, process_symbol_qty};
+use crate::binance::protection::{Protection, ReleaseResult};
+use crate::binance::record::symbol_assets;
 use crate::binance::trading::{reverse_symbol_action, symbol_buy_or_sell};
-use crate::config::settings::ConfigStruct;
-use crate::core::calc::{calculate_exit_qty, percent_diff};
-use crate::core::structs::OrderBookCommand;
+use crate::config::reload::{SharedConfig, SymbolConfigs};
+use crate::core::calc::{calculate_exit_qty, can_exit_position, percent_diff};
+use crate::core::liquidity::check_liquidity;
+use crate::core::order_book::{BookSide, BookUpdate, LocalOrderBook};
+use crate::core::position::{OrderFill, Position};
+use crate::core::structs::{OrderBookCommand, TradeCandidate};
 use crate::core::trading::{check_current_profit_percent, TradingSymbol};
 use crate::core::types::{
-    CurrentTradingProfit, OrderBookCmd, ReadMarketDepthNow, Symbol, SymbolAction, TradingDecision,
-    TradingMode, TradingNextStep,
+    CurrentTradingProfit, ExitReason, OrderBookCmd, ReadMarketDepthNow, Symbol, SymbolAction,
+    TradingDecision, TradingMode, TradingNextStep,
 };
+use binance::account::Account;
 use binance::api::Binance;
 use binance::market::Market;
-use binance::websockets::{WebSockets, WebsocketEvent};
+use binance::websockets::WebsocketEvent;
+use chrono::Utc;
 use log::{debug, error, info, warn};
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::{Decimal, RoundingStrategy};
@@ -19,28 +28,104 @@ use std::collections::HashMap;
 use std::ops::Not;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, Sender};
+use std::time::Duration;
 use tokio::time::Instant;
 
-#[allow(clippy::too_many_arguments)]
+// levels of REST snapshot, diffs can add levels beyond it later
+const ORDER_BOOK_SNAPSHOT_DEPTH: u64 = 1000;
+
+// levels of REST snapshots polled while depth stream is down
+const REST_FALLBACK_DEPTH: u64 = 100;
+// depth stream read waits at most this long, so stale stream is noticed
+const DEPTH_STREAM_READ_TIMEOUT_MS: u64 = 500;
+// trade which was not entered yet is given up when depth stream is down this long (or shorter
+// |depth_stream_stale_exit_secs|) - it keeps the pool otherwise
+const PENDING_ENTRY_STREAM_DOWN_MAX_SECS: u64 = 60;
+
+// (price, qty) levels from exchange format
+fn book_levels(levels: impl Iterator<Item = (f64, f64)>) -> Vec<(Decimal, Decimal)> {
+    levels
+        .filter_map(|(price, qty)| Some((Decimal::from_f64(price)?, Decimal::from_f64(qty)?)))
+        .collect()
+}
+
+/*
+Local order book - snapshot + diffs from the depth stream, resync after missed diff. REST snapshot polled
+while the stream is down (WebsocketEvent::OrderBook) replaces the book, next stream diff continues from it
+or takes a new snapshot. None for other events.
+ */
+fn update_local_order_book(
+    market: &Market,
+    symbol: &Symbol,
+    order_book: &mut LocalOrderBook,
+    event: WebsocketEvent,
+) -> Option<BookUpdate> {
+    match event {
+        WebsocketEvent::DepthOrderBook(depth_order_book) => {
+            if !order_book.is_synced() {
+                info!("snapshoting orderbook for {symbol}...");
+                match market.get_custom_depth(symbol.to_string(), ORDER_BOOK_SNAPSHOT_DEPTH) {
+                    Ok(answer) => order_book.apply_snapshot(
+                        answer.last_update_id,
+                        &book_levels(answer.bids.iter().map(|b| (b.price, b.qty))),
+                        &book_levels(answer.asks.iter().map(|a| (a.price, a.qty))),
+                    ),
+                    Err(e) => error!("{e:?}"),
+                }
+            }
+
+            let book_update = order_book.apply_diff(
+                depth_order_book.first_update_id,
+                depth_order_book.final_update_id,
+                &book_levels(depth_order_book.bids.iter().map(|b| (b.price, b.qty))),
+                &book_levels(depth_order_book.asks.iter().map(|a| (a.price, a.qty))),
+            );
+
+            if let BookUpdate::ResyncNeeded(reason) = &book_update {
+                warn!("{symbol}: order book out of sync ({reason}) - taking new snapshot");
+            }
+
+            Some(book_update)
+        }
+        WebsocketEvent::OrderBook(snapshot) => {
+            order_book.apply_snapshot(
+                snapshot.last_update_id,
+                &book_levels(snapshot.bids.iter().map(|b| (b.price, b.qty))),
+                &book_levels(snapshot.asks.iter().map(|a| (a.price, a.qty))),
+            );
+
+            Some(BookUpdate::Applied)
+        }
+        _ => None,
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::result_large_err)]
 pub fn orderbook_executor(
-    config: ConfigStruct,
-    symbol: Symbol,
+    shared_config: SharedConfig,
+    candidate: TradeCandidate,
     filters_map: HashMap<String, Decimal>,
     symbol_actions: HashMap<Symbol, SymbolAction>,
     my_starting_qty: Decimal,
-    monitored_price: Decimal,
     orderbook_cmd: Receiver<OrderBookCommand>,
     driving_signal_out: Sender<TradingSymbol>,
     api_keys: (String, String),
     trading_mode: TradingMode,
 ) {
+    let symbol = candidate.symbol.clone();
+    let monitored_price = candidate.price;
+
     info!("=> starting websocket for: {symbol}");
-    let (api_key, secret_key) = api_keys.clone();
-    let endpoints =
-        [symbol.clone()].map(|symbol| format!("{}@depth@100ms", symbol.to_string().to_lowercase()));
+    let endpoint = format!("{}@depth@100ms", symbol.to_string().to_lowercase());
 
+    // REST API and websocket addresses require restart, so they are taken only once
+    let api_config = binance_api_config(&shared_config.read().unwrap());
     let (api_key, secret_key) = api_keys.clone();
-    let market: Market = Binance::new(Some(api_key.clone()), Some(secret_key.clone()));
+    let market: Market =
+        Binance::new_with_config(Some(api_key.clone()), Some(secret_key.clone()), &api_config);
+    // protective OCO orders (|exchange_protection_enabled|)
+    let account: Account =
+        Binance::new_with_config(Some(api_key.clone()), Some(secret_key.clone()), &api_config);
 
     let decimal_zero = Decimal::ZERO;
 
@@ -53,9 +138,16 @@ pub fn orderbook_executor(
 
     let mut qty_wanted_to_buy = decimal_zero;
     let mut best_price_now = decimal_zero;
+    let mut protection = Protection::default();
 
     info!("trading request, symbol: {symbol}, qty: {my_starting_qty}");
 
+    let (base_asset, quote_asset) = symbol_assets(
+        &shared_config.read().unwrap(),
+        &symbol.0,
+        symbol_actions.get(&symbol).unwrap(),
+    );
+
     // STATE
     let mut trading_symbol = TradingSymbol {
         symbol: symbol.clone(),
@@ -67,6 +159,7 @@ pub fn orderbook_executor(
         good_profit_price: decimal_zero,
         absolute_minimal_profit_percent: decimal_zero,
         trading_started: Instant::now(),
+        trading_started_at: Utc::now(),
         highest_price_since_min_profit: decimal_zero,
         highest_price_since_good_profit: decimal_zero,
         last_best_price: decimal_zero,
@@ -82,994 +175,1430 @@ pub fn orderbook_executor(
         started_qty: my_starting_qty,
         used_qty: decimal_zero,
         monitored_price,
+        entry_price: decimal_zero,
+        entry_qty: decimal_zero,
+        peak_profit_percent: decimal_zero,
+        entry_signal: candidate.entry_signal,
+        exit_reason: ExitReason::NotEntered,
+        exit_limit_price: decimal_zero,
+        position: Position::new(&base_asset, &quote_asset),
     };
 
-    trading_symbol.absolute_minimal_profit_percent = config.orderbook_monitor.exchange_comission
+    // resolved again only when config is reloaded
+    let mut symbol_configs = SymbolConfigs::new(&shared_config);
+    // the same for depth stream handling - symbol_configs belongs to the book handler
+    let mut stream_configs = SymbolConfigs::new(&shared_config);
+    let config = symbol_configs.for_symbol(&symbol.to_string());
+    for (name, _) in config.overrides_for_symbol(&symbol.to_string()) {
+        info!("{symbol}: using config overrides from [{name}]");
+    }
+
+    trading_symbol.absolute_minimal_profit_percent = config.orderbook_monitor.comission_percent()
         + config
             .orderbook_monitor
             .absolute_minimal_profit_over_comission;
 
     let final_trade_decision = Cell::new(TradingDecision::Decline);
+    // exit reason when leaving is requested - by the engine (cmd file) or by the stale stream watchdog
+    let mut requested_exit_reason = ExitReason::Requested;
+    // liquidity is waited for |liquidity_wait_secs| since now
+    let liquidity_wait_started = Instant::now();
+    let mut liquidity_wait_logged = false;
+    // depth stream is down longer than |depth_stream_stale_exit_secs|
+    let stream_stale_exit = Cell::new(false);
+    let keep_running = AtomicBool::new(true);
+    // the last diff from depth stream (REST fallback doesn't count)
+    let mut last_stream_update = Instant::now();
+    // stream reconnect backoff, doubled after every failed connection
+    let mut reconnect_backoff_ms = config.orderbook_monitor.depth_stream_reconnect_backoff_ms;
 
     loop {
-        let mut last_update_id = 0;
-        let mut snapshot_taken = false;
-        let mut listening_for_orderbook_updates = false;
+        // snapshot is taken again after reconnect
+        let mut order_book = LocalOrderBook::default();
+        // consecutive updates which passed pre-entry liquidity checks
+        let mut liquidity_checks_passed = 0;
         // let mut trading_started = Instant::now();
         let mut reading_market_depth_this_time = ReadMarketDepthNow::YES;
-        let keep_running = AtomicBool::new(true);
-        let final_trade_decision_clone = final_trade_decision.clone();
+        let final_trade_decision_clone = &final_trade_decision;
 
-        let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
-            if !snapshot_taken {
-                info!("snapshoting orderbook for {symbol}...");
-                match market.get_depth(symbol.to_string()) {
-                    Ok(answer) => last_update_id = answer.last_update_id,
-                    Err(e) => error!("{e:?}"),
-                }
-                snapshot_taken = true
-            }
+        // depth stream diffs and REST snapshots (polled while the stream is down) go here
+        let mut handle_book_event = |event: WebsocketEvent| {
+            let from_rest = matches!(event, WebsocketEvent::OrderBook(_));
 
-            if let WebsocketEvent::DepthOrderBook(depth_order_book) = event {
-                if listening_for_orderbook_updates {
-                    //
-                    if depth_order_book.first_update_id == last_update_id + 1 {
-                        ///////////////////////////////////////////////////////////////////////////////
-                        ///////////////////////// BEGIN: TRADING LOGIC HERE ///////////////////////////
-                        ///////////////////////////////////////////////////////////////////////////////
-                        last_update_id = depth_order_book.final_update_id;
+            if let Some(book_update) =
+                update_local_order_book(&market, &symbol, &mut order_book, event)
+            {
+                if book_update == BookUpdate::Applied {
+                    ///////////////////////////////////////////////////////////////////////////////
+                    ///////////////////////// BEGIN: TRADING LOGIC HERE ///////////////////////////
+                    ///////////////////////////////////////////////////////////////////////////////
+
+                    // current config - it might be reloaded since last update
+                    symbol_configs.refresh(&shared_config);
+                    let config = symbol_configs.for_symbol(&symbol.to_string());
 
-                        // default values
-                        let mut best_ask_price = decimal_zero;
-                        let mut best_ask_qty = decimal_zero;
+                    // default values
+                    let mut best_ask_price = decimal_zero;
+                    let mut best_ask_qty = decimal_zero;
 
+                    //
+                    if let Ok(data) = orderbook_cmd.try_recv() {
                         //
-                        if let Ok(data) = orderbook_cmd.try_recv() {
-                            //
-                            if data.cmd == OrderBookCmd::StopAndInstantSell {
-                                // stop and instant sell everything at current price (limit order)
-                                warn!("{symbol} received StopAndInstantSell command");
-                                finishing_action_requested = true;
-                            }
+                        if data.cmd == OrderBookCmd::StopAndInstantSell {
+                            // stop and instant sell everything at current price (|requested_exit_order_type|)
+                            warn!("{symbol} received StopAndInstantSell command");
+                            finishing_action_requested = true;
                         }
+                    }
 
-                        if finishing_action_requested {
-                            finish_trading_for_symbol_now = true;
-                        }
+                    //
+                    // BEGIN: depth stream is down - REST snapshots only keep the open position managed
+                    //
+                    if from_rest && trading_symbol.trading_next_step == TradingNextStep::Join {
+                        // no entry without the stream (pending entry is given up after the REST fallback)
+                        return;
+                    }
 
-                        if trading_symbol.trading_next_step == TradingNextStep::Join
-                            && !finishing_action_requested
-                        {
-                            // only analyse bids if we want to enter
-                            for ask in depth_order_book.asks {
-                                // first price is the best if we want to buy
-                                let ask_price = Decimal::from_f64(ask.price).unwrap();
-                                let ask_qty = Decimal::from_f64(ask.qty).unwrap();
-
-                                // binance algorithm: this removes position from orderbook
-                                if ask_qty == decimal_zero || ask_price == decimal_zero {
-                                    continue;
-                                }
+                    if stream_stale_exit.get()
+                        && !finishing_action_requested
+                        && trading_symbol.trading_next_step == TradingNextStep::Leave
+                    {
+                        warn!(
+                            "{symbol} depth stream is down longer than {}s - leaving the trade",
+                            config.orderbook_monitor.depth_stream_stale_exit_secs
+                        );
+                        finishing_action_requested = true;
+                        requested_exit_reason = ExitReason::StreamStale;
+                    }
+                    //
+                    // END: depth stream is down
+                    //
 
-                                if best_ask_price == decimal_zero {
-                                    // ask should be considered from lowest (best) to highest (worst)
-                                    // first price is is the best
-                                    best_ask_price = ask_price;
-                                    best_ask_qty = ask_qty;
-                                }
+                    if finishing_action_requested {
+                        finish_trading_for_symbol_now = true;
+                    }
 
-                                if best_ask_qty >= trading_symbol.qty {
-                                    qty_wanted_to_buy = trading_symbol.qty / best_ask_price;
+                    //
+                    // BEGIN: liquidity gate - the book has to pass liquidity checks for
+                    // |acceptable_liquidity_count| consecutive updates before we buy
+                    //
+                    let liquidity_checks_required =
+                        config.orderbook_monitor.acceptable_liquidity_count;
+
+                    if trading_symbol.trading_next_step == TradingNextStep::Join
+                        && !finishing_action_requested
+                        && liquidity_checks_passed < liquidity_checks_required
+                    {
+                        match check_liquidity(&config, &order_book, trading_symbol.qty) {
+                            Ok(liquidity) => {
+                                liquidity_checks_passed += 1;
+
+                                if liquidity_checks_passed == liquidity_checks_required {
+                                    info!("{symbol} LIQUIDITY OK after {liquidity_checks_passed} updates: {liquidity:?}");
+                                }
+                            }
+                            Err(reason) => {
+                                // consecutive updates are counted again
+                                liquidity_checks_passed = 0;
 
-                                    let result = process_symbol_qty(
-                                        symbol.clone(),
-                                        qty_wanted_to_buy,
-                                        &trading_symbol.filters_map,
-                                    );
+                                let liquidity_wait_secs =
+                                    config.orderbook_monitor.liquidity_wait_secs;
+                                if liquidity_wait_started.elapsed().as_secs() >= liquidity_wait_secs
+                                {
+                                    trading_symbol.trade_decision = TradingDecision::Decline;
+                                    trading_symbol.exit_reason = ExitReason::LiquidityRejected;
+                                    final_trade_decision_clone.set(TradingDecision::Decline);
+                                    warn!("{symbol} LIQUIDITY REJECTED: {reason}");
+                                } else if !liquidity_wait_logged {
+                                    liquidity_wait_logged = true;
+                                    info!("{symbol} liquidity check failed: {reason} - waiting for liquidity up to {liquidity_wait_secs}s");
+                                }
+                            }
+                        }
+                    }
+                    //
+                    // END: liquidity gate
+                    //
 
-                                    if let Some(val) = result {
-                                        let s = trading_symbol.qty;
-                                        info!("{symbol} --> starting_qty: {my_starting_qty}, price: {ask_price}, in struct: {s}. qty_wanted_to_buy: {qty_wanted_to_buy}, val: {val}");
-                                        qty_wanted_to_buy = val;
-                                        best_ask_price = ask_price;
-                                        best_ask_qty = ask_qty;
+                    if trading_symbol.trading_next_step == TradingNextStep::Join
+                        && !finishing_action_requested
+                        && liquidity_checks_passed >= liquidity_checks_required
+                        && trading_symbol.trade_decision != TradingDecision::Decline
+                    {
+                        // only analyse bids if we want to enter
+                        for (ask_price, ask_qty) in order_book.levels(BookSide::Asks) {
+                            // first price is the best if we want to buy
+
+                            if best_ask_price == decimal_zero {
+                                // ask should be considered from lowest (best) to highest (worst)
+                                // first price is is the best
+                                best_ask_price = ask_price;
+                                best_ask_qty = ask_qty;
+                            }
 
-                                        // BEGIN: prevent buy when price diff is too large in comparison with monitor
-                                        let price_diff_from_monitor =
-                                            percent_diff(monitored_price, best_ask_price).abs();
+                            if best_ask_qty >= trading_symbol.qty {
+                                qty_wanted_to_buy = trading_symbol.qty / best_ask_price;
 
-                                        if price_diff_from_monitor
-                                            >= config
-                                                .orderbook_monitor
-                                                .allowed_buy_diff_from_symbol_monitor_percent
-                                        {
-                                            trading_symbol.trade_decision =
-                                                TradingDecision::Decline;
-                                            final_trade_decision_clone
-                                                .set(TradingDecision::Decline);
-                                            warn!("{symbol} SPREAD REJECTED: price for buy: {best_ask_price}, price from monitor: {monitored_price}, spread: {price_diff_from_monitor}");
-                                            break;
-                                        }
+                                let result = process_symbol_qty(
+                                    symbol.clone(),
+                                    qty_wanted_to_buy,
+                                    &trading_symbol.filters_map,
+                                );
 
-                                        if trading_symbol.trade_decision != TradingDecision::Decline
-                                        {
-                                            // ***WARN:*** field modification
-                                            trading_symbol.qty = qty_wanted_to_buy;
-                                            // ***WARN:*** field modification
-                                            trading_symbol.price = best_ask_price;
-                                            // ***WARN:*** field modification
-                                            trading_symbol.trade_decision = TradingDecision::Start;
+                                if let Some(val) = result {
+                                    let s = trading_symbol.qty;
+                                    info!("{symbol} --> starting_qty: {my_starting_qty}, price: {ask_price}, in struct: {s}. qty_wanted_to_buy: {qty_wanted_to_buy}, val: {val}");
+                                    qty_wanted_to_buy = val;
+                                    best_ask_price = ask_price;
+                                    best_ask_qty = ask_qty;
 
-                                            final_trade_decision_clone.set(TradingDecision::Start);
+                                    // BEGIN: prevent buy when price diff is too large in comparison with monitor
+                                    let price_diff_from_monitor =
+                                        percent_diff(monitored_price, best_ask_price).abs();
 
-                                            // critical - first found then we left
-                                            break;
-                                        }
-                                        // END: prevent buy when price diff is too large in comparison with monitor
+                                    if price_diff_from_monitor
+                                        >= config
+                                            .orderbook_monitor
+                                            .allowed_buy_diff_from_symbol_monitor_percent
+                                    {
+                                        trading_symbol.trade_decision = TradingDecision::Decline;
+                                        trading_symbol.exit_reason = ExitReason::SpreadRejected;
+                                        final_trade_decision_clone.set(TradingDecision::Decline);
+                                        warn!("{symbol} SPREAD REJECTED: price for buy: {best_ask_price}, price from monitor: {monitored_price}, spread: {price_diff_from_monitor}");
+                                        break;
                                     }
-                                }
-                            }
-                        }
-
-                        // ask map contains now current asks from lowest to highest - normal iterator is needed
-
-                        let mut best_bid_price = decimal_zero;
-                        let mut best_bid_qty = decimal_zero;
 
-                        if trading_symbol.trading_next_step == TradingNextStep::Leave {
-                            // only if we want to leave
-                            for bids in depth_order_book.bids.iter() {
-                                // bids are from highest (best) to lowest (worst)
-                                let bid_price = Decimal::from_f64(bids.price).unwrap();
-                                let bid_qty = Decimal::from_f64(bids.qty).unwrap();
+                                    if trading_symbol.trade_decision != TradingDecision::Decline {
+                                        // ***WARN:*** field modification
+                                        trading_symbol.qty = qty_wanted_to_buy;
+                                        // ***WARN:*** field modification
+                                        trading_symbol.price = best_ask_price;
+                                        // ***WARN:*** field modification
+                                        trading_symbol.trade_decision = TradingDecision::Start;
 
-                                // bids should be consider from highest (best) to lowest (worst), so our best price is the last
-                                if bid_price == best_price_now {
-                                    //
-                                    if bid_qty == decimal_zero {
-                                        // https://github.com/binance/binance-spot-api-docs/blob/master/web-socket-streams.md
-                                        // point 8 - remove price level
-                                        best_price_now = decimal_zero;
-                                        continue;
-                                    }
-                                }
+                                        final_trade_decision_clone.set(TradingDecision::Start);
 
-                                if bid_qty >= trading_symbol.qty {
-                                    //
-                                    if bid_price > best_price_now {
-                                        // best price init or reset
-                                        best_price_now = bid_price;
+                                        // critical - first found then we left
+                                        break;
                                     }
-                                    best_bid_price = bid_price;
-                                    best_bid_qty = bid_qty;
-                                    break;
+                                    // END: prevent buy when price diff is too large in comparison with monitor
                                 }
                             }
                         }
-                        //
-                        //
-                        //
+                    }
 
-                        debug!(
-                            "{symbol} NOW: best_bid_price: [{best_bid_price}], \
-                        best_bid_qty: [{best_bid_qty}], \
-                         trade_decision: [{:?}], trading_next_step: [{:?}]",
-                            trading_symbol.trade_decision, trading_symbol.trading_next_step
-                        );
+                    // ask map contains now current asks from lowest to highest - normal iterator is needed
+
+                    let mut best_bid_price = decimal_zero;
+                    let mut best_bid_qty = decimal_zero;
+
+                    if trading_symbol.trading_next_step == TradingNextStep::Leave {
+                        // only if we want to leave
+                        // our whole qty is sold to as many bid levels as needed - profit is checked
+                        // with the average price we would really get and the order is placed at
+                        // the worst level, so it takes all of them
+                        match (
+                            order_book.fill(BookSide::Bids, trading_symbol.qty),
+                            order_book.best_bid(),
+                        ) {
+                            (Some(fill), Some((bid_price, bid_qty))) => {
+                                best_price_now = fill
+                                    .avg_price
+                                    .round_dp_with_strategy(8, RoundingStrategy::ToZero);
+                                best_bid_price = bid_price;
+                                best_bid_qty = bid_qty;
+                                // ***WARN:*** field modification
+                                trading_symbol.exit_limit_price = fill.limit_price;
+                            }
+                            _ => {
+                                debug!("{symbol} bids can't take my qty: {}", trading_symbol.qty);
+                            }
+                        }
+                    }
+                    //
+                    //
+                    //
 
-                        //
-                        // BEGIN: starting trading consideration
-                        //
-                        if trading_symbol.trade_decision == TradingDecision::Start
-                            || trading_symbol.trade_decision == TradingDecision::Continue
-                        {
-                            // first step
+                    debug!(
+                        "{symbol} NOW: best_bid_price: [{best_bid_price}], \
+                    best_bid_qty: [{best_bid_qty}], \
+                     trade_decision: [{:?}], trading_next_step: [{:?}]",
+                        trading_symbol.trade_decision, trading_symbol.trading_next_step
+                    );
 
-                            if trading_symbol.trading_next_step == TradingNextStep::Join {
-                                // checking trading possibility
-                                //
-                                // we can start the trade
-                                //
-                                if qty_wanted_to_buy > decimal_zero {
-                                    // unpack the values - there were already calculated
-                                    let my_current_qty = trading_symbol.qty;
-                                    let my_current_qty_price = trading_symbol.price;
+                    //
+                    // BEGIN: starting trading consideration
+                    //
+                    if trading_symbol.trade_decision == TradingDecision::Start
+                        || trading_symbol.trade_decision == TradingDecision::Continue
+                    {
+                        // first step
 
-                                    // log
-                                    info!("{symbol} after first step: my_current_qty: {my_current_qty}, my_current_qty_price: {my_current_qty_price}");
+                        if trading_symbol.trading_next_step == TradingNextStep::Join {
+                            // checking trading possibility
+                            //
+                            // we can start the trade
+                            //
+                            if qty_wanted_to_buy > decimal_zero {
+                                // unpack the values - there were already calculated
+                                let my_current_qty = trading_symbol.qty;
+                                let my_current_qty_price = trading_symbol.price;
+
+                                // log
+                                info!("{symbol} after first step: my_current_qty: {my_current_qty}, my_current_qty_price: {my_current_qty_price}");
+
+                                // change percent to usable number
+                                let min_percent = config.orderbook_monitor.min_profit_percent
+                                    / Decimal::ONE_HUNDRED;
+
+                                // setting min profit price
+                                // ***WARN:*** field modification
+                                trading_symbol.min_profit_price =
+                                    trading_symbol.price + (trading_symbol.price * min_percent);
+
+                                // None means we can't use this price so stop processing this
+                                // ***WARN:*** field modification
+                                trading_symbol.min_profit_price = process_symbol_price(
+                                    trading_symbol.symbol.clone(),
+                                    trading_symbol.min_profit_price,
+                                    trading_symbol.filters_map.clone(),
+                                )
+                                .unwrap_or(decimal_zero);
+
+                                // change percent to usable number
+                                let good_percent = config.orderbook_monitor.good_profit_percent
+                                    / Decimal::ONE_HUNDRED;
+
+                                // setting good profit price
+                                // ***WARN:*** field modification
+                                trading_symbol.good_profit_price =
+                                    trading_symbol.price + (trading_symbol.price * good_percent);
+
+                                // "None" here means we can't use this price so stop trading this pair
+                                // ***WARN:*** field modification
+                                trading_symbol.good_profit_price = match process_symbol_price(
+                                    trading_symbol.symbol.clone(),
+                                    trading_symbol.good_profit_price,
+                                    trading_symbol.filters_map.clone(),
+                                ) {
+                                    Some(v) => {
+                                        // ***WARN:*** field modification
+                                        trading_symbol.trade_decision = TradingDecision::Continue;
 
-                                    // change percent to usable number
-                                    let min_percent = config.orderbook_monitor.min_profit_percent
-                                        / Decimal::ONE_HUNDRED;
+                                        v
+                                    }
+                                    None => {
+                                        // ***WARN:*** field modification
+                                        trading_symbol.trade_decision = TradingDecision::Stop;
+                                        // ***WARN:*** field modification
+                                        trading_symbol.exit_reason =
+                                            ExitReason::PriceFilterRejected;
+                                        decimal_zero
+                                    }
+                                };
 
-                                    // setting min profit price
-                                    // ***WARN:*** field modification
-                                    trading_symbol.min_profit_price =
-                                        trading_symbol.price + (trading_symbol.price * min_percent);
+                                {
+                                    let min_profit_price = trading_symbol.min_profit_price;
+                                    let good_profit_price = trading_symbol.good_profit_price;
+                                    info!("{symbol} after first step: min_profit_price: {min_profit_price}, good_profit_price: {good_profit_price}");
+                                }
 
-                                    // None means we can't use this price so stop processing this
-                                    // ***WARN:*** field modification
-                                    trading_symbol.min_profit_price = process_symbol_price(
-                                        trading_symbol.symbol.clone(),
-                                        trading_symbol.min_profit_price,
-                                        trading_symbol.filters_map.clone(),
-                                    )
-                                    .unwrap_or_else(|| decimal_zero);
-
-                                    // change percent to usable number
-                                    let good_percent = config.orderbook_monitor.good_profit_percent
-                                        / Decimal::ONE_HUNDRED;
-
-                                    // setting good profit price
-                                    // ***WARN:*** field modification
-                                    trading_symbol.good_profit_price = trading_symbol.price
-                                        + (trading_symbol.price * good_percent);
+                                final_trade_decision_clone.set(trading_symbol.trade_decision);
 
-                                    // "None" here means we can't use this price so stop trading this pair
-                                    // ***WARN:*** field modification
-                                    trading_symbol.good_profit_price = match process_symbol_price(
-                                        trading_symbol.symbol.clone(),
-                                        trading_symbol.good_profit_price,
-                                        trading_symbol.filters_map.clone(),
+                                if trading_symbol.trade_decision == TradingDecision::Continue {
+                                    //
+                                    // BEGIN: we are trying to buy asset, that may fail if the price moves too quickly
+                                    //
+                                    let (a, b) = api_keys.clone();
+                                    let fill = match symbol_buy_or_sell(
+                                        &config,
+                                        &trading_mode,
+                                        &trading_symbol,
+                                        (a, b),
                                     ) {
-                                        Some(v) => {
-                                            // ***WARN:*** field modification
-                                            trading_symbol.trade_decision =
-                                                TradingDecision::Continue;
-
-                                            v
-                                        }
-                                        None => {
-                                            // ***WARN:*** field modification
-                                            trading_symbol.trade_decision = TradingDecision::Stop;
-                                            decimal_zero
+                                        Ok(fill) => fill,
+                                        // the order was not placed - order which was placed is followed until
+                                        // its state is known (see limit_buy_order())
+                                        Err(e) => {
+                                            error!("{symbol}: buy failed: {e}");
+                                            OrderFill::default()
                                         }
                                     };
 
-                                    {
-                                        let min_profit_price = trading_symbol.min_profit_price;
-                                        let good_profit_price = trading_symbol.good_profit_price;
-                                        info!("{symbol} after first step: min_profit_price: {min_profit_price}, good_profit_price: {good_profit_price}");
-                                    }
+                                    // ***WARN:*** field modification
+                                    trading_symbol.position.apply_buy(
+                                        &fill,
+                                        config.orderbook_monitor.comission_percent()
+                                            / Decimal::ONE_HUNDRED,
+                                    );
+
+                                    // check if it was done
+                                    if fill.executed_qty == decimal_zero {
+                                        // we could not enter to trade
 
-                                    final_trade_decision_clone.set(trading_symbol.trade_decision);
+                                        // ***WARN:*** field modification
+                                        trading_symbol.trade_decision = TradingDecision::Stop;
+                                        // ***WARN:*** field modification
+                                        trading_symbol.exit_reason = ExitReason::BuyNotFilled;
+                                        final_trade_decision_clone.set(TradingDecision::Stop);
+                                    }
+                                    //
+                                    // END: we are trying to buy asset, that may fail if the price moves too quickly
+                                    //
 
                                     if trading_symbol.trade_decision == TradingDecision::Continue {
-                                        //
-                                        // BEGIN: we are trying to buy asset, that may fail if the price moves too quickly
-                                        //
-                                        let (a, b) = api_keys.clone();
-                                        let (received_qty, used_qty) = symbol_buy_or_sell(
-                                            &config,
-                                            &trading_mode,
+                                        // partially filled buy is a smaller position, we
+                                        // trade what we hold (fees are taken from it)
+                                        // ***WARN:*** field modification
+                                        trading_symbol.qty = trading_symbol.position.held_qty();
+                                    }
+
+                                    // partial buy can leave qty which can't be sold (LOT_SIZE,
+                                    // MIN_NOTIONAL) - it's recorded as dust, there is nothing to trade
+                                    if trading_symbol.trade_decision == TradingDecision::Continue
+                                        && !can_exit_position(
                                             &trading_symbol,
-                                            (a, b),
+                                            trading_symbol.position.avg_entry_price(),
                                         )
-                                        .unwrap();
+                                    {
+                                        // ***WARN:*** field modification
+                                        trading_symbol.used_qty =
+                                            trading_symbol.position.spent_quote;
+                                        trading_symbol.entry_qty =
+                                            trading_symbol.position.bought_qty;
+                                        trading_symbol.entry_price =
+                                            trading_symbol.position.avg_entry_price();
+                                        trading_symbol.trading_started_at = Utc::now();
+                                        trading_symbol.close_as_dust();
+                                        final_trade_decision_clone.set(TradingDecision::Stop);
+
+                                        warn!("{symbol}: bought qty can't be sold (LOT_SIZE/MIN_NOTIONAL), kept as dust: {} {}",
+                                            trading_symbol.position.dust_qty, trading_symbol.position.base_asset);
+                                    }
+
+                                    if trading_symbol.trade_decision == TradingDecision::Continue {
+                                        // we successfully enter to trade
 
-                                        // check if it was done
-                                        if received_qty == decimal_zero {
-                                            // we could not enter to trade
+                                        // ***WARN:*** field modification
+                                        trading_symbol.used_qty =
+                                            trading_symbol.position.spent_quote;
+                                        trading_symbol.entry_qty =
+                                            trading_symbol.position.bought_qty;
+                                        trading_symbol.entry_price =
+                                            trading_symbol.position.avg_entry_price();
+
+                                        // entry was more expensive (chased or market buy) - profit
+                                        // prices follow the real entry price
+                                        if trading_symbol.entry_price > trading_symbol.price {
+                                            let ratio =
+                                                trading_symbol.entry_price / trading_symbol.price;
 
                                             // ***WARN:*** field modification
-                                            trading_symbol.trade_decision = TradingDecision::Stop;
-                                            final_trade_decision_clone.set(TradingDecision::Stop);
+                                            trading_symbol.min_profit_price = process_symbol_price(
+                                                trading_symbol.symbol.clone(),
+                                                trading_symbol.min_profit_price * ratio,
+                                                trading_symbol.filters_map.clone(),
+                                            )
+                                            .unwrap_or(trading_symbol.min_profit_price);
+                                            // ***WARN:*** field modification
+                                            trading_symbol.good_profit_price =
+                                                process_symbol_price(
+                                                    trading_symbol.symbol.clone(),
+                                                    trading_symbol.good_profit_price * ratio,
+                                                    trading_symbol.filters_map.clone(),
+                                                )
+                                                .unwrap_or(trading_symbol.good_profit_price);
+                                            // ***WARN:*** field modification
+                                            trading_symbol.price = trading_symbol.entry_price;
                                         }
-                                        //
-                                        // END: we are trying to buy asset, that may fail if the price moves too quickly
-                                        //
 
-                                        if trading_symbol.trade_decision
-                                            == TradingDecision::Continue
-                                        {
-                                            // we successfully enter to trade
-
-                                            // ***WARN:*** field modification
-                                            trading_symbol.qty = received_qty;
-                                            trading_symbol.used_qty = used_qty;
-
-                                            let my_current_qty_price = trading_symbol.price;
-                                            let my_current_qty = trading_symbol.qty;
-                                            let min_profit_price = trading_symbol.min_profit_price;
-                                            let good_profit_price =
-                                                trading_symbol.good_profit_price;
-                                            info!(
-                                        "{symbol} JOINED to TRADE: my_current_qty_price: {my_current_qty_price}, \
-                                my_current_qty (received): {my_current_qty}, min_profit_price: {min_profit_price}, \
-                                good_profit_price: {good_profit_price}"
-                                    );
+                                        let my_current_qty_price = trading_symbol.price;
+                                        let my_current_qty = trading_symbol.qty;
+                                        let min_profit_price = trading_symbol.min_profit_price;
+                                        let good_profit_price = trading_symbol.good_profit_price;
+                                        info!(
+                                    "{symbol} JOINED to TRADE: my_current_qty_price: {my_current_qty_price}, \
+                            my_current_qty (received): {my_current_qty}, min_profit_price: {min_profit_price}, \
+                            good_profit_price: {good_profit_price}"
+                                );
 
-                                            // reverse symbol action for next action
-                                            // ***WARN:*** field modification
-                                            trading_symbol.current_symbol_action =
-                                                reverse_symbol_action(
-                                                    trading_symbol.current_symbol_action.clone(),
-                                                );
+                                        // reverse symbol action for next action
+                                        // ***WARN:*** field modification
+                                        trading_symbol.current_symbol_action =
+                                            reverse_symbol_action(
+                                                trading_symbol.current_symbol_action.clone(),
+                                            );
 
-                                            // we are entering to trade so we have to set some vars
-                                            // ***WARN:*** field modification
-                                            trading_symbol.trading_next_step =
-                                                TradingNextStep::Leave;
-                                            // ***WARN:*** field modification
-                                            trading_symbol.trading_started = Instant::now();
+                                        // we are entering to trade so we have to set some vars
+                                        // ***WARN:*** field modification
+                                        trading_symbol.trading_next_step = TradingNextStep::Leave;
+                                        // ***WARN:*** field modification
+                                        trading_symbol.trading_started = Instant::now();
+                                        trading_symbol.trading_started_at = Utc::now();
+
+                                        // position is protected right after the buy, not with the next
+                                        // book update (depth stream can be down just now) - there is no
+                                        // OCO yet, so nothing can be filled here
+                                        if config.orderbook_monitor.exchange_protection_enabled
+                                            && trading_mode == TradingMode::RealTrading
+                                        {
+                                            let _ = protection.update(
+                                                &account,
+                                                &config,
+                                                &trading_symbol,
+                                            );
                                         }
                                     }
                                 }
                             }
+                        }
+                        //
+                        // END: starting trading consideration
+                        //
+
+                        //
+                        // BEGIN: TRADING LOGIC
+                        //
+                        if trading_symbol.trading_next_step == TradingNextStep::Leave
+                            && trading_symbol.trade_decision == TradingDecision::Continue
+                        {
                             //
-                            // END: starting trading consideration
+                            // price leave calculation algorithms
                             //
 
+                            // default value - it will change later if something is wrong and we
+                            // should NOT reading/processing orderbook this time only
+                            reading_market_depth_this_time = ReadMarketDepthNow::YES;
+
+                            if best_bid_price == decimal_zero && best_bid_qty == decimal_zero {
+                                // no update - skip reading
+                                reading_market_depth_this_time = ReadMarketDepthNow::NO;
+                            }
+
                             //
-                            // BEGIN: TRADING LOGIC
                             //
-                            if trading_symbol.trading_next_step == TradingNextStep::Leave
-                                && trading_symbol.trade_decision == TradingDecision::Continue
-                            {
-                                //
-                                // price leave calculation algorithms
-                                //
-
-                                // default value - it will change later if something is wrong and we
-                                // should NOT reading/processing orderbook this time only
-                                reading_market_depth_this_time = ReadMarketDepthNow::YES;
+                            //
+                            if reading_market_depth_this_time == ReadMarketDepthNow::YES {
+                                // ***WARN:*** field modification
+                                trading_symbol.current_profit_percent =
+                                    check_current_profit_percent(
+                                        trading_symbol.clone(),
+                                        best_ask_qty,
+                                        best_ask_price,
+                                        best_bid_qty,
+                                        best_price_now,
+                                    );
 
-                                if best_bid_price == decimal_zero && best_bid_qty == decimal_zero {
-                                    // no update - skip reading
-                                    reading_market_depth_this_time = ReadMarketDepthNow::NO;
+                                if trading_symbol.current_profit_percent
+                                    > trading_symbol.peak_profit_percent
+                                {
+                                    // ***WARN:*** field modification
+                                    trading_symbol.peak_profit_percent =
+                                        trading_symbol.current_profit_percent;
                                 }
 
                                 //
+                                // BEGIN: difference between last and current profit logic in percent points
                                 //
-                                //
-                                if reading_market_depth_this_time == ReadMarketDepthNow::YES {
-                                    // ***WARN:*** field modification
-                                    trading_symbol.current_profit_percent =
-                                        check_current_profit_percent(
-                                            trading_symbol.clone(),
-                                            best_ask_qty,
-                                            best_ask_price,
-                                            best_bid_qty,
-                                            best_price_now,
-                                        );
+                                if trading_symbol.previous_profit_percent
+                                    != trading_symbol.current_profit_percent
+                                {
+                                    // calculate absolute value no matter if it's profit/loss
+                                    if trading_symbol.previous_profit_percent != decimal_zero {
+                                        //
+                                        // previous_profit_percent is already set
+                                        //
 
-                                    //
-                                    // BEGIN: difference between last and current profit logic in percent points
-                                    //
-                                    if trading_symbol.previous_profit_percent
-                                        != trading_symbol.current_profit_percent
-                                    {
-                                        // calculate absolute value no matter if it's profit/loss
-                                        if trading_symbol.previous_profit_percent != decimal_zero {
-                                            //
-                                            // previous_profit_percent is already set
-                                            //
-
-                                            // calculate diff between previous and current - we are interested in
-                                            // difference, not profit/loss so that why absolute value
-                                            let mut larger = trading_symbol.current_profit_percent;
-                                            let mut smaller =
-                                                trading_symbol.previous_profit_percent;
-
-                                            if larger < trading_symbol.previous_profit_percent {
-                                                larger = trading_symbol.previous_profit_percent;
-                                                smaller = trading_symbol.current_profit_percent
-                                            }
-                                            let price_change_diff = larger - smaller;
+                                        // calculate diff between previous and current - we are interested in
+                                        // difference, not profit/loss so that why absolute value
+                                        let mut larger = trading_symbol.current_profit_percent;
+                                        let mut smaller = trading_symbol.previous_profit_percent;
 
-                                            // previous profit percent is used and should not be zero
-                                            if price_change_diff >= config
+                                        if larger < trading_symbol.previous_profit_percent {
+                                            larger = trading_symbol.previous_profit_percent;
+                                            smaller = trading_symbol.current_profit_percent
+                                        }
+                                        let price_change_diff = larger - smaller;
+
+                                        // previous profit percent is used and should not be zero
+                                        if price_change_diff
+                                            >= config
                                                 .orderbook_monitor
                                                 .ignore_if_percent_profit_changed_more_than_percent
-                                            {
-                                                // to big change in percent which should be ignored
-                                                if trading_symbol.previous_profit_large_change_count <=
-                                                    config.orderbook_monitor.maximum_count_of_profit_changed_ignored_readings {
+                                        {
+                                            // to big change in percent which should be ignored
+                                            if trading_symbol.previous_profit_large_change_count <=
+                                                config.orderbook_monitor.maximum_count_of_profit_changed_ignored_readings {
 
-                                                    // skip reading market data as it's "ignored
-                                                    reading_market_depth_this_time = ReadMarketDepthNow::NO;
+                                                // skip reading market data as it's "ignored
+                                                reading_market_depth_this_time = ReadMarketDepthNow::NO;
 
-                                                    // increment number of these ignored readings
-                                                    // ***WARN:*** field modification
-                                                    trading_symbol.previous_profit_large_change_count += 1;
-                                                }
+                                                // increment number of these ignored readings
+                                                // ***WARN:*** field modification
+                                                trading_symbol.previous_profit_large_change_count += 1;
+                                            }
 
-                                                if reading_market_depth_this_time == ReadMarketDepthNow::YES {
-                                                    // too many ignored readings - we reset counters
-                                                    // and now current reading make as previous
+                                            if reading_market_depth_this_time
+                                                == ReadMarketDepthNow::YES
+                                            {
+                                                // too many ignored readings - we reset counters
+                                                // and now current reading make as previous
 
-                                                    // ***WARN:*** field modification
-                                                    trading_symbol
-                                                        .previous_profit_large_change_count = 0;
+                                                // ***WARN:*** field modification
+                                                trading_symbol.previous_profit_large_change_count =
+                                                    0;
 
-                                                    // now current reading make as previous because it lasts longer
-                                                    // than it expected
-                                                    trading_symbol.previous_profit_percent =
-                                                        trading_symbol.current_profit_percent;
-                                                }
+                                                // now current reading make as previous because it lasts longer
+                                                // than it expected
+                                                trading_symbol.previous_profit_percent =
+                                                    trading_symbol.current_profit_percent;
                                             }
                                         }
-
-                                        // first set of previous profit - happens only once
-                                        if trading_symbol.previous_profit_percent == decimal_zero {
-                                            trading_symbol.previous_profit_percent =
-                                                trading_symbol.current_profit_percent
-                                        }
                                     }
-                                    //
-                                    // END: difference between last and current profit logic
-                                    //
-
-                                    if best_price_now == decimal_zero {
-                                        // no fit orderbook skip this shit to avoid division by zero later
-                                        reading_market_depth_this_time = ReadMarketDepthNow::NO;
 
-                                        // just in case - if there is finish action then we have to be sure we sell for something
-                                        finish_trading_for_symbol_now = false
+                                    // first set of previous profit - happens only once
+                                    if trading_symbol.previous_profit_percent == decimal_zero {
+                                        trading_symbol.previous_profit_percent =
+                                            trading_symbol.current_profit_percent
                                     }
+                                }
+                                //
+                                // END: difference between last and current profit logic
+                                //
 
-                                    if trading_symbol.current_profit_percent
-                                        < config.orderbook_monitor.loss_limit_sudden_drop_to_percent
-                                            * negative_one
-                                    {
-                                        // no fit orderbook skip this shit to avoid division by zero later
-                                        reading_market_depth_this_time = ReadMarketDepthNow::NO;
+                                if best_price_now == decimal_zero {
+                                    // no fit orderbook skip this shit to avoid division by zero later
+                                    reading_market_depth_this_time = ReadMarketDepthNow::NO;
 
-                                        // just in case - if there is finish action then we have to be sure we sell for something
-                                        finish_trading_for_symbol_now = false
-                                    }
+                                    // just in case - if there is finish action then we have to be sure we sell for something
+                                    finish_trading_for_symbol_now = false
                                 }
 
-                                //
-                                // BEGIN: request leave logic (from engine channel)
-                                //
-                                if finish_trading_for_symbol_now
-                                    && reading_market_depth_this_time == ReadMarketDepthNow::YES
+                                if trading_symbol.current_profit_percent
+                                    < config.orderbook_monitor.loss_limit_sudden_drop_to_percent
+                                        * negative_one
                                 {
-                                    let diff = trading_symbol.trading_started.elapsed();
-                                    let minutes = (diff.as_secs() / 60) % 60;
-                                    let hours = (diff.as_secs() / 60) / 60;
-                                    let time_passed_str = format!("{}h {}m", hours, minutes);
-
-                                    //
-                                    // MAIN PROFIT STAT HERE
-                                    //
-                                    let current_profit_percent =
-                                        trading_symbol.current_profit_percent;
+                                    // no fit orderbook skip this shit to avoid division by zero later
+                                    reading_market_depth_this_time = ReadMarketDepthNow::NO;
 
-                                    let log_prefix = format!("[{current_profit_percent}%] [{time_passed_str}] [{symbol}]");
-                                    let exit_qty =
-                                        calculate_exit_qty(&config, &trading_symbol).unwrap();
+                                    // just in case - if there is finish action then we have to be sure we sell for something
+                                    finish_trading_for_symbol_now = false
+                                }
+                            }
 
-                                    // // //
-                                    let my_current_qty = trading_symbol.qty;
-                                    let my_current_qty_price = trading_symbol.price;
-                                    info!("{log_prefix}: [LEAVE BY REQUEST]
-                                        my_used_price: {my_current_qty_price}, best_exit_price: {best_price_now}, exit_qty: {exit_qty} (from my_current_qty: {my_current_qty})");
-                                    // // //
+                            //
+                            // BEGIN: exchange-side protection - OCO is kept in line with trailing
+                            // levels, when it was filled the trade is over
+                            //
+                            if config.orderbook_monitor.exchange_protection_enabled
+                                && trading_mode == TradingMode::RealTrading
+                            {
+                                if let Some(fill) =
+                                    protection.update(&account, &config, &trading_symbol)
+                                {
+                                    info!("{symbol}: [EXCHANGE PROTECTION FILLED] sold qty: {}, received qty: {}",
+                                        fill.executed_qty, fill.quote_qty);
 
+                                    // partly filled OCO was cancelled - rest is protected again
+                                    // with the next update
+                                    if fill.complete {
+                                        // ***WARN:*** field modification
+                                        trading_symbol.exit_reason = ExitReason::ExchangeProtection;
+                                    }
                                     // ***WARN:*** field modification
-                                    trading_symbol.qty = exit_qty;
-                                    // ***WARN:*** field modification
-                                    trading_symbol.price = best_price_now;
+                                    if trading_symbol.apply_sell(
+                                        &fill,
+                                        config.orderbook_monitor.comission_percent()
+                                            / Decimal::ONE_HUNDRED,
+                                    ) {
+                                        // ***WARN:*** field modification
+                                        trading_symbol.trade_decision = TradingDecision::Stop;
+                                        final_trade_decision_clone.set(TradingDecision::Stop);
+                                    }
 
-                                    // so we don't need read market depth anymore as we are finishing now
                                     reading_market_depth_this_time = ReadMarketDepthNow::NO;
+                                    finish_trading_for_symbol_now = false;
                                 }
-                                //
-                                // END: request leave logic (from engine channel)
-                                //
+                            }
+                            //
+                            // END: exchange-side protection
+                            //
+
+                            //
+                            // BEGIN: request leave logic (from engine channel)
+                            //
+                            if finish_trading_for_symbol_now
+                                && reading_market_depth_this_time == ReadMarketDepthNow::YES
+                            {
+                                let diff = trading_symbol.trading_started.elapsed();
+                                let minutes = (diff.as_secs() / 60) % 60;
+                                let hours = (diff.as_secs() / 60) / 60;
+                                let time_passed_str = format!("{}h {}m", hours, minutes);
 
                                 //
+                                // MAIN PROFIT STAT HERE
                                 //
-                                //
-                                if reading_market_depth_this_time == ReadMarketDepthNow::YES {
-                                    //
-                                    // not skipped - we can analyse now as data is ok
-                                    //
+                                let current_profit_percent = trading_symbol.current_profit_percent;
 
-                                    // ***WARN:*** field modification
-                                    trading_symbol.last_best_price = best_price_now;
+                                let log_prefix = format!(
+                                    "[{current_profit_percent}%] [{time_passed_str}] [{symbol}]"
+                                );
+                                match calculate_exit_qty(&trading_symbol) {
+                                    Some(exit_qty) => {
+                                        // // //
+                                        let my_current_qty = trading_symbol.qty;
+                                        let my_current_qty_price = trading_symbol.price;
+                                        info!("{log_prefix}: [LEAVE BY REQUEST]
+                                    my_used_price: {my_current_qty_price}, best_exit_price: {best_price_now}, exit_qty: {exit_qty} (from my_current_qty: {my_current_qty})");
+                                        // // //
 
-                                    //
-                                    // BEGIN: ABSOLUTE MINIMAL PROFIT
-                                    //
-                                    there_is_abs_minimal_profit_now = false;
+                                        // ***WARN:*** field modification
+                                        trading_symbol.qty = exit_qty;
+                                        // ***WARN:*** field modification
+                                        trading_symbol.price = best_price_now;
+                                        // ***WARN:*** field modification
+                                        trading_symbol.exit_reason = requested_exit_reason.clone();
 
-                                    if trading_symbol.current_profit_percent
-                                        >= trading_symbol.absolute_minimal_profit_percent
-                                    {
-                                        there_is_abs_minimal_profit_now = true;
+                                        // so we don't need read market depth anymore as we are finishing now
+                                        reading_market_depth_this_time = ReadMarketDepthNow::NO;
                                     }
-                                    //
-                                    // END: ABSOLUTE MINIMAL PROFIT
-                                    //
+                                    None => {
+                                        // held qty doesn't pass the filters, it can't be sold anymore
+                                        error!("{symbol}: exit qty can't be calculated from held qty: {}, kept as dust", trading_symbol.qty);
+                                        // ***WARN:*** field modification
+                                        trading_symbol.close_as_dust();
+                                        final_trade_decision_clone.set(TradingDecision::Stop);
+                                        reading_market_depth_this_time = ReadMarketDepthNow::NO;
+                                    }
+                                }
+                            }
+                            //
+                            // END: request leave logic (from engine channel)
+                            //
 
-                                    // showing trading time for symbol
+                            //
+                            //
+                            //
+                            if reading_market_depth_this_time == ReadMarketDepthNow::YES {
+                                //
+                                // not skipped - we can analyse now as data is ok
+                                //
 
-                                    let diff = trading_symbol.trading_started.elapsed();
-                                    let minutes = (diff.as_secs() / 60) % 60;
-                                    let hours = (diff.as_secs() / 60) / 60;
-                                    let time_passed_str = format!("{}h {}m", hours, minutes);
+                                // ***WARN:*** field modification
+                                trading_symbol.last_best_price = best_price_now;
 
-                                    //
-                                    // MAIN PROFIT STAT HERE
-                                    //
-                                    let my_current_qty = trading_symbol.clone().qty;
-                                    let my_current_qty_price = trading_symbol.clone().price;
+                                //
+                                // BEGIN: ABSOLUTE MINIMAL PROFIT
+                                //
+                                there_is_abs_minimal_profit_now = false;
 
-                                    let current_profit_percent =
-                                        trading_symbol.current_profit_percent;
+                                if trading_symbol.current_profit_percent
+                                    >= trading_symbol.absolute_minimal_profit_percent
+                                {
+                                    there_is_abs_minimal_profit_now = true;
+                                }
+                                //
+                                // END: ABSOLUTE MINIMAL PROFIT
+                                //
 
-                                    let my_base = trading_symbol.used_qty;
-                                    let log_prefix = format!("[{current_profit_percent}%] [{time_passed_str}] [{symbol}]");
-                                    info!("{log_prefix}: my price: {my_current_qty_price}, my base: {my_base}, \
-                                my qty {my_current_qty}, best price now: {best_price_now} \
-                                [NOW: price: {best_bid_price}, qty: {best_bid_qty}]");
+                                // showing trading time for symbol
 
-                                    //
-                                    //
-                                    //
-                                    //
-                                    //
+                                let diff = trading_symbol.trading_started.elapsed();
+                                let minutes = (diff.as_secs() / 60) % 60;
+                                let hours = (diff.as_secs() / 60) / 60;
+                                let time_passed_str = format!("{}h {}m", hours, minutes);
 
+                                //
+                                // MAIN PROFIT STAT HERE
+                                //
+                                let my_current_qty = trading_symbol.clone().qty;
+                                let my_current_qty_price = trading_symbol.clone().price;
+
+                                let current_profit_percent = trading_symbol.current_profit_percent;
+
+                                let my_base = trading_symbol.used_qty;
+                                let log_prefix = format!(
+                                    "[{current_profit_percent}%] [{time_passed_str}] [{symbol}]"
+                                );
+                                info!("{log_prefix}: my price: {my_current_qty_price}, my base: {my_base}, \
+                            my qty {my_current_qty}, best price now: {best_price_now} \
+                            [NOW: price: {best_bid_price}, qty: {best_bid_qty}, exit limit price: {}]",
+                            trading_symbol.exit_limit_price);
+
+                                //
+                                //
+                                //
+                                //
+                                //
+
+                                //
+                                // BEGIN: minimal profit
+                                //
+                                if trading_symbol.current_trading_profit
+                                    != CurrentTradingProfit::MinimalProfit
+                                    && trading_symbol.current_trading_profit
+                                        != CurrentTradingProfit::GoodProfit
+                                {
                                     //
-                                    // BEGIN: minimal profit
+                                    // min profit price was crossed but not good profit set?
                                     //
-                                    if trading_symbol.current_trading_profit
-                                        != CurrentTradingProfit::MinimalProfit
-                                        && trading_symbol.current_trading_profit
-                                            != CurrentTradingProfit::GoodProfit
-                                    {
-                                        //
-                                        // min profit price was crossed but not good profit set?
-                                        //
-                                        if best_price_now >= trading_symbol.min_profit_price {
-                                            info!("{log_prefix}: [__MIN__ PROFIT SET] my_used_price: {my_current_qty_price}, best_price now: {best_price_now}");
-                                            // ***WARN:*** field modification
-                                            trading_symbol.current_trading_profit =
-                                                CurrentTradingProfit::MinimalProfit;
-                                            // ***WARN:*** field modification
-                                            trading_symbol.highest_price_since_min_profit =
-                                                best_price_now;
-                                        }
+                                    if best_price_now >= trading_symbol.min_profit_price {
+                                        info!("{log_prefix}: [__MIN__ PROFIT SET] my_used_price: {my_current_qty_price}, best_price now: {best_price_now}");
+                                        // ***WARN:*** field modification
+                                        trading_symbol.current_trading_profit =
+                                            CurrentTradingProfit::MinimalProfit;
+                                        // ***WARN:*** field modification
+                                        trading_symbol.highest_price_since_min_profit =
+                                            best_price_now;
                                     }
-                                    //
-                                    // END: minimal profit
-                                    //
+                                }
+                                //
+                                // END: minimal profit
+                                //
+
+                                //
+                                // BEGIN: good profit
+                                //
+                                let mut good_profit_reached = false;
+
+                                if trading_symbol.current_trading_profit
+                                    != CurrentTradingProfit::GoodProfit
+                                {
+                                    // good profit price?
+                                    if best_price_now >= trading_symbol.good_profit_price {
+                                        info!("{log_prefix}: [# |GOOD| # PROFIT SET] my_used_price: {my_current_qty_price}, best_price now: {best_price_now}");
+                                        // ***WARN:*** field modification
+                                        trading_symbol.current_trading_profit =
+                                            CurrentTradingProfit::GoodProfit;
+                                        // ***WARN:*** field modification
+                                        trading_symbol.highest_price_since_good_profit =
+                                            best_price_now;
+                                        good_profit_reached = true;
+                                    }
+                                }
 
+                                if good_profit_reached {
                                     //
-                                    // BEGIN: good profit
+                                    // good profit logic
                                     //
-                                    let mut good_profit_reached = false;
-
-                                    if trading_symbol.current_trading_profit
-                                        != CurrentTradingProfit::GoodProfit
+                                    if best_price_now
+                                        > trading_symbol.highest_price_since_good_profit
                                     {
-                                        // good profit price?
-                                        if best_price_now >= trading_symbol.good_profit_price {
-                                            info!("{log_prefix}: [# |GOOD| # PROFIT SET] my_used_price: {my_current_qty_price}, best_price now: {best_price_now}");
-                                            // ***WARN:*** field modification
-                                            trading_symbol.current_trading_profit =
-                                                CurrentTradingProfit::GoodProfit;
-                                            // ***WARN:*** field modification
-                                            trading_symbol.highest_price_since_good_profit =
-                                                best_price_now;
-                                            good_profit_reached = true;
-                                        }
+                                        let highest_price_since_good_profit =
+                                            trading_symbol.clone().highest_price_since_good_profit;
+                                        // price still rising - remember this
+                                        info!("{log_prefix}: [# |GOOD| # PROFIT UPDATE] my_used_price: {my_current_qty_price}, \
+                                previous_highest: {highest_price_since_good_profit}, best_price now: {best_price_now}");
+                                        // ***WARN:*** field modification
+                                        trading_symbol.highest_price_since_good_profit =
+                                            best_price_now;
                                     }
 
-                                    if good_profit_reached {
-                                        //
-                                        // good profit logic
-                                        //
-                                        if best_price_now
-                                            > trading_symbol.highest_price_since_good_profit
-                                        {
-                                            let highest_price_since_good_profit = trading_symbol
-                                                .clone()
-                                                .highest_price_since_good_profit;
-                                            // price still rising - remember this
-                                            info!("{log_prefix}: [# |GOOD| # PROFIT UPDATE] my_used_price: {my_current_qty_price}, \
-                                    previous_highest: {highest_price_since_good_profit}, best_price now: {best_price_now}");
-                                            // ***WARN:*** field modification
-                                            trading_symbol.highest_price_since_good_profit =
-                                                best_price_now;
-                                        }
+                                    if best_price_now
+                                        < trading_symbol.highest_price_since_good_profit
+                                    {
+                                        // price dropped
+                                        let highest_price_since_good_profit =
+                                            trading_symbol.clone().highest_price_since_good_profit;
+                                        let price_drop_now = percent_diff(
+                                            best_price_now,
+                                            highest_price_since_good_profit,
+                                        )
+                                        .round_dp_with_strategy(2, RoundingStrategy::ToZero);
 
-                                        if best_price_now
-                                            < trading_symbol.highest_price_since_good_profit
+                                        if price_drop_now
+                                            >= config
+                                                .orderbook_monitor
+                                                .good_profit_crossed_allowed_drop_percent
+                                            && best_price_now >= trading_symbol.min_profit_price
                                         {
-                                            // price dropped
-                                            let highest_price_since_good_profit = trading_symbol
-                                                .clone()
-                                                .highest_price_since_good_profit;
-                                            let price_drop_now = percent_diff(
-                                                best_price_now,
-                                                highest_price_since_good_profit,
-                                            )
-                                            .round_dp_with_strategy(2, RoundingStrategy::ToZero);
+                                            match calculate_exit_qty(&trading_symbol) {
+                                                Some(exit_qty) => {
+                                                    // // //
+                                                    let my_current_qty_price = trading_symbol.price;
+                                                    info!("{log_prefix}: [### |GOOD PROFIT LEAVE| ###] drop: {price_drop_now}%, \
+                                            my_used_price: {my_current_qty_price}, best_exit_price: {best_price_now}, exit_qty: {exit_qty} \
+                                            [previous_highest: {highest_price_since_good_profit}]");
+                                                    // // //
 
-                                            if price_drop_now
-                                                >= config
-                                                    .orderbook_monitor
-                                                    .good_profit_crossed_allowed_drop_percent
-                                                && best_price_now >= trading_symbol.min_profit_price
-                                            {
-                                                let exit_qty =
-                                                    calculate_exit_qty(&config, &trading_symbol)
-                                                        .unwrap();
-
-                                                // // //
-                                                let my_current_qty_price = trading_symbol.price;
-                                                info!("{log_prefix}: [### |GOOD PROFIT LEAVE| ###] drop: {price_drop_now}%, \
-                                        my_used_price: {my_current_qty_price}, best_exit_price: {best_price_now}, exit_qty: {exit_qty} \
-                                        [previous_highest: {highest_price_since_good_profit}]");
-                                                // // //
-
-                                                // ***WARN:*** field modification
-                                                trading_symbol.qty = exit_qty;
-                                                // ***WARN:*** field modification
-                                                trading_symbol.price = best_price_now;
+                                                    // ***WARN:*** field modification
+                                                    trading_symbol.qty = exit_qty;
+                                                    // ***WARN:*** field modification
+                                                    trading_symbol.price = best_price_now;
+                                                    // ***WARN:*** field modification
+                                                    trading_symbol.exit_reason =
+                                                        ExitReason::GoodProfit;
 
-                                                finish_trading_for_symbol_now = true;
+                                                    finish_trading_for_symbol_now = true;
+                                                }
+                                                None => {
+                                                    // held qty doesn't pass the filters, it can't be sold anymore
+                                                    error!("{symbol}: exit qty can't be calculated from held qty: {}, kept as dust", trading_symbol.qty);
+                                                    // ***WARN:*** field modification
+                                                    trading_symbol.close_as_dust();
+                                                    final_trade_decision_clone
+                                                        .set(TradingDecision::Stop);
+                                                    reading_market_depth_this_time =
+                                                        ReadMarketDepthNow::NO;
+                                                }
                                             }
                                         }
                                     }
-                                    //
-                                    // END: good profit
-                                    //
+                                }
+                                //
+                                // END: good profit
+                                //
 
+                                //
+                                // BEGIN: min profit
+                                //
+                                if trading_symbol.current_trading_profit
+                                    == CurrentTradingProfit::MinimalProfit
+                                    && trading_symbol.trade_decision == TradingDecision::Continue
+                                // only if no signal to leave
+                                {
                                     //
-                                    // BEGIN: min profit
+                                    // min profit logic, only if:
+                                    // - no good price recorded
+                                    // - no stop trading signal
                                     //
-                                    if trading_symbol.current_trading_profit
-                                        == CurrentTradingProfit::MinimalProfit
-                                        && trading_symbol.trade_decision
-                                            == TradingDecision::Continue
-                                    // only if no signal to leave
-                                    {
-                                        //
-                                        // min profit logic, only if:
-                                        // - no good price recorded
-                                        // - no stop trading signal
-                                        //
-                                        let highest_price_since_min_profit =
-                                            trading_symbol.clone().highest_price_since_min_profit;
-                                        let my_current_qty_price = trading_symbol.price;
+                                    let highest_price_since_min_profit =
+                                        trading_symbol.clone().highest_price_since_min_profit;
+                                    let my_current_qty_price = trading_symbol.price;
 
-                                        if best_price_now > highest_price_since_min_profit {
-                                            // price still rising
-                                            // log message
-                                            info!("{log_prefix}: [_MIN PROFIT UPDATE_] my_used_price: {my_current_qty_price}, \
-                                    previous_highest: {highest_price_since_min_profit}, best_price now: {best_price_now}");
-                                            // ***WARN:*** field modification
-                                            trading_symbol.highest_price_since_min_profit =
-                                                best_price_now;
-                                        }
+                                    if best_price_now > highest_price_since_min_profit {
+                                        // price still rising
+                                        // log message
+                                        info!("{log_prefix}: [_MIN PROFIT UPDATE_] my_used_price: {my_current_qty_price}, \
+                                previous_highest: {highest_price_since_min_profit}, best_price now: {best_price_now}");
+                                        // ***WARN:*** field modification
+                                        trading_symbol.highest_price_since_min_profit =
+                                            best_price_now;
+                                    }
 
-                                        if best_price_now < highest_price_since_min_profit {
-                                            // price dropped
-                                            let percent_drop = percent_diff(
-                                                best_price_now,
-                                                highest_price_since_min_profit,
-                                            )
-                                            .round_dp_with_strategy(2, RoundingStrategy::ToZero);
+                                    if best_price_now < highest_price_since_min_profit {
+                                        // price dropped
+                                        let percent_drop = percent_diff(
+                                            best_price_now,
+                                            highest_price_since_min_profit,
+                                        )
+                                        .round_dp_with_strategy(2, RoundingStrategy::ToZero);
 
-                                            if percent_drop
-                                                >= config
-                                                    .orderbook_monitor
-                                                    .min_profit_crossed_allowed_drop_percent
-                                                && best_price_now > trading_symbol.min_profit_price
-                                            {
-                                                let exit_qty =
-                                                    calculate_exit_qty(&config, &trading_symbol)
-                                                        .unwrap();
-
-                                                // // //
-                                                let highest_price_since_min_profit =
-                                                    trading_symbol.highest_price_since_min_profit;
-                                                let my_current_qty = trading_symbol.qty;
-                                                let my_current_qty_price = trading_symbol.price;
-                                                info!("{log_prefix}: [_+++MIN PROFIT LEAVE+++_] drop: {percent_drop}%, \
-                                        my_used_price: {my_current_qty_price}, best_exit_price: {best_price_now}, exit_qty: {exit_qty} (from my_current_qty: {my_current_qty}) \
-                                        [previous_highest: {highest_price_since_min_profit}]");
-                                                // // //
+                                        if percent_drop
+                                            >= config
+                                                .orderbook_monitor
+                                                .min_profit_crossed_allowed_drop_percent
+                                            && best_price_now > trading_symbol.min_profit_price
+                                        {
+                                            match calculate_exit_qty(&trading_symbol) {
+                                                Some(exit_qty) => {
+                                                    // // //
+                                                    let highest_price_since_min_profit =
+                                                        trading_symbol
+                                                            .highest_price_since_min_profit;
+                                                    let my_current_qty = trading_symbol.qty;
+                                                    let my_current_qty_price = trading_symbol.price;
+                                                    info!("{log_prefix}: [_+++MIN PROFIT LEAVE+++_] drop: {percent_drop}%, \
+                                            my_used_price: {my_current_qty_price}, best_exit_price: {best_price_now}, exit_qty: {exit_qty} (from my_current_qty: {my_current_qty}) \
+                                            [previous_highest: {highest_price_since_min_profit}]");
+                                                    // // //
 
-                                                // ***WARN:*** field modification
-                                                trading_symbol.qty = exit_qty;
-                                                // ***WARN:*** field modification
-                                                trading_symbol.price = best_price_now;
+                                                    // ***WARN:*** field modification
+                                                    trading_symbol.qty = exit_qty;
+                                                    // ***WARN:*** field modification
+                                                    trading_symbol.price = best_price_now;
+                                                    // ***WARN:*** field modification
+                                                    trading_symbol.exit_reason =
+                                                        ExitReason::MinProfit;
 
-                                                finish_trading_for_symbol_now = true;
+                                                    finish_trading_for_symbol_now = true;
+                                                }
+                                                None => {
+                                                    // held qty doesn't pass the filters, it can't be sold anymore
+                                                    error!("{symbol}: exit qty can't be calculated from held qty: {}, kept as dust", trading_symbol.qty);
+                                                    // ***WARN:*** field modification
+                                                    trading_symbol.close_as_dust();
+                                                    final_trade_decision_clone
+                                                        .set(TradingDecision::Stop);
+                                                    reading_market_depth_this_time =
+                                                        ReadMarketDepthNow::NO;
+                                                }
                                             }
                                         }
                                     }
-                                    //
-                                    // END: min profit
-                                    //
-
-                                    //
-                                    // BEGIN: limiting loss logic (when timeout which prevents from instant sells with losses)
-                                    //
-                                    if config.orderbook_monitor.loss_limit_enabled // loss limiting is enabled in config
-                                        && trading_symbol.trade_decision == TradingDecision::Continue // we continue trade
-                                        && reading_market_depth_this_time == ReadMarketDepthNow::YES
-                                    // we continue reading market depth
-                                    {
-                                        // loss limit logic kicks in if current_profit_percent is negative
-                                        if current_profit_percent < decimal_zero {
-                                            let loss_percent = current_profit_percent.abs();
-                                            let highest_price_since_min_profit =
-                                                trading_symbol.highest_price_since_min_profit;
-                                            let my_current_qty_price = trading_symbol.price;
-                                            let my_current_qty = trading_symbol.qty;
+                                }
+                                //
+                                // END: min profit
+                                //
 
-                                            if loss_percent
-                                                > config
-                                                    .orderbook_monitor
-                                                    .loss_limit_sudden_drop_to_percent
-                                            {
-                                                // it protects from sudden escape from sudden loss
-                                                if trading_symbol.loss_too_large_displayed.not() {
-                                                    warn!("{log_prefix}: [LOSS LIMIT IGNORED] my_used_price: {my_current_qty_price}, best_exit_price: {best_price_now}, loss_percent: {loss_percent} (from my_current_qty: {my_current_qty}) [previous_highest: {highest_price_since_min_profit}]");
-                                                    // ***WARN:*** field modification
-                                                    trading_symbol.loss_too_large_displayed = true;
-                                                }
+                                //
+                                // BEGIN: limiting loss logic (when timeout which prevents from instant sells with losses)
+                                //
+                                if config.orderbook_monitor.loss_limit_enabled // loss limiting is enabled in config
+                                    && trading_symbol.trade_decision == TradingDecision::Continue // we continue trade
+                                    && reading_market_depth_this_time == ReadMarketDepthNow::YES
+                                // we continue reading market depth
+                                {
+                                    // loss limit logic kicks in if current_profit_percent is negative
+                                    if current_profit_percent < decimal_zero {
+                                        let loss_percent = current_profit_percent.abs();
+                                        let highest_price_since_min_profit =
+                                            trading_symbol.highest_price_since_min_profit;
+                                        let my_current_qty_price = trading_symbol.price;
+                                        let my_current_qty = trading_symbol.qty;
 
+                                        if loss_percent
+                                            > config
+                                                .orderbook_monitor
+                                                .loss_limit_sudden_drop_to_percent
+                                        {
+                                            // it protects from sudden escape from sudden loss
+                                            if trading_symbol.loss_too_large_displayed.not() {
+                                                warn!("{log_prefix}: [LOSS LIMIT IGNORED] my_used_price: {my_current_qty_price}, best_exit_price: {best_price_now}, loss_percent: {loss_percent} (from my_current_qty: {my_current_qty}) [previous_highest: {highest_price_since_min_profit}]");
                                                 // ***WARN:*** field modification
-                                                trading_symbol.current_trading_profit =
-                                                    CurrentTradingProfit::LossTooLarge;
+                                                trading_symbol.loss_too_large_displayed = true;
                                             }
 
-                                            if loss_percent
-                                                >= config.orderbook_monitor.loss_limit_percent
-                                                && trading_symbol.current_trading_profit
-                                                    == CurrentTradingProfit::LossTooLarge
-                                            {
-                                                let exit_qty =
-                                                    calculate_exit_qty(&config, &trading_symbol)
-                                                        .unwrap();
+                                            // ***WARN:*** field modification
+                                            trading_symbol.current_trading_profit =
+                                                CurrentTradingProfit::LossTooLarge;
+                                        }
 
-                                                // // //
-                                                info!("{log_prefix} [!!! LOSS LIMIT LEAVE !!!]
-                                            my_used_price: {my_current_qty_price}, best_exit_price: {best_price_now}, exit_qty: {exit_qty} (from my_current_qty: {my_current_qty}) \
-                                            [previous_highest: {highest_price_since_min_profit}]");
-                                                // // //
+                                        if loss_percent
+                                            >= config.orderbook_monitor.loss_limit_percent
+                                            && trading_symbol.current_trading_profit
+                                                == CurrentTradingProfit::LossTooLarge
+                                        {
+                                            match calculate_exit_qty(&trading_symbol) {
+                                                Some(exit_qty) => {
+                                                    // // //
+                                                    info!("{log_prefix} [!!! LOSS LIMIT LEAVE !!!]
+                                        my_used_price: {my_current_qty_price}, best_exit_price: {best_price_now}, exit_qty: {exit_qty} (from my_current_qty: {my_current_qty}) \
+                                                [previous_highest: {highest_price_since_min_profit}]");
+                                                    // // //
 
-                                                // ***WARN:*** field modification
-                                                trading_symbol.qty = exit_qty;
-                                                // ***WARN:*** field modification
-                                                trading_symbol.price = best_price_now;
+                                                    // ***WARN:*** field modification
+                                                    trading_symbol.qty = exit_qty;
+                                                    // ***WARN:*** field modification
+                                                    trading_symbol.price = best_price_now;
+                                                    // ***WARN:*** field modification
+                                                    trading_symbol.exit_reason =
+                                                        ExitReason::LossLimit;
 
-                                                finish_trading_for_symbol_now = true;
+                                                    finish_trading_for_symbol_now = true;
+                                                }
+                                                None => {
+                                                    // held qty doesn't pass the filters, it can't be sold anymore
+                                                    error!("{symbol}: exit qty can't be calculated from held qty: {}, kept as dust", trading_symbol.qty);
+                                                    // ***WARN:*** field modification
+                                                    trading_symbol.close_as_dust();
+                                                    final_trade_decision_clone
+                                                        .set(TradingDecision::Stop);
+                                                    reading_market_depth_this_time =
+                                                        ReadMarketDepthNow::NO;
+                                                }
                                             }
                                         }
                                     }
-                                    //
-                                    // END: limiting loss logic
-                                    //
                                 }
+                                //
+                                // END: limiting loss logic
+                                //
+                            }
 
+                            //
+                            // BEGIN: TRADING TIME LIMIT
+                            //
+                            if (trading_symbol.trading_started.elapsed().as_secs()
+                                >= config.orderbook_monitor.time_limit_secs
+                                || trading_symbol.soft_timeout_trading)
+                                && trading_symbol.trade_decision == TradingDecision::Continue // continue to trade
+                                && reading_market_depth_this_time == ReadMarketDepthNow::YES
+                            // continue read market depth
+                            {
                                 //
-                                // BEGIN: TRADING TIME LIMIT
+                                // TIMEOUT ESCAPE
                                 //
-                                if (trading_symbol.trading_started.elapsed().as_secs()
-                                    >= config.orderbook_monitor.time_limit_secs
-                                    || trading_symbol.soft_timeout_trading)
-                                    && trading_symbol.trade_decision == TradingDecision::Continue // continue to trade
-                                    && reading_market_depth_this_time == ReadMarketDepthNow::YES
-                                // continue read market depth
+                                let diff = trading_symbol.trading_started.elapsed();
+                                let minutes = (diff.as_secs() / 60) % 60;
+                                let hours = (diff.as_secs() / 60) / 60;
+                                let time_passed_str = format!("{}h {}m", hours, minutes);
+                                let current_profit_percent = trading_symbol.current_profit_percent;
+                                let log_prefix = format!(
+                                    "[{current_profit_percent}%] [{time_passed_str}] [{symbol}]"
+                                );
+
+                                if there_is_abs_minimal_profit_now.not()
+                                    && config.orderbook_monitor.time_limit_requires_profit
+                                    && trading_symbol.soft_timeout_trading.not()
                                 {
-                                    //
-                                    // TIMEOUT ESCAPE
-                                    //
-                                    let diff = trading_symbol.trading_started.elapsed();
-                                    let minutes = (diff.as_secs() / 60) % 60;
-                                    let hours = (diff.as_secs() / 60) / 60;
-                                    let time_passed_str = format!("{}h {}m", hours, minutes);
-                                    let current_profit_percent =
-                                        trading_symbol.current_profit_percent;
-                                    let log_prefix = format!("[{current_profit_percent}%] [{time_passed_str}] [{symbol}]");
+                                    warn!("{log_prefix}: [TIMEOUT EXIT STOPPED] - no profit, so waiting (unless loss_limit will kick in)");
+                                }
 
-                                    if there_is_abs_minimal_profit_now.not()
-                                        && config.orderbook_monitor.time_limit_requires_profit
-                                        && trading_symbol.soft_timeout_trading.not()
-                                    {
-                                        warn!("{log_prefix}: [TIMEOUT EXIT STOPPED] - no profit, so waiting (unless loss_limit will kick in)");
-                                    }
+                                if trading_symbol.soft_timeout_trading.not() {
+                                    // ***WARN:*** field modification
+                                    trading_symbol.soft_timeout_trading = true;
+                                }
 
-                                    if trading_symbol.soft_timeout_trading.not() {
-                                        // ***WARN:*** field modification
-                                        trading_symbol.soft_timeout_trading = true;
-                                    }
+                                let mut best_price = best_price_now;
 
-                                    let mut best_price = best_price_now;
+                                if best_price == decimal_zero {
+                                    best_price = trading_symbol.last_best_price;
+                                }
 
-                                    if best_price == decimal_zero {
-                                        best_price = trading_symbol.last_best_price;
-                                    }
+                                // division by zero if base is 0
+                                if best_price == decimal_zero {
+                                    // warn log
+                                }
 
-                                    // division by zero if base is 0
-                                    if best_price == decimal_zero {
-                                        // warn log
-                                    }
+                                //
+                                // BEGIN: possible scenarios when we can stop if timeout
+                                //
 
-                                    //
-                                    // BEGIN: possible scenarios when we can stop if timeout
-                                    //
+                                let mut we_can_leave_with_profit = false;
 
-                                    let mut we_can_leave_with_profit = false;
+                                /*
 
-                                    /*
+                                Possible scenarios to leave (if timeout):
 
-                                    Possible scenarios to leave (if timeout):
+                                1) There is good_profit and current price is equal or larger than good_profit_price
 
-                                    1) There is good_profit and current price is equal or larger than good_profit_price
+                                2) There is min_profit and current price is equal or larger than min_profit_price
+                                   However: it has to be lower than good_profit_price and no good_profit should
+                                   be at the same time (good_profit has higher priority)
 
-                                    2) There is min_profit and current price is equal or larger than min_profit_price
-                                       However: it has to be lower than good_profit_price and no good_profit should
-                                       be at the same time (good_profit has higher priority)
+                                3) There is absolute minimal profit and current price is equal or larger than
+                                   absolute_minimal_profit price. This is the lowest priority scenario.
+                                   At the same time we can't have good_profit and min_profit
 
-                                    3) There is absolute minimal profit and current price is equal or larger than
-                                       absolute_minimal_profit price. This is the lowest priority scenario.
-                                       At the same time we can't have good_profit and min_profit
+                                 */
 
-                                     */
+                                // first we check good profit - highest priority
+                                // if there is good profit - then we can leave with timeout
+                                if trading_symbol.current_trading_profit
+                                    == CurrentTradingProfit::GoodProfit
+                                    && trading_symbol.good_profit_price <= best_price
+                                {
+                                    // there is good profit indeed - we can leave now because of timeout
+                                    we_can_leave_with_profit = true;
+                                }
 
-                                    // first we check good profit - highest priority
-                                    // if there is good profit - then we can leave with timeout
+                                if !we_can_leave_with_profit {
+                                    //
+                                    // enter here if: NO good_profit
+                                    // no good profit - check now min profit, maybe it's applicable
+                                    //
                                     if trading_symbol.current_trading_profit
-                                        == CurrentTradingProfit::GoodProfit
-                                        && trading_symbol.good_profit_price <= best_price
+                                        == CurrentTradingProfit::MinimalProfit
+                                        && trading_symbol.min_profit_price <= best_price
                                     {
-                                        // there is good profit indeed - we can leave now because of timeout
+                                        // there is min profit - we can leave now because of timeout
                                         we_can_leave_with_profit = true;
                                     }
+                                }
 
-                                    if !we_can_leave_with_profit {
-                                        //
-                                        // enter here if: NO good_profit
-                                        // no good profit - check now min profit, maybe it's applicable
-                                        //
-                                        if trading_symbol.current_trading_profit
-                                            == CurrentTradingProfit::MinimalProfit
-                                            && trading_symbol.min_profit_price <= best_price
-                                        {
-                                            // there is min profit - we can leave now because of timeout
-                                            we_can_leave_with_profit = true;
-                                        }
-                                    }
-
-                                    if !we_can_leave_with_profit {
-                                        // we enter here only if: (NO min_profit) AND (NO good_profit)
-                                        // we can now check if there is absolute minimal profit
-                                        if there_is_abs_minimal_profit_now
-                                            || config
-                                                .orderbook_monitor
-                                                .time_limit_requires_profit
-                                                .not()
-                                        {
-                                            // there is absolute minimal profit or we don't require profit
-                                            // so we can leave now because of timeout
-                                            we_can_leave_with_profit = true;
-                                        }
+                                if !we_can_leave_with_profit {
+                                    // we enter here only if: (NO min_profit) AND (NO good_profit)
+                                    // we can now check if there is absolute minimal profit
+                                    if there_is_abs_minimal_profit_now
+                                        || config.orderbook_monitor.time_limit_requires_profit.not()
+                                    {
+                                        // there is absolute minimal profit or we don't require profit
+                                        // so we can leave now because of timeout
+                                        we_can_leave_with_profit = true;
                                     }
+                                }
 
-                                    // we_can_leave_with_profit = false means there is timeout and there is NO
-                                    // required profit so bot will be waiting
-
-                                    //
-                                    // END: possible scenarios when we can stop if timeout
-                                    //
+                                // we_can_leave_with_profit = false means there is timeout and there is NO
+                                // required profit so bot will be waiting
 
-                                    if best_price > decimal_zero && we_can_leave_with_profit {
-                                        // LEAVE action can happen here after timeout
+                                //
+                                // END: possible scenarios when we can stop if timeout
+                                //
 
-                                        let exit_qty =
-                                            calculate_exit_qty(&config, &trading_symbol).unwrap();
+                                if best_price > decimal_zero && we_can_leave_with_profit {
+                                    // LEAVE action can happen here after timeout
 
-                                        // // //
-                                        let highest_price_since_min_profit =
-                                            trading_symbol.highest_price_since_min_profit;
-                                        let my_used_qty = trading_symbol.qty;
-                                        let my_used_price = trading_symbol.price;
-                                        info!("{log_prefix}: [TIMEOUT - LEAVE WITH PROFIT] \
-                                    my_used_price: {my_used_price}, best_exit_price: {best_price_now}, exit_qty: {exit_qty} (from my_current_qty: {my_used_qty}) \
-                                    [previous_highest: {highest_price_since_min_profit}]");
-                                        // // //
+                                    match calculate_exit_qty(&trading_symbol) {
+                                        Some(exit_qty) => {
+                                            // // //
+                                            let highest_price_since_min_profit =
+                                                trading_symbol.highest_price_since_min_profit;
+                                            let my_used_qty = trading_symbol.qty;
+                                            let my_used_price = trading_symbol.price;
+                                            info!("{log_prefix}: [TIMEOUT - LEAVE WITH PROFIT] \
+                                        my_used_price: {my_used_price}, best_exit_price: {best_price_now}, exit_qty: {exit_qty} (from my_current_qty: {my_used_qty}) \
+                                        [previous_highest: {highest_price_since_min_profit}]");
+                                            // // //
 
-                                        // ***WARN:*** field modification
-                                        trading_symbol.qty = exit_qty;
-                                        // ***WARN:*** field modification
-                                        trading_symbol.price = best_price_now;
+                                            // ***WARN:*** field modification
+                                            trading_symbol.qty = exit_qty;
+                                            // ***WARN:*** field modification
+                                            trading_symbol.price = best_price_now;
+                                            // ***WARN:*** field modification
+                                            trading_symbol.exit_reason = ExitReason::Timeout;
 
-                                        finish_trading_for_symbol_now = true;
+                                            finish_trading_for_symbol_now = true;
+                                        }
+                                        None => {
+                                            // held qty doesn't pass the filters, it can't be sold anymore
+                                            error!("{symbol}: exit qty can't be calculated from held qty: {}, kept as dust", trading_symbol.qty);
+                                            // ***WARN:*** field modification
+                                            trading_symbol.close_as_dust();
+                                            final_trade_decision_clone.set(TradingDecision::Stop);
+                                            reading_market_depth_this_time = ReadMarketDepthNow::NO;
+                                        }
                                     }
                                 }
-                                //
-                                // END: TRADING TIME LIMIT
-                                //
                             }
                             //
-                            // END: TRADING LOGIC
+                            // END: TRADING TIME LIMIT
                             //
                         }
+                        //
+                        // END: TRADING LOGIC
+                        //
+                    }
+
+                    // position kept as dust has nothing to sell
+                    if finish_trading_for_symbol_now
+                        && trading_symbol.trade_decision != TradingDecision::Stop
+                    {
+                        info!("{symbol}: finishing trading now...");
+                        let comission =
+                            config.orderbook_monitor.comission_percent() / Decimal::ONE_HUNDRED;
+                        let mut position_closed = false;
+
+                        // protective OCO locks our qty - it has to be cancelled before we sell, it could
+                        // have been filled (or partly filled) in the meantime
+                        let sell_now = match protection.release(&account, &symbol) {
+                            ReleaseResult::Released => true,
+                            ReleaseResult::Filled(fill) => {
+                                info!("{symbol}: [EXCHANGE PROTECTION FILLED] sold qty: {}, received qty: {}",
+                                    fill.executed_qty, fill.quote_qty);
+                                if fill.complete {
+                                    // ***WARN:*** field modification
+                                    trading_symbol.exit_reason = ExitReason::ExchangeProtection;
+                                }
+
+                                // ***WARN:*** field modification
+                                position_closed = trading_symbol.apply_sell(&fill, comission);
+
+                                // OCO was cancelled after partial fill - the rest is sold by us
+                                if !position_closed {
+                                    match calculate_exit_qty(&trading_symbol) {
+                                        Some(exit_qty) => {
+                                            // ***WARN:*** field modification
+                                            trading_symbol.qty = exit_qty;
+                                        }
+                                        None => {
+                                            error!("{symbol}: qty left after partial OCO fill can't be sold: {}", trading_symbol.qty);
+                                        }
+                                    }
+                                }
+
+                                !position_closed
+                            }
+                            ReleaseResult::StillLocked => {
+                                warn!("{symbol}: protective OCO still locks the position - nothing is sold now, exit is tried again with next book update");
+                                false
+                            }
+                        };
 
-                        if finish_trading_for_symbol_now {
-                            info!("{symbol}: finishing trading now...");
+                        // qty locked by the OCO is never sold twice
+                        if sell_now && !protection.is_active() {
                             // symbol action
-                            let (received_qty, _) = symbol_buy_or_sell(
+                            match symbol_buy_or_sell(
                                 &config,
                                 &trading_mode,
                                 &trading_symbol,
                                 (api_key.clone(), secret_key.clone()),
-                            )
-                            .unwrap();
+                            ) {
+                                Ok(fill) => {
+                                    // ***WARN:*** field modification
+                                    position_closed = trading_symbol.apply_sell(&fill, comission);
+                                }
+                                Err(e) => {
+                                    error!("{symbol}: sell failed: {e} - exit is tried again with next book update");
+                                }
+                            }
+                        }
 
-                            // ***WARN:*** field modification
-                            trading_symbol.qty = received_qty;
+                        if position_closed {
+                            let dust_qty = trading_symbol.position.dust_qty;
+                            if dust_qty > decimal_zero {
+                                info!(
+                                    "{symbol}: dust left (below LOT_SIZE): {dust_qty} {}",
+                                    trading_symbol.position.base_asset
+                                );
+                            }
 
                             // finish trading
                             // ***WARN:*** field modification
                             trading_symbol.trade_decision = TradingDecision::Stop;
 
                             final_trade_decision_clone.set(TradingDecision::Stop);
+                        } else {
+                            // partial fill (or nothing sold) - the rest is sold when exit conditions are met again
+                            warn!("{symbol}: position is not closed, still holding {} - trading goes on",
+                                trading_symbol.qty);
+                            finish_trading_for_symbol_now = false;
                         }
+                    }
 
-                        if trading_symbol.trade_decision == TradingDecision::Decline
-                            || trading_symbol.trade_decision == TradingDecision::Stop
-                        {
-                            driving_signal_out.send(trading_symbol.clone()).unwrap();
-                            keep_running.store(false, Ordering::Relaxed);
+                    if trading_symbol.trade_decision == TradingDecision::Stop {
+                        // fees paid in other assets (BNB) are valued by their current price for the net profit
+                        for asset in trading_symbol.position.other_fee_assets() {
+                            let price_symbol =
+                                format!("{asset}{}", trading_symbol.position.quote_asset);
+
+                            match market.get_price(price_symbol.clone()) {
+                                Ok(p) => {
+                                    // ***WARN:*** field modification
+                                    trading_symbol.position.fee_prices.insert(
+                                        asset,
+                                        Decimal::try_from(p.price).unwrap_or_default(),
+                                    );
+                                }
+                                Err(e) => {
+                                    warn!("{symbol}: {price_symbol} price can't be read, {asset} fee is not in the net profit: {e:?}");
+                                }
+                            }
                         }
                     }
 
+                    if trading_symbol.trade_decision == TradingDecision::Decline
+                        || trading_symbol.trade_decision == TradingDecision::Stop
+                    {
+                        driving_signal_out.send(trading_symbol.clone()).unwrap();
+                        keep_running.store(false, Ordering::Relaxed);
+                    }
+
                     ///////////////////////////////////////////////////////////////////////////////
                     ////////////////////////// END: TRADING LOGIC HERE ////////////////////////////
                     ///////////////////////////////////////////////////////////////////////////////
                 }
-
-                //
-                // BEGIN: not relevant to main logic - this is binance algo for catching orderbook
-                //
-                if !listening_for_orderbook_updates {
-                    //
-                    if depth_order_book.final_update_id > last_update_id {
-                        //
-                        let next_update = last_update_id + 1;
-                        if depth_order_book.final_update_id >= next_update {
-                            //
-                            if depth_order_book.first_update_id <= next_update {
-                                last_update_id = depth_order_book.final_update_id;
-                                listening_for_orderbook_updates = true;
-                            }
+            }
+        };
+
+        //
+        // BEGIN: depth stream - reconnect with backoff, REST fallback and stale watchdog
+        //
+        let read_timeout = Duration::from_millis(DEPTH_STREAM_READ_TIMEOUT_MS);
+
+        match DepthStream::connect(&api_config, &endpoint, read_timeout) {
+            Ok(mut stream) => {
+                info!("{symbol}: depth stream connected");
+                // a fresh connection gets its own |depth_stream_stale_secs| to deliver the first diff
+                let connected_at = Instant::now();
+
+                while keep_running.load(Ordering::Relaxed) {
+                    // current config - it might be reloaded since last read
+                    stream_configs.refresh(&shared_config);
+                    let config = stream_configs.for_symbol(&symbol.to_string());
+
+                    match stream.read() {
+                        DepthStreamRead::Update(depth_order_book) => {
+                            handle_book_event(WebsocketEvent::DepthOrderBook(depth_order_book));
+                            // handling may take a while (orders) - it's not the stream's fault
+                            last_stream_update = Instant::now();
+                            stream_stale_exit.set(false);
+                            // backoff is reset only by a working stream, not by a connection which stays silent
+                            reconnect_backoff_ms =
+                                config.orderbook_monitor.depth_stream_reconnect_backoff_ms;
+                        }
+                        DepthStreamRead::Idle => {}
+                        DepthStreamRead::Closed(e) => {
+                            error!("{symbol}: depth stream error: {e}");
+                            break;
                         }
                     }
+
+                    let stale_after =
+                        Duration::from_secs(config.orderbook_monitor.depth_stream_stale_secs);
+                    let last_activity = last_stream_update.max(connected_at);
+                    if last_activity.elapsed() >= stale_after {
+                        warn!(
+                            "{symbol}: depth stream is stale (no update for {}s) - reconnecting",
+                            last_activity.elapsed().as_secs()
+                        );
+                        break;
+                    }
                 }
-                //
-                // END: not relevant to main logic - this is binance algo for catching orderbook
-                //
-            }
 
-            Ok(())
-        });
+                stream.close();
+            }
+            Err(e) => error!("{symbol}: depth stream can't connect: {e}"),
+        }
 
-        web_socket.connect_multiple_streams(&endpoints).unwrap(); // check error
-        let mut it_was_error = false;
-        if let Err(e) = web_socket.event_loop(&keep_running) {
-            error!("{e:?}");
-            it_was_error = true;
+        if !keep_running.load(Ordering::Relaxed) {
+            info!("{symbol}: websocket disconnected");
+            break;
         }
 
-        info!("{symbol}: websocket disconnected");
+        // stream is down - REST snapshots keep the position managed until the next connection attempt
+        warn!("{symbol}: depth stream is down, reconnecting in {reconnect_backoff_ms}ms (REST fallback until then)");
+        let reconnect_at = Instant::now() + Duration::from_millis(reconnect_backoff_ms);
+
+        while keep_running.load(Ordering::Relaxed) && Instant::now() < reconnect_at {
+            stream_configs.refresh(&shared_config);
+            let config = stream_configs.for_symbol(&symbol.to_string());
+            let om = &config.orderbook_monitor;
 
-        let _disconnection = web_socket.disconnect();
-        if it_was_error && final_trade_decision.get() == TradingDecision::Continue {
-            warn!("{symbol} websockect reconnecting as there was an error...");
-            continue;
+            let stale_exit_secs = om.depth_stream_stale_exit_secs;
+            if stale_exit_secs > 0 && last_stream_update.elapsed().as_secs() >= stale_exit_secs {
+                stream_stale_exit.set(true);
+            }
+
+            match market.get_custom_depth(symbol.to_string(), REST_FALLBACK_DEPTH) {
+                Ok(snapshot) => handle_book_event(WebsocketEvent::OrderBook(snapshot)),
+                Err(e) => warn!("{symbol}: REST fallback depth error: {e:?}"),
+            }
+
+            std::thread::sleep(Duration::from_millis(
+                om.depth_rest_fallback_period_ms.min(
+                    reconnect_at
+                        .saturating_duration_since(Instant::now())
+                        .as_millis() as u64,
+                ),
+            ));
+        }
+
+        stream_configs.refresh(&shared_config);
+        let config = stream_configs.for_symbol(&symbol.to_string());
+        let om = &config.orderbook_monitor;
+
+        reconnect_backoff_ms = (reconnect_backoff_ms * 2)
+            .min(om.depth_stream_reconnect_max_backoff_secs * 1000)
+            .max(1);
+
+        // checked here, not by the book handler - REST fallback may be down as well
+        let pending_entry_max_secs = match om.depth_stream_stale_exit_secs {
+            0 => PENDING_ENTRY_STREAM_DOWN_MAX_SECS,
+            secs => secs.min(PENDING_ENTRY_STREAM_DOWN_MAX_SECS),
+        };
+        if keep_running.load(Ordering::Relaxed)
+            && trading_symbol.trading_next_step == TradingNextStep::Join
+            && last_stream_update.elapsed().as_secs() >= pending_entry_max_secs
+        {
+            warn!(
+                "{symbol} depth stream is down for {}s - trade is given up",
+                last_stream_update.elapsed().as_secs()
+            );
+            // ***WARN:*** field modification
+            trading_symbol.trade_decision = TradingDecision::Decline;
+            final_trade_decision.set(TradingDecision::Decline);
+
+            driving_signal_out.send(trading_symbol.clone()).unwrap();
+            keep_running.store(false, Ordering::Relaxed);
         }
+        //
+        // END: depth stream
+        //
 
-        break;
+        if !keep_running.load(Ordering::Relaxed) {
+            info!("{symbol}: websocket disconnected");
+            break;
+        }
     }
 }
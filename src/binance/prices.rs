@@ -1,15 +1,8 @@
-use std::collections::HashMap;
 use crate::core::types::Symbol;
 use log::warn;
 use rust_decimal::{Decimal, RoundingStrategy};
 use rust_decimal_macros::dec;
-use serde::{Deserialize, Serialize};
-
-#[derive(Serialize, Deserialize)]
-struct PriceAPIResponse {
-    symbol: String,
-    price: String,
-}
+use std::collections::HashMap;
 
 // function provides value for dec.round_dp_with_strategy(<value>, RoundingStrategy::ToZero);
 pub fn get_round_number_based_on_ticker(tick_value: Decimal) -> Option<u32> {
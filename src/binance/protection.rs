@@ -0,0 +1,392 @@
+use crate::binance::prices::process_symbol_price;
+use crate::binance::trading::order_commissions;
+use crate::config::settings::ConfigStruct;
+use crate::core::calc::{calculate_exit_qty, percent_diff};
+use crate::core::position::OrderFill;
+use crate::core::trading::TradingSymbol;
+use crate::core::types::{CurrentTradingProfit, Symbol};
+use binance::account::Account;
+use binance::api::{Spot, API};
+use binance::util::build_signed_request;
+use log::{error, info, warn};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::thread;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/*
+Exchange-side protection of the position: OCO sell order placed right after the buy is filled
+- take profit leg (LIMIT_MAKER) at |good_profit_price|
+- stop leg (STOP_LOSS_LIMIT) at the loss limit price, limit price |exchange_protection_stop_limit_offset_percent|
+  below the stop price, so it's filled even if the price falls fast
+
+Once min profit is reached the stop follows the highest price like min profit leave does (the highest price
+minus |min_profit_crossed_allowed_drop_percent| as long as it's above min profit price).
+
+When bot process or its websocket dies, the position is still closed by the exchange.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtectionLevels {
+    pub take_profit_price: Decimal,
+    pub stop_price: Decimal,
+    pub stop_limit_price: Decimal,
+}
+
+// OCO placed on the exchange
+#[derive(Debug, Clone)]
+pub struct ProtectiveOco {
+    pub order_list_id: u64,
+    pub qty: Decimal,
+    pub levels: ProtectionLevels,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcoState {
+    Open,
+    // one of the legs was filled (or partly filled before the OCO was cancelled - fill is not complete then)
+    Filled(OrderFill),
+    // cancelled or expired without any fill
+    Closed,
+}
+
+// result of cancelling the OCO before bot sells by itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReleaseResult {
+    // OCO is gone, but it sold (part of) our qty before - the fill has to be applied first
+    Filled(OrderFill),
+    // OCO is gone (or there was none), whole position is free
+    Released,
+    // OCO can't be cancelled and its state is unknown - our qty is still locked, nothing can be sold
+    StillLocked,
+}
+
+// cancel attempts before the position is reported as still locked
+const RELEASE_ATTEMPTS: u64 = 3;
+const RELEASE_RETRY_DELAY_MS: u64 = 300;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct OrderList {
+    order_list_id: u64,
+    list_order_status: String,
+    orders: Vec<OrderListOrder>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct OrderListOrder {
+    order_id: u64,
+}
+
+// None if some of prices don't pass PRICE_FILTER
+pub fn protection_levels(
+    config: &ConfigStruct,
+    trading_symbol: &TradingSymbol,
+) -> Option<ProtectionLevels> {
+    let om = &config.orderbook_monitor;
+    let hundred = Decimal::ONE_HUNDRED;
+
+    if trading_symbol.qty.is_zero() {
+        return None;
+    }
+
+    // price for which our qty is worth |loss_limit_percent| less than we paid
+    let loss_limit_price = trading_symbol.used_qty
+        * (Decimal::ONE - om.loss_limit_percent / hundred)
+        / trading_symbol.qty;
+
+    let trailing_price = trading_symbol.highest_price_since_min_profit
+        * (Decimal::ONE - om.min_profit_crossed_allowed_drop_percent / hundred);
+
+    let stop_price = match trading_symbol.current_trading_profit {
+        CurrentTradingProfit::MinimalProfit if trailing_price > trading_symbol.min_profit_price => {
+            trailing_price.max(loss_limit_price)
+        }
+        _ => loss_limit_price,
+    };
+    let stop_limit_price =
+        stop_price * (Decimal::ONE - om.exchange_protection_stop_limit_offset_percent / hundred);
+
+    let symbol = trading_symbol.symbol.clone();
+    let filters_map = trading_symbol.filters_map.clone();
+
+    Some(ProtectionLevels {
+        take_profit_price: trading_symbol.good_profit_price,
+        stop_price: process_symbol_price(symbol.clone(), stop_price, filters_map.clone())?,
+        stop_limit_price: process_symbol_price(symbol, stop_limit_price, filters_map)?,
+    })
+}
+
+pub fn place_oco(
+    account: &Account,
+    symbol: &Symbol,
+    qty: Decimal,
+    levels: &ProtectionLevels,
+) -> Result<ProtectiveOco, String> {
+    let mut params: BTreeMap<String, String> = BTreeMap::new();
+    params.insert("symbol".into(), symbol.to_string());
+    params.insert("side".into(), "SELL".into());
+    params.insert("quantity".into(), qty.normalize().to_string());
+    params.insert(
+        "price".into(),
+        levels.take_profit_price.normalize().to_string(),
+    );
+    params.insert(
+        "stopPrice".into(),
+        levels.stop_price.normalize().to_string(),
+    );
+    params.insert(
+        "stopLimitPrice".into(),
+        levels.stop_limit_price.normalize().to_string(),
+    );
+    params.insert("stopLimitTimeInForce".into(), "GTC".into());
+
+    let request =
+        build_signed_request(params, account.recv_window).map_err(|e| format!("{e:?}"))?;
+    let order_list: OrderList = account
+        .client
+        .post_signed(API::Spot(Spot::Oco), request)
+        .map_err(|e| format!("{e:?}"))?;
+
+    Ok(ProtectiveOco {
+        order_list_id: order_list.order_list_id,
+        qty,
+        levels: levels.clone(),
+    })
+}
+
+fn order_list_params(symbol: &Symbol, oco: &ProtectiveOco) -> BTreeMap<String, String> {
+    let mut params: BTreeMap<String, String> = BTreeMap::new();
+    params.insert("symbol".into(), symbol.to_string());
+    params.insert("orderListId".into(), oco.order_list_id.to_string());
+
+    params
+}
+
+pub fn oco_state(
+    account: &Account,
+    symbol: &Symbol,
+    oco: &ProtectiveOco,
+) -> Result<OcoState, String> {
+    let request = build_signed_request(order_list_params(symbol, oco), account.recv_window)
+        .map_err(|e| format!("{e:?}"))?;
+    let order_list: OrderList = account
+        .client
+        .get_signed(API::Spot(Spot::OrderList), Some(request))
+        .map_err(|e| format!("{e:?}"))?;
+
+    if order_list.list_order_status != "ALL_DONE" {
+        return Ok(OcoState::Open);
+    }
+
+    // list is done - one leg was filled (the other one expired) or both were cancelled
+    let mut received_qty = Decimal::ZERO;
+    let mut sold_qty = Decimal::ZERO;
+    let mut commissions: BTreeMap<String, Decimal> = BTreeMap::new();
+
+    for order in &order_list.orders {
+        let order = account
+            .order_status(symbol.to_string(), order.order_id)
+            .map_err(|e| format!("{e:?}"))?;
+
+        received_qty += order
+            .cummulative_quote_qty
+            .parse::<Decimal>()
+            .unwrap_or_default();
+        let executed_qty = order.executed_qty.parse::<Decimal>().unwrap_or_default();
+        sold_qty += executed_qty;
+
+        if !executed_qty.is_zero() {
+            for (asset, fee) in order_commissions(account, symbol, order.order_id) {
+                *commissions.entry(asset).or_default() += fee;
+            }
+        }
+    }
+
+    if sold_qty.is_zero() {
+        return Ok(OcoState::Closed);
+    }
+
+    Ok(OcoState::Filled(OrderFill {
+        executed_qty: sold_qty,
+        quote_qty: received_qty,
+        complete: sold_qty >= oco.qty,
+        commissions,
+    }))
+}
+
+/*
+Cancels the OCO, so our qty can be sold by the bot or protected by a new OCO. The OCO could have been
+filled in the meantime (cancel fails then) or some of the take profit leg could have been filled before
+the cancel - state of the cancelled OCO is read, so the returned state tells it.
+ */
+pub fn cancel_oco(
+    account: &Account,
+    symbol: &Symbol,
+    oco: &ProtectiveOco,
+) -> Result<OcoState, String> {
+    let request = build_signed_request(order_list_params(symbol, oco), account.recv_window)
+        .map_err(|e| format!("{e:?}"))?;
+    let cancelled: Result<OrderList, _> = account
+        .client
+        .delete_signed(API::Spot(Spot::OrderList), Some(request));
+
+    match cancelled {
+        Ok(_) => match oco_state(account, symbol, oco)? {
+            OcoState::Open => Err("OCO is still open after cancel".to_string()),
+            state => Ok(state),
+        },
+        Err(e) => match oco_state(account, symbol, oco)? {
+            OcoState::Open => Err(format!("{e:?}")),
+            state => Ok(state),
+        },
+    }
+}
+
+/*
+Protection of one trade as orderbook executor sees it - the OCO currently on the exchange. Exchange
+is asked at most every |exchange_protection_check_period_secs| (status checks and retries after
+failed orders), trailing the stop doesn't wait.
+ */
+pub struct Protection {
+    oco: Option<ProtectiveOco>,
+    checked: Instant,
+    // the last order failed, next try after check period
+    failed: bool,
+}
+
+impl Default for Protection {
+    fn default() -> Self {
+        Self {
+            oco: None,
+            checked: Instant::now(),
+            failed: false,
+        }
+    }
+}
+
+impl Protection {
+    // keeps the OCO in line with current levels, Some if the OCO was filled (trade is over)
+    pub fn update(
+        &mut self,
+        account: &Account,
+        config: &ConfigStruct,
+        trading_symbol: &TradingSymbol,
+    ) -> Option<OrderFill> {
+        let symbol = &trading_symbol.symbol;
+        let check_now = self.checked.elapsed().as_secs()
+            >= config
+                .orderbook_monitor
+                .exchange_protection_check_period_secs;
+
+        if check_now {
+            self.checked = Instant::now();
+            self.failed = false;
+
+            if let Some(oco) = &self.oco {
+                match oco_state(account, symbol, oco) {
+                    Ok(OcoState::Filled(fill)) => return Some(fill),
+                    Ok(OcoState::Closed) => {
+                        warn!("{symbol} protective OCO {} is closed without fill (cancelled on the exchange?), placing new one", oco.order_list_id);
+                        self.oco = None;
+                    }
+                    Ok(OcoState::Open) => {}
+                    Err(e) => warn!("{symbol} protective OCO status error: {e}"),
+                }
+            }
+        }
+
+        if self.failed {
+            return None;
+        }
+
+        let levels = protection_levels(config, trading_symbol)?;
+
+        // stop only moves up
+        let replace = match &self.oco {
+            None => true,
+            Some(oco) => {
+                levels.stop_price > oco.levels.stop_price
+                    && percent_diff(oco.levels.stop_price, levels.stop_price)
+                        >= config
+                            .orderbook_monitor
+                            .exchange_protection_min_stop_move_percent
+            }
+        };
+
+        if !replace {
+            return None;
+        }
+
+        // our qty is locked by the old OCO - it has to be cancelled first
+        match self.release(account, symbol) {
+            ReleaseResult::Filled(fill) => return Some(fill),
+            ReleaseResult::StillLocked => {
+                self.failed = true;
+                return None;
+            }
+            ReleaseResult::Released => {}
+        }
+
+        let qty = calculate_exit_qty(trading_symbol)?;
+
+        match place_oco(account, symbol, qty, &levels) {
+            Ok(oco) => {
+                info!(
+                    "{symbol} protective OCO {} placed: qty: {qty}, take profit: {}, stop: {} (limit: {})",
+                    oco.order_list_id,
+                    levels.take_profit_price,
+                    levels.stop_price,
+                    levels.stop_limit_price
+                );
+                self.oco = Some(oco);
+            }
+            Err(e) => {
+                error!("{symbol} protective OCO can't be placed: {e}");
+                self.failed = true;
+            }
+        }
+
+        None
+    }
+
+    // cancels the OCO (before bot sells by itself), cancel is retried - qty locked by the OCO can't be sold
+    pub fn release(&mut self, account: &Account, symbol: &Symbol) -> ReleaseResult {
+        let Some(oco) = self.oco.clone() else {
+            return ReleaseResult::Released;
+        };
+
+        for attempt in 1..=RELEASE_ATTEMPTS {
+            match cancel_oco(account, symbol, &oco) {
+                Ok(OcoState::Filled(fill)) => {
+                    self.oco = None;
+                    return ReleaseResult::Filled(fill);
+                }
+                Ok(_) => {
+                    info!("{symbol} protective OCO {} cancelled", oco.order_list_id);
+                    self.oco = None;
+                    return ReleaseResult::Released;
+                }
+                Err(e) => {
+                    error!(
+                        "{symbol} protective OCO {} can't be cancelled ({attempt}/{RELEASE_ATTEMPTS}): {e}",
+                        oco.order_list_id
+                    );
+                }
+            }
+
+            if attempt < RELEASE_ATTEMPTS {
+                thread::sleep(Duration::from_millis(RELEASE_RETRY_DELAY_MS));
+            }
+        }
+
+        ReleaseResult::StillLocked
+    }
+
+    // protective OCO is on the exchange
+    pub fn is_active(&self) -> bool {
+        self.oco.is_some()
+    }
+}
@@ -0,0 +1,228 @@
+use crate::binance::endpoints::binance_api_config;
+use crate::binance::exchange_info::update_symbols_and_filters_list;
+use crate::config::settings::ConfigStruct;
+use crate::core::cli::data_dir_path;
+use crate::core::types::{Symbol, SymbolAction};
+use crate::mock::recording::{RecordedTick, RecordingLine};
+use crate::mock::scenario::{MockPrice, MockSymbol};
+use binance::websockets::{WebSockets, WebsocketEvent};
+use chrono::Utc;
+use log::{error, info, warn};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+// base and quote asset of the symbol, one of them is from |base_starting_assets|
+pub fn symbol_assets(
+    config: &ConfigStruct,
+    symbol: &str,
+    action: &SymbolAction,
+) -> (String, String) {
+    for asset in &config.base_starting_assets {
+        let asset = &asset.0;
+        match action {
+            SymbolAction::Buy if symbol.ends_with(asset.as_str()) => {
+                return (
+                    symbol[..symbol.len() - asset.len()].to_string(),
+                    asset.clone(),
+                );
+            }
+            SymbolAction::Sell if symbol.starts_with(asset.as_str()) => {
+                return (asset.clone(), symbol[asset.len()..].to_string());
+            }
+            _ => (),
+        }
+    }
+
+    (symbol.to_string(), String::new())
+}
+
+// recorded symbols with filters from exchangeInfo
+async fn recorded_symbols(config: &ConfigStruct, only_symbols: &[String]) -> Vec<MockSymbol> {
+    let valid_trading_symbols = Arc::new(RwLock::new(HashMap::new()));
+    let symbol_actions = Arc::new(RwLock::new(HashMap::new()));
+    let filters_map = Arc::new(RwLock::new(HashMap::new()));
+
+    update_symbols_and_filters_list(
+        config.clone(),
+        valid_trading_symbols.clone(),
+        symbol_actions.clone(),
+        filters_map.clone(),
+    )
+    .await;
+
+    let valid_trading_symbols = valid_trading_symbols.read().unwrap();
+    let symbol_actions = symbol_actions.read().unwrap();
+    let filters_map = filters_map.read().unwrap();
+    let filter = |symbol: &str, name: &str| -> Decimal {
+        *filters_map
+            .get(&format!("{symbol}-{name}"))
+            .unwrap_or(&Decimal::ZERO)
+    };
+
+    let mut symbols: Vec<MockSymbol> = valid_trading_symbols
+        .keys()
+        .filter(|s| only_symbols.is_empty() || only_symbols.contains(&s.0))
+        .filter_map(|s| {
+            let action = symbol_actions.get(s)?;
+            let (base_asset, quote_asset) = symbol_assets(config, &s.0, action);
+
+            Some(MockSymbol {
+                symbol: s.0.clone(),
+                base_asset,
+                quote_asset,
+                tick_size: filter(&s.0, "price-filter-tick-size"),
+                step_size: filter(&s.0, "lot-size-step-size"),
+                min_qty: filter(&s.0, "lot-size-min-qty"),
+                min_notional: filter(&s.0, "min-notional-min"),
+            })
+        })
+        .collect();
+    symbols.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+    for symbol in only_symbols {
+        if !valid_trading_symbols.contains_key(&Symbol(symbol.clone())) {
+            warn!("record: {symbol} is not tradable, it won't be recorded");
+        }
+    }
+
+    symbols
+}
+
+fn write_line(file: &mut BufWriter<File>, line: &RecordingLine) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *file, line)?;
+    file.write_all(b"\n")
+}
+
+// "record" subcommand: writes tickers of tradable symbols to a file in data dir (see RecordingLine)
+#[allow(clippy::result_large_err)]
+pub async fn run_record(
+    config: ConfigStruct,
+    data_dir: &str,
+    duration_secs: Option<u64>,
+    only_symbols: Vec<String>,
+) {
+    let symbols = recorded_symbols(&config, &only_symbols).await;
+    if symbols.is_empty() {
+        error!("record: no symbols to record");
+        return;
+    }
+
+    let path = data_dir_path(
+        data_dir,
+        &format!("record-{}.jsonl", Utc::now().format("%Y%m%d-%H%M%S")),
+    );
+    let mut file = match File::create(&path) {
+        Ok(f) => BufWriter::new(f),
+        Err(e) => {
+            error!("record: cannot create {path}: {e}");
+            return;
+        }
+    };
+
+    if let Err(e) = write_line(&mut file, &RecordingLine::Symbols(symbols.clone())) {
+        error!("record: cannot write {path}: {e}");
+        return;
+    }
+
+    info!("record: recording {} symbols to {path}", symbols.len());
+
+    let recorded: HashMap<String, bool> =
+        symbols.iter().map(|s| (s.symbol.clone(), true)).collect();
+    let mut last_num_trades: HashMap<String, u64> = HashMap::new();
+    let mut ticks_recorded: u64 = 0;
+    let started = Instant::now();
+    let keep_running = AtomicBool::new(true);
+    let api_config = binance_api_config(&config);
+    let ticker_all = String::from("!ticker@arr");
+
+    while keep_running.load(Ordering::Relaxed) {
+        let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
+            if let WebsocketEvent::DayTickerAll(ticker_events) = event {
+                let mut tick = RecordedTick {
+                    time: 0,
+                    prices: BTreeMap::new(),
+                };
+
+                for tick_event in ticker_events {
+                    if !recorded.contains_key(&tick_event.symbol) {
+                        continue;
+                    }
+
+                    let (bid, ask, bid_qty, ask_qty) = match (
+                        Decimal::from_str(&tick_event.best_bid),
+                        Decimal::from_str(&tick_event.best_ask),
+                        Decimal::from_str(&tick_event.best_bid_qty),
+                        Decimal::from_str(&tick_event.best_ask_qty),
+                    ) {
+                        (Ok(b), Ok(a), Ok(bq), Ok(aq)) => (b, a, bq, aq),
+                        _ => continue,
+                    };
+
+                    // number of trades is for last 24h, so only difference since last tick counts
+                    let previous = last_num_trades
+                        .insert(tick_event.symbol.clone(), tick_event.num_trades)
+                        .unwrap_or(tick_event.num_trades);
+
+                    tick.time = tick.time.max(tick_event.event_time);
+                    tick.prices.insert(
+                        tick_event.symbol.clone(),
+                        MockPrice {
+                            bid,
+                            ask,
+                            bid_qty,
+                            ask_qty,
+                            trades: tick_event.num_trades.saturating_sub(previous),
+                        },
+                    );
+                }
+
+                if !tick.prices.is_empty() {
+                    if let Err(e) = write_line(&mut file, &RecordingLine::Tick(tick)) {
+                        error!("record: cannot write {path}: {e}");
+                        keep_running.store(false, Ordering::Relaxed);
+                    }
+                    ticks_recorded += 1;
+
+                    if ticks_recorded.is_multiple_of(60) {
+                        info!("record: {ticks_recorded} ticks recorded");
+                    }
+                }
+
+                if let Some(duration_secs) = duration_secs {
+                    if started.elapsed().as_secs() >= duration_secs {
+                        keep_running.store(false, Ordering::Relaxed);
+                    }
+                }
+            }
+
+            Ok(())
+        });
+
+        if let Err(e) = web_socket.connect_with_config(&ticker_all, &api_config) {
+            error!("record: cannot connect to {ticker_all}: {e:?}");
+            break;
+        }
+        if let Err(e) = web_socket.event_loop(&keep_running) {
+            error!("{e:?}");
+        }
+        if let Err(e) = web_socket.disconnect() {
+            warn!("record: disconnect failed: {e:?}");
+        }
+
+        if keep_running.load(Ordering::Relaxed) {
+            info!("record: disconnected - reconnecting");
+        }
+    }
+
+    if let Err(e) = file.flush() {
+        error!("record: cannot write {path}: {e}");
+    }
+
+    info!("record: finished, {ticks_recorded} ticks recorded to {path}");
+}
@@ -1,10 +1,10 @@
 use crate::binance::exchange_info::update_symbols_and_filters_list;
 use crate::binance::object::BinanceObj;
 use crate::binance::symbols_monitor::all_trades_websocket;
+use crate::config::reload::config_watcher;
 use crate::core::engine::engine;
-use crate::core::types::Symbol;
+use crate::core::structs::TradeCandidate;
 use log::info;
-use rust_decimal::Decimal;
 use std::sync::Arc;
 use std::thread;
 use tokio::sync::mpsc;
@@ -19,8 +19,8 @@ impl BinanceObj {
         info!("starting exchange object");
         #[allow(clippy::type_complexity)]
         let (symbol_monitor_sender, symbol_monitor_receiver): (
-            UnboundedSender<(Symbol, Decimal)>,
-            UnboundedReceiver<(Symbol, Decimal)>,
+            UnboundedSender<TradeCandidate>,
+            UnboundedReceiver<TradeCandidate>,
         ) = mpsc::unbounded_channel();
 
         info!("fetching exchangeInfo for the first time...");
@@ -28,16 +28,34 @@ impl BinanceObj {
         let s = self.clone();
         tokio::spawn(async move {
             // comment
+            let config = s.state.lock().unwrap().config.read().unwrap().clone();
             let valid_symbols_map = s.state.lock().unwrap().valid_trading_symbols.clone();
             let filters_map = s.state.lock().unwrap().filters_map.clone();
             let symbol_actions = s.state.lock().unwrap().default_symbol_action.clone();
-            update_symbols_and_filters_list(valid_symbols_map, symbol_actions, filters_map).await;
+            update_symbols_and_filters_list(
+                (*config).clone(),
+                valid_symbols_map,
+                symbol_actions,
+                filters_map,
+            )
+            .await;
         })
         .await
         .expect("cannot retrieve exchangeInfo");
         info!("exchangeInfo fetched for the first time...");
 
         tokio::time::sleep(time::Duration::from_secs(2)).await;
+
+        let config_file = self.state.lock().unwrap().config_file.clone();
+        if let Some(config_file) = config_file {
+            info!("starting config watcher thread...");
+            let s = self.clone();
+            let config = s.state.lock().unwrap().config.clone();
+            thread::spawn(move || {
+                // comment
+                config_watcher(&config_file, config);
+            });
+        }
         //
         // THREAD 1: perodically fetches exchangeInfo and updates symbols list and filters for them
         //           These data are exposed outside by list_symbols() and process_price_and_qty()
@@ -52,6 +70,7 @@ impl BinanceObj {
             let api_key = s.state.lock().unwrap().api_key.clone();
             let secret_key = s.state.lock().unwrap().secret_key.clone();
             let trading_mode = s.state.lock().unwrap().trading_mode.clone();
+            let monitor_health = s.state.lock().unwrap().symbols_monitor_health.clone();
             tokio::spawn(async move {
                 // comment
                 engine(
@@ -59,6 +78,7 @@ impl BinanceObj {
                     filters_map,
                     symbol_actions,
                     symbol_monitor_receiver,
+                    monitor_health,
                     (api_key, secret_key),
                     trading_mode,
                 )
@@ -72,9 +92,15 @@ impl BinanceObj {
             let s = self.clone();
             let config = s.state.lock().unwrap().config.clone();
             let valid_symbols_map = s.state.lock().unwrap().valid_trading_symbols.clone();
+            let monitor_health = s.state.lock().unwrap().symbols_monitor_health.clone();
             thread::spawn(move || {
                 // comment
-                all_trades_websocket(config, valid_symbols_map, symbol_monitor_sender);
+                all_trades_websocket(
+                    config,
+                    valid_symbols_map,
+                    symbol_monitor_sender,
+                    monitor_health,
+                );
             });
         }
         info!("monitor thread started");
@@ -85,12 +111,17 @@ impl BinanceObj {
         let handle_exchange_info = tokio::spawn(async move {
             // comment
             loop {
-                let config = s.state.lock().unwrap().config.clone();
+                let config = s.state.lock().unwrap().config.read().unwrap().clone();
                 let valid_symbols_map = s.state.lock().unwrap().valid_trading_symbols.clone();
                 let filters_map = s.state.lock().unwrap().filters_map.clone();
                 let symbol_actions = s.state.lock().unwrap().default_symbol_action.clone();
-                update_symbols_and_filters_list(valid_symbols_map, symbol_actions, filters_map)
-                    .await;
+                update_symbols_and_filters_list(
+                    (*config).clone(),
+                    valid_symbols_map,
+                    symbol_actions,
+                    filters_map,
+                )
+                .await;
                 time::sleep(time::Duration::from_secs(
                     config.exchange_info_fetch_delay_secs,
                 ))
@@ -1,13 +1,17 @@
-use std::collections::HashMap;
+use crate::config::reload::SharedConfig;
+use crate::core::structs::SharedStreamHealth;
 use crate::core::types::{Symbol, SymbolAction, TradingMode};
-use crate::ConfigStruct;
 use rust_decimal::Decimal;
+use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
 #[derive(Clone)]
 pub struct BinanceState {
     // *********************** BEGIN: basic data types ********************* //
-    pub config: ConfigStruct,
+    // updated by config watcher when config file changes
+    pub config: SharedConfig,
+    // config file reloaded by config watcher, None - config is never reloaded
+    pub config_file: Option<String>,
 
     // valid_trading_symbols(): genuine trading symbols taken directly from exchangeInfo.
     pub valid_trading_symbols: Arc<RwLock<HashMap<Symbol, bool>>>,
@@ -33,6 +37,9 @@ pub struct BinanceState {
     // default symbol => action, for BTCUSDT is BUY, but for symbols USDTXXX, it's sell
     pub default_symbol_action: Arc<RwLock<HashMap<Symbol, SymbolAction>>>,
 
+    // health of symbols monitor stream, updated by symbols monitor and checked by the engine
+    pub symbols_monitor_health: SharedStreamHealth,
+
     // for API account
     pub api_key: String,
     pub secret_key: String,
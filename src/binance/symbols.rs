@@ -1,5 +1,6 @@
 use serde::*;
 
+#[allow(dead_code)]
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BinanceSymbol {
@@ -30,6 +31,7 @@ pub struct BinanceSymbol {
     pub permissions: Vec<String>,
 }
 
+#[allow(dead_code)]
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Filter {
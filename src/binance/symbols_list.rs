@@ -0,0 +1,115 @@
+use crate::binance::endpoints::exchange_info_apis;
+use crate::binance::exchange_info::{
+    check_symbol_inclusion, fetch_exchange_info, randomly_select_api_address, SymbolInclusion,
+};
+use crate::binance::filters::FiltersParser;
+use crate::config::settings::ConfigStruct;
+use crate::core::json::extract_json_data;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+// symbol as "symbols" subcommand shows it
+#[derive(Serialize, Debug, Clone)]
+pub struct SymbolListEntry {
+    #[serde(flatten)]
+    pub inclusion: SymbolInclusion,
+    pub included: bool,
+    // readable exclusion reason
+    pub reason: Option<String>,
+    pub tick_size: Decimal,
+    pub step_size: Decimal,
+    pub min_qty: Decimal,
+    pub min_notional: Decimal,
+}
+
+// every symbol from exchangeInfo with inclusion status, only symbols with |asset| if set
+pub async fn list_symbols(
+    config: &ConfigStruct,
+    asset: Option<&str>,
+) -> Result<Vec<SymbolListEntry>, String> {
+    let api = randomly_select_api_address(exchange_info_apis(config)).await;
+    let json_string = fetch_exchange_info(api.clone())
+        .await
+        .ok_or(format!("cannot fetch exchangeInfo from {api}"))?;
+    let json_data =
+        extract_json_data(&json_string).ok_or(format!("cannot parse exchangeInfo from {api}"))?;
+    let symbols_data = json_data["symbols"]
+        .as_array()
+        .ok_or(format!("no symbols in exchangeInfo from {api}"))?;
+
+    let mut entries: Vec<SymbolListEntry> = symbols_data
+        .iter()
+        .map(|s| {
+            let inclusion = check_symbol_inclusion(config, s);
+            let filter = FiltersParser::new(s["filters"].as_array().map_or(&[], |f| f.as_slice()));
+
+            SymbolListEntry {
+                included: inclusion.is_included(),
+                reason: inclusion.exclusion.as_ref().map(|e| e.to_string()),
+                inclusion,
+                tick_size: filter.tick_size.normalize(),
+                step_size: filter.lot_step_size.normalize(),
+                min_qty: filter.lot_min_qty.normalize(),
+                min_notional: filter.min_notional_min.normalize(),
+            }
+        })
+        .filter(|e| match asset {
+            Some(asset) => {
+                e.inclusion.base_asset.eq_ignore_ascii_case(asset)
+                    || e.inclusion.quote_asset.eq_ignore_ascii_case(asset)
+            }
+            None => true,
+        })
+        .collect();
+    entries.sort_by(|a, b| a.inclusion.symbol.cmp(&b.inclusion.symbol));
+
+    Ok(entries)
+}
+
+// "symbols" subcommand: what the bot would trade (and why not the rest), Err when symbols can't be listed
+pub async fn run_symbols(
+    config: ConfigStruct,
+    json: bool,
+    asset: Option<String>,
+) -> Result<(), String> {
+    let entries = list_symbols(&config, asset.as_deref()).await?;
+
+    if json {
+        let json = serde_json::to_string_pretty(&entries)
+            .map_err(|e| format!("cannot serialize symbols: {e}"))?;
+        println!("{json}");
+        return Ok(());
+    }
+
+    println!(
+        "{:<16} {:<8} {:<6} {:>14} {:>14} {:>14} {:>14}  REASON",
+        "SYMBOL", "INCLUDED", "ACTION", "TICK SIZE", "STEP SIZE", "MIN QTY", "MIN NOTIONAL"
+    );
+    for e in &entries {
+        let line = format!(
+            "{:<16} {:<8} {:<6} {:>14} {:>14} {:>14} {:>14}  {}",
+            e.inclusion.symbol,
+            if e.included { "yes" } else { "no" },
+            e.inclusion
+                .action
+                .as_ref()
+                .map(|a| format!("{a:?}"))
+                .unwrap_or("-".to_string()),
+            e.tick_size,
+            e.step_size,
+            e.min_qty,
+            e.min_notional,
+            e.reason.as_deref().unwrap_or("")
+        );
+        println!("{}", line.trim_end());
+    }
+
+    let included = entries.iter().filter(|e| e.included).count();
+    println!(
+        "{} symbols, {included} included, {} excluded",
+        entries.len(),
+        entries.len() - included
+    );
+
+    Ok(())
+}
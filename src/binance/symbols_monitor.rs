@@ -1,32 +1,53 @@
+use crate::binance::depth_stream::{is_read_timeout, set_read_timeout};
+use crate::binance::endpoints::binance_api_config;
+use crate::config::reload::{SharedConfig, SymbolConfigs};
 use crate::config::settings::ConfigStruct;
+use crate::core::activity::{ActivitySample, SymbolActivity};
+use crate::core::calc::percent_diff;
 use crate::core::post_window_monitor::calculate_post_window;
 use crate::core::pre_window_monitor::calculate_pre_window;
+use crate::core::price_history::{window_spans, PriceHistory, PricePoint, WindowVolumes};
+use crate::core::structs::{
+    EntrySignal, SharedStreamHealth, StreamStatus, TradeCandidate, WindowPercentChanges,
+};
 use crate::core::types::{SendToTradeDecision, Symbol};
 use crate::core::window_monitor::calculate_window;
+use binance::errors::ErrorKind;
 use binance::websockets::{WebSockets, WebsocketEvent};
-use log::{error, info};
+use log::{error, info, warn};
 use rust_decimal::{Decimal, RoundingStrategy};
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::ops::Not;
+use std::fs;
 use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, RwLock};
-use std::time::Instant;
+use std::thread;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc::UnboundedSender;
 
+// stream read waits at most this long, so hung stream is noticed even if no data comes at all
+const STREAM_READ_TIMEOUT_MS: u64 = 1000;
+// health file is written when the stream status changes, otherwise once per this period
+const HEALTH_WRITE_PERIOD_SECS: u64 = 10;
+
+#[allow(clippy::result_large_err)]
 pub fn all_trades_websocket(
-    config: ConfigStruct,
+    shared_config: SharedConfig,
     valid_trading_symbols: Arc<RwLock<HashMap<Symbol, bool>>>,
-    channel_to_engine: UnboundedSender<(Symbol, Decimal)>,
+    channel_to_engine: UnboundedSender<TradeCandidate>,
+    symbols_monitor_health: SharedStreamHealth,
 ) {
     let mut analyze_reminder_shown = false;
     let mut analyze_reminder_time = Instant::now();
     let mut initial_time_passed = false;
 
-    let mut prices_map: HashMap<String, Vec<Decimal>> = HashMap::new();
-    let mut symbols_currently_selected_to_monitor: HashMap<String, Decimal> = HashMap::new();
+    let mut prices_map: HashMap<String, PriceHistory> = HashMap::new();
+    let mut symbols_currently_selected_to_monitor: HashMap<
+        String,
+        (SendToTradeDecision, WindowPercentChanges),
+    > = HashMap::new();
     let mut symbols_pre_window_with_percent_changes: Rc<Cell<HashMap<String, Decimal>>> =
         Rc::new(Cell::new(HashMap::new()));
     let mut symbols_window_with_percent_changes: Rc<Cell<HashMap<String, Decimal>>> =
@@ -42,23 +63,75 @@ pub fn all_trades_websocket(
     let mut temp_monitored_symbols: HashMap<String, (Instant, Decimal)> = HashMap::new();
     let mut biggest_monitored: HashMap<String, Decimal> = HashMap::new();
 
-    // variability of symbols
-    let mut symbols_vars_timestamps: HashMap<String, Instant> = HashMap::new();
-    let mut symbols_variability_count: HashMap<String, u64> = HashMap::new();
-    let mut symbols_var_last_key: HashMap<String, String> = HashMap::new();
+    // activity (trades, volume, book updates, volatility) of symbols
+    let mut symbols_activity: HashMap<String, SymbolActivity> = HashMap::new();
+
+    let health_publisher = RefCell::new(HealthPublisher::default());
+
+    // per-symbol configs, resolved again only after config reload
+    let mut symbol_configs = SymbolConfigs::new(&shared_config);
 
     let keep_running = AtomicBool::new(true); // Used to control the event loop
     let agg_trade = String::from("!ticker@arr");
     loop {
         //
+        // BEGIN: stream gap - price histories (and activity) would span it, so they are collected again
         //
+        let stream_gap = symbols_monitor_health
+            .read()
+            .unwrap()
+            .last_event
+            .map(|t| {
+                t.elapsed().as_secs() >= shared_config.read().unwrap().symbols_monitor_stale_secs
+            })
+            .unwrap_or(false);
+
+        if stream_gap && !prices_map.is_empty() {
+            warn!("symbols monitor: stream gap - price lists are dropped, symbols are analyzed again once they are complete");
+            prices_map.clear();
+            symbols_activity.clear();
+            symbols_currently_selected_to_monitor.clear();
+            remembered_symbols.clear();
+            temp_monitored_symbols.clear();
+            biggest_monitored.clear();
+            symbols_pre_window_with_percent_changes = Rc::new(Cell::new(HashMap::new()));
+            symbols_window_with_percent_changes = Rc::new(Cell::new(HashMap::new()));
+            symbols_post_window_with_percent_changes = Rc::new(Cell::new(HashMap::new()));
+            initial_time_passed = false;
+        }
+        //
+        // END: stream gap
         //
+
         let mut web_socket = WebSockets::new(|event: WebsocketEvent| {
             if let WebsocketEvent::DayTickerAll(ticker_events) = event {
                 {
                     list_valid_symbols = valid_trading_symbols.read().unwrap().clone();
                 }
 
+                // current config - it might be reloaded since last tick
+                let global_config = shared_config.read().unwrap().clone();
+                symbol_configs.refresh(&shared_config);
+
+                // event time of this update
+                let update_time = ticker_events
+                    .iter()
+                    .map(|t| t.event_time)
+                    .max()
+                    .unwrap_or_default();
+
+                // heartbeat
+                {
+                    let mut health = symbols_monitor_health.write().unwrap();
+                    health.connected = true;
+                    health.stale = false;
+                    health.last_event = Some(Instant::now());
+                    health.last_event_time = update_time;
+                }
+                health_publisher
+                    .borrow_mut()
+                    .publish(&global_config, &symbols_monitor_health);
+
                 for tick_event in ticker_events {
                     //
                     // BEGIN: cyclic reminder
@@ -77,7 +150,9 @@ pub fn all_trades_websocket(
                     //
 
                     let symbol = tick_event.symbol.clone();
-                    let symbol_type = Symbol(tick_event.symbol.clone()).clone();
+
+                    // thresholds for this symbol (with per-symbol overrides)
+                    let config = symbol_configs.for_symbol(&symbol);
 
                     if list_valid_symbols.contains_key(&Symbol(symbol.to_string())) {
                         //
@@ -85,167 +160,108 @@ pub fn all_trades_websocket(
                         //
 
                         //
-                        // BEGIN: volatility check logic, counts are later available in symbols_var_count map
+                        // BEGIN: activity metrics, later available in symbols_activity map
                         //
-                        let current_symbol_var_key = format!(
-                            "{}-{}-{}-{}-{}",
-                            tick_event.num_trades,
-                            tick_event.price_change,
-                            tick_event.best_bid,
-                            tick_event.best_ask,
-                            tick_event.volume
-                        );
-
-                        if symbols_variability_count.contains_key(symbol.as_str()) {
-                            // maps already contain this symbol
-
-                            // get key of this symbol to compare with new one
-                            let previous_key = symbols_var_last_key
-                                .get(symbol.clone().as_str())
-                                .unwrap()
-                                .clone();
-
-                            if previous_key != current_symbol_var_key {
-                                // there is a change in volatility - keys mismatch
-
-                                // update key
-                                symbols_var_last_key
-                                    .insert(symbol.clone(), current_symbol_var_key.clone());
-
-                                // get timestamp
-                                let symbol_time_measurement = symbols_vars_timestamps
-                                    .get(symbol.clone().as_str())
-                                    .unwrap();
-
-                                if symbol_time_measurement.elapsed().as_secs()
-                                    >= config.symbol_monitor.symbol_price_violatile_check_time_secs
-                                {
-                                    // timeout - reset to timer and counts
-                                    symbols_variability_count.insert(symbol.clone(), 1);
-                                    symbols_vars_timestamps.insert(symbol.clone(), Instant::now());
-                                } else {
-                                    // still no timeout, still in assessment window - update volatility counter
-                                    *symbols_variability_count
-                                        .entry(symbol.clone())
-                                        .or_insert_with(|| 1) += 1;
-                                }
-                            }
-                        }
-
-                        if !symbols_variability_count.contains_key(symbol.as_str()) {
-                            // this symbol is NOT YET present in maps - we add with default values
-                            symbols_variability_count.insert(symbol.clone(), 1);
-                            symbols_var_last_key.insert(symbol.clone(), current_symbol_var_key);
-                            symbols_vars_timestamps.insert(symbol.clone(), Instant::now());
-                        }
+                        let sample = ActivitySample {
+                            time: tick_event.event_time,
+                            num_trades: tick_event.num_trades,
+                            quote_volume: Decimal::from_str(&tick_event.quote_volume)
+                                .unwrap_or_default(),
+                            best_bid: Decimal::from_str(&tick_event.best_bid).unwrap_or_default(),
+                            best_bid_qty: Decimal::from_str(&tick_event.best_bid_qty)
+                                .unwrap_or_default(),
+                            best_ask: Decimal::from_str(&tick_event.best_ask).unwrap_or_default(),
+                            best_ask_qty: Decimal::from_str(&tick_event.best_ask_qty)
+                                .unwrap_or_default(),
+                        };
+                        symbols_activity
+                            .entry(symbol.clone())
+                            .or_default()
+                            .add(sample, config.symbol_monitor.activity_window_secs);
                         //
-                        // END: volatility check logic, counts are later available in symbols_var_count map
+                        // END: activity metrics, later available in symbols_activity map
                         //
 
-                        let mut old_list: Vec<Decimal> = Vec::new();
-
                         // current price from stream
                         let current_price =
                             Decimal::from_str(tick_event.best_bid.clone().as_str()).unwrap();
 
-                        if prices_map.contains_key(symbol.as_str()) {
-                            old_list = prices_map.get(symbol.as_str()).unwrap().clone();
-                        }
-
-                        // construct new list with new added price to the end of the list
-                        let new_symbols_percentage_list =
-                            add_price_to_list(config.clone(), old_list, current_price);
-
-                        *prices_map
-                            .entry(tick_event.symbol.clone())
-                            .or_insert_with(|| new_symbols_percentage_list.clone()) =
-                            new_symbols_percentage_list.clone();
-
-                        // because we have 3 parts of the list: pre window, window and post window
-                        // WARNING: in config we have to have symbol length suitable for
-                        // pre/window/post
-                        let percentage_change_list_length =
-                            config.symbol_monitor.symbol_price_list_length;
+                        // history is keyed by event time, so windows are real-time spans even if
+                        // the symbol is missing in some updates
+                        let (pre_window_secs, main_window_secs, post_window_secs) =
+                            window_spans(&config.symbol_monitor);
+                        let history_secs = pre_window_secs + main_window_secs + post_window_secs;
+
+                        let price_point = PricePoint {
+                            price: current_price,
+                            num_trades: tick_event.num_trades,
+                            quote_volume: Decimal::from_str(&tick_event.quote_volume)
+                                .unwrap_or_default(),
+                        };
+                        let price_history = prices_map.entry(symbol.clone()).or_default();
+                        price_history.add(tick_event.event_time, price_point, history_secs);
 
                         ////////////////////////////////////////////////////////////////////////
                         ////////////////////////////////////////////////////////////////////////
                         ////////////////////////////////////////////////////////////////////////
 
-                        if new_symbols_percentage_list.len() == percentage_change_list_length {
+                        if price_history.is_complete(history_secs) {
                             //
-                            // we have required count of prices in our list, we can review price changes now
-                            // and divide them for pre, main and post window if needed.
+                            // we have prices for entire history, we can review price changes now
+                            // in pre, main and post window (oldest to newest)
                             //
                             if !initial_time_passed {
                                 initial_time_passed = true;
                                 info!("!!! full symbols lists with prices have been created.")
                             }
 
-                            let all_symbols_length = percentage_change_list_length;
-
-                            let mut main_divider = 1;
-                            let mut main_window_divider_start = 0;
-                            let mut main_window_divider_end = all_symbols_length;
-                            let mut pre_window_divider_start = 0;
-                            let mut pre_window_divider_end = all_symbols_length;
-                            let mut post_window_divider_start = 0;
-                            let mut post_window_divider_end = all_symbols_length;
-
-                            if config.symbol_monitor.pre_window_analysis {
-                                main_divider += 1;
-                            }
-
-                            if config.symbol_monitor.post_window_analysis {
-                                main_divider += 1;
-                            }
-
-                            if main_divider == 2 {
-                                // either pre or post window is enabled
-
-                                if config.symbol_monitor.pre_window_analysis {
-                                    // only pre window is enabled
-                                    // main window starts from middle, otherwise default value is fine
-                                    main_window_divider_start = all_symbols_length / 2;
-                                    pre_window_divider_end = main_window_divider_start - 1;
-                                }
-
-                                if config.symbol_monitor.post_window_analysis {
-                                    // only post window is enabled
-                                    // main window start from beginning, but ends in the middle
-                                    main_window_divider_end = all_symbols_length / 2;
-                                    post_window_divider_start = main_window_divider_end + 1;
-                                }
-                            }
-
-                            if main_divider == 3 {
-                                // both pre and post window are enabled
-                                pre_window_divider_end = all_symbols_length / main_divider;
-                                main_window_divider_start = pre_window_divider_end + 1;
-                                main_window_divider_end = pre_window_divider_end * 2;
-                                post_window_divider_start = main_window_divider_end + 1;
-                                post_window_divider_end = all_symbols_length;
-                            }
-
                             // to allow symbol to be sent for trading this is the most important factor
                             let mut symbol_classify_decision = SendToTradeDecision::Negative;
+                            let mut window_percent_changes = WindowPercentChanges::default();
+
+                            // volume traded in windows - price moves made by a couple of tiny
+                            // trades are not signals
+                            let window_volumes = WindowVolumes {
+                                pre_window: config.symbol_monitor.pre_window_analysis.then(|| {
+                                    price_history.window_volume(
+                                        history_secs,
+                                        post_window_secs + main_window_secs,
+                                    )
+                                }),
+                                window: price_history.window_volume(
+                                    post_window_secs + main_window_secs,
+                                    post_window_secs,
+                                ),
+                                post_window: config
+                                    .symbol_monitor
+                                    .post_window_analysis
+                                    .then(|| price_history.window_volume(post_window_secs, 0)),
+                            };
+                            let volume_confirmed =
+                                config.symbol_monitor.volume_confirmed(&window_volumes);
+                            window_percent_changes.volumes = window_volumes;
 
                             //
                             // BEGIN: main window analysis
                             //
-                            let main_window = &new_symbols_percentage_list
-                                [main_window_divider_start..main_window_divider_end];
+                            let main_window = price_history
+                                .window(post_window_secs + main_window_secs, post_window_secs);
                             let window_status = calculate_window(
-                                config.clone(),
+                                &config,
                                 symbol.as_str(),
-                                main_window,
+                                &main_window,
                                 Rc::clone(&symbols_window_with_percent_changes),
                             );
 
                             symbols_window_with_percent_changes =
                                 window_status.symbols_window_with_percent_changes;
+                            window_percent_changes.window = window_status.percent_change;
+                            window_percent_changes.window_stats = window_status.stats;
 
-                            if window_status.drop_threshold_reached
-                                || window_status.rise_threshold_reached
+                            if (window_status.drop_threshold_reached
+                                || window_status.rise_threshold_reached)
+                                && window_status.stats_thresholds_reached
+                                && volume_confirmed
                             {
                                 symbol_classify_decision =
                                     SendToTradeDecision::MainWindowPositiveAnalysis;
@@ -265,20 +281,25 @@ pub fn all_trades_websocket(
                                 // main window positive decision has to be true, otherwise we don't
                                 // analyze this as this is supplement for main window
 
-                                let pre_window = &new_symbols_percentage_list
-                                    [pre_window_divider_start..pre_window_divider_end];
+                                let pre_window = price_history
+                                    .window(history_secs, post_window_secs + main_window_secs);
                                 let pre_window_status = calculate_pre_window(
-                                    config.clone(),
+                                    &config,
                                     symbol.as_str(),
-                                    pre_window,
+                                    &pre_window,
                                     Rc::clone(&symbols_pre_window_with_percent_changes),
                                 );
 
                                 symbols_pre_window_with_percent_changes =
                                     pre_window_status.symbols_pre_window_with_percent_changes;
-
-                                if pre_window_status.drop_threshold_reached
-                                    || pre_window_status.rise_threshold_reached
+                                window_percent_changes.pre_window =
+                                    Some(pre_window_status.percent_change);
+                                window_percent_changes.pre_window_stats =
+                                    Some(pre_window_status.stats);
+
+                                if (pre_window_status.drop_threshold_reached
+                                    || pre_window_status.rise_threshold_reached)
+                                    && pre_window_status.stats_thresholds_reached
                                 {
                                     symbol_classify_decision =
                                         SendToTradeDecision::MainWindowAndPreWindowPositiveAnalysis;
@@ -295,20 +316,24 @@ pub fn all_trades_websocket(
                                 && symbol_classify_decision
                                     == SendToTradeDecision::MainWindowPositiveAnalysis
                             {
-                                let post_window = &new_symbols_percentage_list
-                                    [post_window_divider_start..post_window_divider_end];
+                                let post_window = price_history.window(post_window_secs, 0);
                                 let post_window_status = calculate_post_window(
-                                    config.clone(),
+                                    &config,
                                     symbol.as_str(),
-                                    post_window,
+                                    &post_window,
                                     Rc::clone(&symbols_window_with_percent_changes),
                                 );
 
                                 symbols_post_window_with_percent_changes =
                                     post_window_status.symbols_post_window_with_percent_changes;
-
-                                if post_window_status.drop_threshold_reached
-                                    || post_window_status.rise_threshold_reached
+                                window_percent_changes.post_window =
+                                    Some(post_window_status.percent_change);
+                                window_percent_changes.post_window_stats =
+                                    Some(post_window_status.stats);
+
+                                if (post_window_status.drop_threshold_reached
+                                    || post_window_status.rise_threshold_reached)
+                                    && post_window_status.stats_thresholds_reached
                                 {
                                     if symbol_classify_decision == SendToTradeDecision::MainWindowAndPreWindowPositiveAnalysis {
                                         symbol_classify_decision = SendToTradeDecision::MainWindowAndBothWindowsPositiveAnalysis;
@@ -335,8 +360,13 @@ pub fn all_trades_websocket(
                                     if !symbols_currently_selected_to_monitor.contains_key(&symbol)
                                     {
                                         let s = symbol.clone();
-                                        symbols_currently_selected_to_monitor
-                                            .insert(s, window_status.percent_change);
+                                        symbols_currently_selected_to_monitor.insert(
+                                            s,
+                                            (
+                                                symbol_classify_decision.clone(),
+                                                window_percent_changes,
+                                            ),
+                                        );
                                     }
                                 }
                                 SendToTradeDecision::Negative => {
@@ -367,121 +397,217 @@ pub fn all_trades_websocket(
                         ////////////////////////////////////////////////////////////////////////
                         ////////////////////////////////////////////////////////////////////////
                     }
+                }
 
-                    for (k, v) in symbols_currently_selected_to_monitor.iter() {
-                        let key = format!("{}{}", k, v);
-
-                        let event_price = tick_event.best_bid.clone();
-
-                        if remembered_symbols.contains_key(&symbol) {
-                            // we have this symbol so we check if it's price needs to be updated
-                            let timestamp_now = Instant::now();
-
-                            // take old price from remembered hashmap
-                            let (_, old_price) = remembered_symbols.get(symbol.as_str()).unwrap();
-
-                            // new price directly from stream
-                            let new_price = Decimal::from_str(&event_price).unwrap();
-
-                            if old_price < &new_price {
-                                // if old_price is smaller than new price then refresh this data
-                                // in the map and refresh timestamp - symbol which price is rising
-                                // will be kept longer in the list
-                                let new_tuple = (timestamp_now, *old_price);
+                // symbols missing in this update - their price didn't change since the last one
+                for (symbol, price_history) in prices_map.iter_mut() {
+                    let (pre_window_secs, main_window_secs, post_window_secs) =
+                        window_spans(&symbol_configs.for_symbol(symbol).symbol_monitor);
+                    price_history.fill_to(
+                        update_time,
+                        pre_window_secs + main_window_secs + post_window_secs,
+                    );
+                }
 
-                                // update hashmap
-                                *remembered_symbols
-                                    .entry(tick_event.symbol.clone())
-                                    .or_insert_with(|| new_tuple) = new_tuple;
+                //
+                // BEGIN: candidates - selected symbols which are active enough are sent to the engine
+                // (once per ticker batch, each with its own latest prices)
+                //
+                for (k, (decision, window_percent_changes)) in
+                    symbols_currently_selected_to_monitor.iter()
+                {
+                    // thresholds for the candidate (with per-symbol overrides)
+                    let candidate_config = symbol_configs.for_symbol(k);
+                    let activity_window_secs = candidate_config.symbol_monitor.activity_window_secs;
+
+                    let symbol_activity = match symbols_activity.get(k) {
+                        Some(a) if a.is_complete(activity_window_secs) => a,
+                        // we don't have activity data for entire window yet
+                        _ => continue,
+                    };
+                    let activity = symbol_activity.metrics();
+                    let activity_score = activity.score(&candidate_config);
+
+                    let last_sample = symbol_activity.last().unwrap();
+                    let price_now = last_sample.best_bid;
+                    let spread_percent = match price_now.is_zero() {
+                        true => Decimal::ZERO,
+                        false => percent_diff(price_now, last_sample.best_ask).round_dp(4),
+                    };
+
+                    match remembered_symbols.get(k) {
+                        Some((_, old_price)) => {
+                            if *old_price < price_now {
+                                // if old_price is smaller than new price then refresh timestamp -
+                                // symbol which price is rising will be kept longer in the list
+                                let new_tuple = (Instant::now(), *old_price);
+                                remembered_symbols.insert(k.clone(), new_tuple);
                             }
                         }
-
-                        let price_now = Decimal::from_str(event_price.as_str()).unwrap();
-
-                        if !remembered_symbols.contains_key(symbol.as_str()) {
+                        None => {
                             // here we are if we don't know this symbol yet - so add this
-                            let val = (
-                                Instant::now(),
-                                Decimal::from_str(event_price.as_str()).unwrap(),
-                            );
-                            remembered_symbols.insert(symbol.to_string(), val);
+                            remembered_symbols.insert(k.clone(), (Instant::now(), price_now));
                         }
+                    }
 
-                        let to_send = (Symbol(k.clone()), price_now);
-
-                        if !symbols_variability_count.contains_key(symbol.as_str()) {
-                            // we don't have variability data yet
-                            continue;
-                        }
-
-                        let volatility_count =
-                            *symbols_variability_count.get(symbol.as_str()).unwrap();
-
-                        let percent = v.round_dp_with_strategy(2, RoundingStrategy::ToZero);
-
-                        if volatility_count
-                            >= config.symbol_monitor.symbol_price_violatile_required_count
-                        {
-                            // ############################################################## //
-                            // ############################################################## //
-                            // ############################################################## //
-
-                            // this symbol has required volatility - so we will send
-                            // it now
-                            let msg = to_send.clone();
-                            info!(
-                                    "{symbol}: sent to engine [price: {price_now}, diff: {percent}, volatility count: {volatility_count}]"
-                                );
-                            channel_to_engine.send(msg).unwrap();
-
-                            symbols_already_sent.insert(symbol_type.clone(), true);
-                            let time = Instant::now();
-                            temp_monitored_symbols.insert(symbol.clone(), (time, price_now));
-                            biggest_monitored.insert(symbol.clone(), price_now);
-
-                            // ############################################################## //
-                            // ############################################################## //
-                            // ############################################################## //
-                        }
+                    if !activity.thresholds_reached(&candidate_config) {
+                        continue;
                     }
+
+                    let to_send = TradeCandidate {
+                        symbol: Symbol(k.clone()),
+                        price: price_now,
+                        spread_percent,
+                        entry_signal: EntrySignal {
+                            decision: decision.clone(),
+                            window_percent_changes: window_percent_changes.clone(),
+                            activity: activity.clone(),
+                            activity_score,
+                        },
+                    };
+
+                    let percent = window_percent_changes
+                        .window
+                        .round_dp_with_strategy(2, RoundingStrategy::ToZero);
+
+                    // ############################################################## //
+                    // this symbol has required activity - so we will send it now, engine ranks it
+                    // ############################################################## //
+                    info!(
+                        "{k}: sent to engine [price: {price_now}, diff: {percent}, spread: {spread_percent}%, activity score: {activity_score}, {activity:?}]"
+                    );
+                    channel_to_engine.send(to_send).unwrap();
+
+                    symbols_already_sent.insert(Symbol(k.clone()), true);
+                    temp_monitored_symbols.insert(k.clone(), (Instant::now(), price_now));
+                    biggest_monitored.insert(k.clone(), price_now);
                 }
+                //
+                // END: candidates
+                //
             };
             Ok(())
         });
 
-        web_socket.connect(&agg_trade).unwrap(); // check error
-        if let Err(e) = web_socket.event_loop(&keep_running) {
-            error!("{e:?}");
+        //
+        // BEGIN: event loop with heartbeat watchdog - stream which is connected but doesn't send anything
+        // for |symbols_monitor_stale_secs| is reconnected
+        //
+        let config = shared_config.read().unwrap().clone();
+        let api_config = binance_api_config(&config);
+        let stale_after = Duration::from_secs(config.symbols_monitor_stale_secs);
+        let read_timeout = Duration::from_millis(STREAM_READ_TIMEOUT_MS);
+
+        let connected = web_socket
+            .connect_with_config(&agg_trade, &api_config)
+            .map_err(|e| e.to_string())
+            .and_then(|_| match web_socket.socket.as_ref() {
+                Some((socket, _)) => {
+                    set_read_timeout(socket, read_timeout).map_err(|e| e.to_string())
+                }
+                None => Ok(()),
+            });
+
+        let error = match connected {
+            Ok(_) => {
+                info!("symbols monitor connected");
+                // a fresh connection gets its own |symbols_monitor_stale_secs| to deliver the first update
+                let connected_at = Instant::now();
+
+                let error = loop {
+                    match web_socket.event_loop(&keep_running) {
+                        Ok(_) => break None,
+                        Err(e) => match e.kind() {
+                            ErrorKind::Tungstenite(e) if is_read_timeout(e) => {}
+                            _ => break Some(e.to_string()),
+                        },
+                    }
+
+                    let last_event = symbols_monitor_health.read().unwrap().last_event;
+                    let last_activity = last_event.unwrap_or(connected_at).max(connected_at);
+                    if last_activity.elapsed() >= stale_after {
+                        let e = format!(
+                            "stream is stale (no update for {}s)",
+                            last_activity.elapsed().as_secs()
+                        );
+                        symbols_monitor_health.write().unwrap().stale = true;
+                        break Some(e);
+                    }
+                };
+
+                let _ = web_socket.disconnect();
+                error
+            }
+            Err(e) => Some(format!("can't connect: {e}")),
+        };
+
+        {
+            let mut health = symbols_monitor_health.write().unwrap();
+            health.connected = false;
+            health.reconnects += 1;
+            if let Some(e) = &error {
+                health.last_error = Some(e.clone());
+            }
+        }
+        health_publisher
+            .borrow_mut()
+            .publish(&config, &symbols_monitor_health);
+
+        match error {
+            Some(e) => error!(
+                "symbols monitor: {e} - reconnecting in {}ms",
+                config.symbols_monitor_reconnect_delay_ms
+            ),
+            None => info!(
+                "symbols monitor disconnected - reconnecting in {}ms",
+                config.symbols_monitor_reconnect_delay_ms
+            ),
         }
-        web_socket.disconnect().unwrap();
-        info!("symbols monitor disconnected - reconnecting");
+        thread::sleep(Duration::from_millis(
+            config.symbols_monitor_reconnect_delay_ms,
+        ));
+        //
+        // END: event loop with heartbeat watchdog
+        //
     }
 }
 
-fn add_price_to_list(config: ConfigStruct, old_vec: Vec<Decimal>, price: Decimal) -> Vec<Decimal> {
-    if old_vec.len() < config.symbol_monitor.symbol_price_list_length {
-        let mut new_list = old_vec;
-        new_list.push(price);
-        return new_list;
-    }
-
-    let new_value = vec![price];
-
-    if old_vec.is_empty() {
-        return new_value;
-    }
-
-    let slice = &old_vec[1..old_vec.len()];
-    [slice.to_vec(), new_value].concat()
+// writes health to |symbols_monitor_health_file| - not on every update, but when the stream status changes
+// or once per HEALTH_WRITE_PERIOD_SECS
+#[derive(Default)]
+struct HealthPublisher {
+    // status written last time and when
+    written: Option<(StreamStatus, Instant)>,
 }
 
-fn price_is_constantly_rising(list: Vec<Decimal>) -> bool {
-    is_sorted::<Vec<Decimal>>(list)
-}
+impl HealthPublisher {
+    fn publish(&mut self, config: &ConfigStruct, shared_health: &SharedStreamHealth) {
+        let Some(path) = config.symbols_monitor_health_path() else {
+            return;
+        };
+
+        // file is written without holding the lock
+        let mut health = shared_health.read().unwrap().clone();
+        let status = health.status();
+
+        if let Some((written_status, written_at)) = &self.written {
+            if *written_status == status
+                && written_at.elapsed() < Duration::from_secs(HEALTH_WRITE_PERIOD_SECS)
+            {
+                return;
+            }
+        }
 
-fn is_sorted<T>(data: Vec<Decimal>) -> bool
-where
-    T: Ord,
-{
-    data.windows(2).all(|w| w[0] <= w[1])
+        health.refresh_age();
+        match serde_json::to_string_pretty(&health) {
+            Ok(json) => {
+                if let Err(e) = fs::write(&path, json) {
+                    error!("symbols monitor: cannot write health to {path}: {e}");
+                }
+            }
+            Err(e) => error!("symbols monitor: cannot serialize health: {e}"),
+        }
+
+        self.written = Some((status, Instant::now()));
+    }
 }
@@ -1,19 +1,30 @@
+use crate::binance::endpoints::binance_api_config;
+use crate::binance::prices::process_symbol_qty;
 use crate::config::settings::ConfigStruct;
+use crate::core::position::OrderFill;
 use crate::core::trading::TradingSymbol;
-use crate::core::types::{SymbolAction, TradingMode};
-use binance::account::Account;
-use binance::api::Binance;
+use crate::core::types::{ExitReason, OrderKind, Symbol, SymbolAction, TradingMode};
+use binance::account::{Account, OrderSide, OrderType, TimeInForce};
+use binance::api::{Binance, Spot, API};
+use binance::market::Market;
+use binance::model::FillInfo;
+use binance::util::build_signed_request;
 use log::{error, info, warn};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::ops::Not;
-use std::process::exit;
 use std::{thread, time};
 
 const FILL_BUY_ASK_DELAY: u64 = 2;
 const LIMIT_BUY_ATTEMPTS: u64 = 3;
 const FILL_SELL_ASK_DELAY: u64 = 10;
 const LIMIT_SELL_ATTEMPTS: u64 = 3;
+// failed order status reads / cancels of one order before its state is given up as unknown
+const ORDER_REQUEST_ATTEMPTS: u64 = 3;
+// buy order in unknown state is asked at most this often until the exchange answers
+const UNKNOWN_ORDER_MAX_DELAY_SECS: u64 = 30;
 
 pub fn reverse_symbol_action(symbol_action: SymbolAction) -> SymbolAction {
     if symbol_action == SymbolAction::Buy {
@@ -23,17 +34,346 @@ pub fn reverse_symbol_action(symbol_action: SymbolAction) -> SymbolAction {
     SymbolAction::Buy
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct OrderTrade {
+    commission: String,
+    commission_asset: String,
+}
+
+// commission by asset from fills of the order response
+fn fills_commissions(fills: &Option<Vec<FillInfo>>) -> BTreeMap<String, Decimal> {
+    let mut commissions: BTreeMap<String, Decimal> = BTreeMap::new();
+
+    for fill in fills.iter().flatten() {
+        *commissions
+            .entry(fill.commission_asset.clone())
+            .or_default() += Decimal::try_from(fill.commission).unwrap_or_default();
+    }
+
+    commissions
+}
+
+fn order_trades(
+    account: &Account,
+    symbol: &Symbol,
+    order_id: u64,
+) -> Result<Vec<OrderTrade>, String> {
+    let mut params: BTreeMap<String, String> = BTreeMap::new();
+    params.insert("symbol".into(), symbol.to_string());
+    params.insert("orderId".into(), order_id.to_string());
+
+    let request =
+        build_signed_request(params, account.recv_window).map_err(|e| format!("{e:?}"))?;
+
+    account
+        .client
+        .get_signed(API::Spot(Spot::MyTrades), Some(request))
+        .map_err(|e| format!("{e:?}"))
+}
+
+/*
+Commission by asset of all trades of the order (status of the order doesn't have them). Empty if trades
+can't be read - fee is estimated then.
+ */
+pub fn order_commissions(
+    account: &Account,
+    symbol: &Symbol,
+    order_id: u64,
+) -> BTreeMap<String, Decimal> {
+    let mut commissions: BTreeMap<String, Decimal> = BTreeMap::new();
+
+    match order_trades(account, symbol, order_id) {
+        Ok(trades) => {
+            for trade in trades {
+                *commissions.entry(trade.commission_asset).or_default() +=
+                    trade.commission.parse::<Decimal>().unwrap_or_default();
+            }
+        }
+        Err(e) => {
+            warn!("{symbol} trades of order {order_id} can't be read (fee is estimated): {e}")
+        }
+    }
+
+    commissions
+}
+
+// order type of the current action - entry or exit by its reason
+fn order_kind(config: &ConfigStruct, trading_symbol: &TradingSymbol) -> OrderKind {
+    let om = &config.orderbook_monitor;
+
+    if trading_symbol.current_symbol_action == SymbolAction::Buy {
+        return om.entry_order_type;
+    }
+
+    match trading_symbol.exit_reason {
+        ExitReason::GoodProfit => om.good_profit_exit_order_type,
+        ExitReason::MinProfit => om.min_profit_exit_order_type,
+        ExitReason::LossLimit => om.loss_limit_exit_order_type,
+        ExitReason::Timeout => om.timeout_exit_order_type,
+        ExitReason::Requested | ExitReason::StreamStale => om.requested_exit_order_type,
+        _ => OrderKind::Limit,
+    }
+}
+
+/*
+MARKET and IOC/FOK LIMIT orders are done when the response comes - there is nothing to wait for and
+nothing stays on the exchange, the part which was not filled (IOC) or the whole order (FOK) is expired.
+ */
+fn instant_order(
+    binance_account: &Account,
+    trading_symbol: &TradingSymbol,
+    order_kind: OrderKind,
+    qty: f64,
+    price: f64,
+) -> Result<OrderFill, String> {
+    let symbol = trading_symbol.symbol.clone();
+    let symbol_string = symbol.to_string();
+    let buy = trading_symbol.current_symbol_action == SymbolAction::Buy;
+
+    let result = match (order_kind, buy) {
+        // buy spends the whole pool amount, exchange calculates the qty
+        (OrderKind::Market, true) => binance_account.market_buy_using_quote_quantity(
+            symbol_string,
+            trading_symbol.started_qty.to_f64().unwrap(),
+        ),
+        (OrderKind::Market, false) => binance_account.market_sell(symbol_string, qty),
+        _ => {
+            let time_in_force = match order_kind {
+                OrderKind::LimitFok => TimeInForce::FOK,
+                _ => TimeInForce::IOC,
+            };
+            let order_side = match buy {
+                true => OrderSide::Buy,
+                false => OrderSide::Sell,
+            };
+            binance_account.custom_order(
+                symbol_string,
+                qty,
+                price,
+                None,
+                order_side,
+                OrderType::Limit,
+                time_in_force,
+                None,
+            )
+        }
+    };
+
+    let t = result.map_err(|e| format!("{symbol} {order_kind} order error: {e:?}"))?;
+
+    info!(
+        "{symbol} {order_kind} {} (result) => executed qty: [{}], cumm_quote_qty: [{}], status: [{}]",
+        t.side, t.executed_qty, t.cummulative_quote_qty, t.status
+    );
+    if t.status != "FILLED" {
+        warn!("{symbol} {order_kind} order not filled completely (status: {}) - only executed qty was traded", t.status);
+    }
+
+    Ok(OrderFill {
+        executed_qty: Decimal::try_from(t.executed_qty).unwrap(),
+        quote_qty: Decimal::try_from(t.cummulative_quote_qty).unwrap(),
+        complete: t.status == "FILLED",
+        commissions: fills_commissions(&t.fills),
+    })
+}
+
+// delay before next request about buy order in unknown state, longer with every failed request
+fn unknown_order_delay(failed_requests: u64) -> time::Duration {
+    time::Duration::from_secs(
+        (FILL_BUY_ASK_DELAY * failed_requests).min(UNKNOWN_ORDER_MAX_DELAY_SECS),
+    )
+}
+
+/*
+GTC limit buy - waits a while for the fill, not filled order is cancelled (result is what was filled).
+
+Err means the order was not placed. Once it's placed, its final state has to be known - the order might be filled or
+still open, so it's never reported as not filled when the exchange doesn't answer: status reads and cancels are
+repeated until they succeed (after |ORDER_REQUEST_ATTEMPTS| failures as errors - the trade needs attention) and the
+executor keeps the pool meanwhile.
+ */
+fn limit_buy_order(
+    binance_account: &Account,
+    symbol: &Symbol,
+    qty: f64,
+    price: f64,
+) -> Result<OrderFill, String> {
+    match binance_account.limit_buy(symbol.to_string(), qty, price) {
+        Err(e) => Err(format!("{e:?}")),
+        Ok(t) => {
+            let mut qty_to_return = t.executed_qty;
+            let mut buy_used_qty = t.cummulative_quote_qty;
+            let mut order_status = t.status;
+
+            let order_id = t.order_id;
+
+            let mut counts = 0;
+            let mut failed_requests = 0;
+            let mut order_was_cancelled = false;
+            let mut wait_longer_to_confirm = true;
+
+            if order_status == "FILLED" {
+                // filled - finishing
+                info!("{symbol}: OK - order is now successfully filled (INSTANTLY).");
+                wait_longer_to_confirm = false;
+            }
+
+            if wait_longer_to_confirm {
+                loop {
+                    let order = match binance_account.order_status(symbol.0.clone(), order_id) {
+                        Ok(order) => order,
+                        Err(e) => {
+                            // order might be filled or still open - no other buy can be placed for this qty
+                            failed_requests += 1;
+                            match failed_requests >= ORDER_REQUEST_ATTEMPTS {
+                                true => error!("{symbol} => BUY order {order_id} is in unknown state, status can't be read ({failed_requests}x): {e:?} - waiting for the exchange"),
+                                false => warn!("{symbol} => BUY order {order_id} status can't be read: {e:?}"),
+                            }
+                            thread::sleep(unknown_order_delay(failed_requests));
+                            continue;
+                        }
+                    };
+
+                    qty_to_return = order.executed_qty.parse().unwrap();
+                    buy_used_qty = order.cummulative_quote_qty.parse().unwrap();
+                    order_status = order.status;
+
+                    if order_status == "FILLED" {
+                        // filled - finishing
+                        info!("{symbol}: OK - order is now successfully filled.");
+                        break;
+                    }
+
+                    // order is closed (cancelled by us, or expired) - what's executed is final
+                    if matches!(order_status.as_str(), "CANCELED" | "EXPIRED" | "REJECTED") {
+                        order_was_cancelled = true;
+                    }
+
+                    if order_was_cancelled && order_status == "PARTIALLY_FILLED" {
+                        // cancelled - finishing anyway
+                        warn!("{symbol} => ORDER CANCELLED, but partially filled");
+                        break;
+                    }
+
+                    if order_was_cancelled && qty_to_return == 0.0 {
+                        // nothing
+                        warn!("{symbol} => ORDER CANCELLED, 0 executed - order unsuccessfull");
+                        return Ok(OrderFill::default());
+                    }
+
+                    if counts >= LIMIT_BUY_ATTEMPTS {
+                        // first we have to cancell - the order can be filled just before, cancel fails
+                        // then ("Unknown order sent") and the status read again tells what happened
+                        if order_was_cancelled.not() {
+                            match binance_account.cancel_order(symbol.to_string(), order_id) {
+                                Ok(_) => {
+                                    order_was_cancelled = true;
+                                    warn!("{symbol} => could not make BUY instantly within time limit, CANCELLING ORDER");
+                                }
+                                Err(e) => {
+                                    failed_requests += 1;
+                                    match failed_requests >= ORDER_REQUEST_ATTEMPTS {
+                                        true => error!("{symbol} => BUY order {order_id} can't be cancelled ({failed_requests}x): {e:?} - reading its status again"),
+                                        false => warn!("{symbol} => BUY order {order_id} can't be cancelled: {e:?} - reading its status again"),
+                                    }
+                                    thread::sleep(unknown_order_delay(failed_requests));
+                                }
+                            }
+                            continue;
+                        }
+                    }
+                    warn!(
+                        "{symbol} => BUY: requesting status for order_id={order_id}, status: [{order_status}] [NOT FILLED YET]..."
+                    );
+
+                    if order_was_cancelled {
+                        break;
+                    }
+                    counts += 1;
+                    thread::sleep(time::Duration::from_secs(FILL_BUY_ASK_DELAY));
+                }
+            }
+
+            info!(
+                "{symbol} LIMIT BUY (result) => wanted qty: {}, received qty: [{}], cumm_quote_qty: [{}], price: [{}], status: [{}], side: [{}]",
+                qty, qty_to_return, buy_used_qty, t.price, order_status, t.side,
+            );
+
+            Ok(OrderFill {
+                executed_qty: Decimal::try_from(qty_to_return).unwrap(),
+                quote_qty: Decimal::try_from(buy_used_qty).unwrap(),
+                complete: order_status == "FILLED",
+                commissions: order_commissions(binance_account, symbol, order_id),
+            })
+        }
+    }
+}
+
+/*
+Entry chase: next order of not filled limit buy is placed at the current best ask (cancel and replace) if it's at most
+|entry_chase_max_deviation_percent| above the price sent by symbols monitor. It buys only for what is left from the
+planned amount (qty x price of the first order). None - chasing is over.
+
+It's not the atomic order/cancelReplace endpoint (binance crate doesn't have it) - cancel and new order are two requests.
+The previous order is always closed before (cancelled and its status read until it's CANCELED or FILLED,
+limit_buy_order()), so a fill which happened between our cancel request and the cancel itself is in its final
+executed qty and only the rest is bought - the same qty can't be bought twice. The price can move in the gap, the new
+order is priced by the book read after the previous order is closed.
+ */
+fn chase_order(
+    config: &ConfigStruct,
+    binance_market: &Market,
+    trading_symbol: &TradingSymbol,
+    fill: &OrderFill,
+) -> Option<(Decimal, Decimal)> {
+    let symbol = &trading_symbol.symbol;
+    let max_deviation = config.orderbook_monitor.entry_chase_max_deviation_percent;
+    let max_price =
+        trading_symbol.monitored_price * (Decimal::ONE + max_deviation / Decimal::ONE_HUNDRED);
+
+    let best_ask = match binance_market.get_depth(symbol.to_string()) {
+        Ok(book) => Decimal::try_from(book.asks.first()?.price).ok()?,
+        Err(e) => {
+            warn!("{symbol} BUY CHASE => order book can't be read: {e:?}");
+            return None;
+        }
+    };
+
+    if best_ask > max_price {
+        warn!(
+            "{symbol} BUY CHASE stopped => best ask {best_ask} is more than {max_deviation}% above monitored price {}",
+            trading_symbol.monitored_price
+        );
+        return None;
+    }
+
+    let quote_left = trading_symbol.qty * trading_symbol.price - fill.quote_qty;
+    let qty = process_symbol_qty(
+        symbol.clone(),
+        quote_left / best_ask,
+        &trading_symbol.filters_map,
+    )?;
+
+    Some((best_ask, qty))
+}
+
 pub fn symbol_buy_or_sell(
     config: &ConfigStruct,
     trading_mode: &TradingMode,
     trading_symbol: &TradingSymbol,
     api_keys: (String, String),
-) -> Result<(Decimal, Decimal), String> {
-    // Result<(Decimal, Decimal)> means: (received_qty, used_qty) depends on side
+) -> Result<OrderFill, String> {
+    // result is what was really filled (base qty and quote qty), not what was requested
     // default symbol action for Join as we enter
-    let decimal_zero = Decimal::ZERO;
     let (api_key, secret_key) = api_keys;
-    let binance_account: Account = Binance::new(Some(api_key), Some(secret_key));
+    let binance_account: Account = Binance::new_with_config(
+        Some(api_key.clone()),
+        Some(secret_key.clone()),
+        &binance_api_config(config),
+    );
+    let binance_market: Market =
+        Binance::new_with_config(Some(api_key), Some(secret_key), &binance_api_config(config));
 
     let symbol_string = trading_symbol.symbol.to_string();
     let symbol = trading_symbol.symbol.clone();
@@ -41,9 +381,10 @@ pub fn symbol_buy_or_sell(
     // price and qty which will be used here
     let price = trading_symbol.price.to_f64().unwrap();
     let qty = trading_symbol.qty.to_f64().unwrap();
+    let order_kind = order_kind(config, trading_symbol);
 
     if trading_symbol.current_symbol_action == SymbolAction::Buy {
-        info!("{symbol} LIMIT BUY (request) => qty (to receive): {qty}, for price: {price}");
+        info!("{symbol} {order_kind} BUY (request) => qty (to receive): {qty}, for price: {price}");
 
         if *trading_mode == TradingMode::Simulation {
             //
@@ -52,110 +393,69 @@ pub fn symbol_buy_or_sell(
             let qty_to_return = qty;
             let buy_used_qty = price * qty;
             info!(
-                "{symbol} [TEST SIMULATION] LIMIT BUY (result) => received qty: [{}]",
+                "{symbol} [TEST SIMULATION] {order_kind} BUY (result) => received qty: [{}]",
                 qty_to_return
             );
-            let to_return_tuple = (
-                Decimal::try_from(qty_to_return).unwrap(),
-                Decimal::try_from(buy_used_qty).unwrap(),
-            );
-            return Ok(to_return_tuple);
+            return Ok(OrderFill {
+                executed_qty: Decimal::try_from(qty_to_return).unwrap(),
+                quote_qty: Decimal::try_from(buy_used_qty).unwrap(),
+                complete: true,
+                ..Default::default()
+            });
         }
 
-        // when SIDE=BUY quantity means: I want "quantity" base for "current_symbol_price"
-        match binance_account.limit_buy(symbol_string, qty, price) {
-            Err(e) => {
-                error!("{symbol} error: {e:?}");
-                exit(1);
-            }
-            Ok(t) => {
-                let mut qty_to_return = t.executed_qty;
-                let mut buy_used_qty = t.cummulative_quote_qty;
-                let mut order_status = t.status;
-
-                let order_id = t.order_id;
-
-                let mut counts = 0;
-                let mut order_was_cancelled = false;
-                let mut wait_longer_to_confirm = true;
-
-                if order_status == "FILLED" {
-                    // filled - finishing
-                    info!("{symbol}: OK - order is now successfully filled (INSTANTLY).");
-                    wait_longer_to_confirm = false;
-                }
-
-                if wait_longer_to_confirm {
-                    loop {
-                        let order = binance_account
-                            .order_status(symbol.0.clone(), t.order_id)
-                            .expect("cannot fetch order status");
-
-                        qty_to_return = order.executed_qty.parse().unwrap();
-                        buy_used_qty = order.cummulative_quote_qty.parse().unwrap();
-                        order_status = order.status;
-
-                        if order_status == "FILLED" {
-                            // filled - finishing
-                            info!("{symbol}: OK - order is now successfully filled.");
-                            break;
-                        }
-
-                        if order_was_cancelled && order_status == "PARTIALLY_FILLED" {
-                            // cancelled - finishing anyway
-                            warn!("{symbol} => ORDER CANCELLED, but partially filled");
-                            break;
-                        }
-
-                        if order_was_cancelled && qty_to_return == 0.0 {
-                            // nothing
-                            warn!("{symbol} => ORDER CANCELLED, 0 executed - order unsuccessfull");
-                            buy_used_qty = t.cummulative_quote_qty;
-                            return Ok((decimal_zero, Decimal::try_from(buy_used_qty).unwrap()));
-                        }
-
-                        if counts >= LIMIT_BUY_ATTEMPTS {
-                            // first we have to cancell
-                            if order_was_cancelled.not() {
-                                binance_account
-                                    .cancel_order(symbol.to_string(), order_id)
-                                    .unwrap();
-                                order_was_cancelled = true;
-                                warn!("{symbol} => could not make BUY instantly within time limit, CANCELLING ORDER");
-                                continue;
-                            }
-                        }
-                        warn!(
-                        "{symbol} => BUY: requesting status for order_id={order_id}, status: [{order_status}] [NOT FILLED YET]..."
-                    );
+        if order_kind != OrderKind::Limit {
+            return instant_order(&binance_account, trading_symbol, order_kind, qty, price);
+        }
 
-                        if order_was_cancelled {
-                            break;
-                        }
-                        counts += 1;
-                        thread::sleep(time::Duration::from_secs(FILL_BUY_ASK_DELAY));
-                    }
+        // when SIDE=BUY quantity means: I want "quantity" base for "current_symbol_price"
+        let mut fill = limit_buy_order(&binance_account, &symbol, qty, price)
+            .map_err(|e| format!("{symbol} limit buy error: {e}"))?;
+
+        // not filled in time - chasing the price with new orders
+        let mut chases: u64 = 0;
+        let chase_attempts = config.orderbook_monitor.entry_chase_attempts;
+
+        while !fill.complete && chases < chase_attempts {
+            chases += 1;
+
+            let (chase_price, chase_qty) =
+                match chase_order(config, &binance_market, trading_symbol, &fill) {
+                    Some(v) => v,
+                    None => break,
+                };
+
+            info!("{symbol} BUY CHASE {chases}/{chase_attempts} => placing new order at best ask: {chase_price}, qty: {chase_qty}");
+
+            match limit_buy_order(
+                &binance_account,
+                &symbol,
+                chase_qty.to_f64().unwrap(),
+                chase_price.to_f64().unwrap(),
+            ) {
+                Ok(chase_fill) => fill.merge(&chase_fill),
+                Err(e) => {
+                    warn!("{symbol} BUY CHASE order failed, chasing stopped: {e}");
+                    break;
                 }
-
-                info!(
-                        "{symbol} LIMIT BUY (result) => wanted qty: {}, received qty: [{}], cumm_quote_qty: [{}], price: [{}], status: [{}], side: [{}]",
-                        qty, qty_to_return, buy_used_qty, t.price, order_status, t.side,
-                    );
-
-                let to_return_tuple = (
-                    Decimal::try_from(qty_to_return).unwrap(),
-                    Decimal::try_from(buy_used_qty).unwrap(),
-                );
-                return Ok(to_return_tuple);
             }
         }
+
+        if !fill.complete && !fill.executed_qty.is_zero() {
+            warn!(
+                "{symbol} => BUY filled only partially: {} of {qty}",
+                fill.executed_qty
+            );
+        }
+
+        return Ok(fill);
     }
 
     if trading_symbol.current_symbol_action == SymbolAction::Sell {
         // when SIDE=SELL quantity means: I want to USE (sell) this my "quantity" for "current_symbol_price"
         let possible_qty = price * qty;
         info!(
-            "{symbol} LIMIT SELL (request) => qty: {qty}, price: {price}, possible qty: {possible_qty}"
+            "{symbol} {order_kind} SELL (request) => qty: {qty}, price: {price}, possible qty: {possible_qty}"
         );
 
         if *trading_mode == TradingMode::Simulation {
@@ -164,46 +464,119 @@ pub fn symbol_buy_or_sell(
             let sell_used_qty = qty;
 
             info!(
-                "{symbol} [TEST SIMULATION] LIMIT SELL (result) => received qty: [{}]",
+                "{symbol} [TEST SIMULATION] {order_kind} SELL (result) => received qty: [{}]",
                 qty_to_return
             );
 
-            let to_return_tuple = (
-                Decimal::try_from(qty_to_return).unwrap(),
-                Decimal::try_from(sell_used_qty).unwrap(),
+            return Ok(OrderFill {
+                executed_qty: Decimal::try_from(sell_used_qty).unwrap(),
+                quote_qty: Decimal::try_from(qty_to_return).unwrap(),
+                complete: true,
+                ..Default::default()
+            });
+        }
+
+        // average price is not on any level - the order goes to the worst level we need, it's
+        // filled level by level for their prices
+        let limit_price = match trading_symbol.exit_limit_price.is_zero() {
+            true => price,
+            false => trading_symbol.exit_limit_price.to_f64().unwrap(),
+        };
+
+        if order_kind != OrderKind::Limit {
+            return instant_order(
+                &binance_account,
+                trading_symbol,
+                order_kind,
+                qty,
+                limit_price,
             );
-            return Ok(to_return_tuple);
         }
 
-        match binance_account.limit_sell(symbol_string, qty, price) {
-            Err(e) => {
-                error!("{symbol} error: {e:?}");
-                exit(1);
-            }
+        info!("{symbol} LIMIT SELL (request) => limit price: {limit_price}");
+
+        match binance_account.limit_sell(symbol_string, qty, limit_price) {
+            Err(e) => return Err(format!("{symbol} limit sell error: {e:?}")),
             Ok(t) => {
                 let mut qty_to_return = t.cummulative_quote_qty;
-                let sell_used_qty = t.executed_qty;
+                let mut sell_used_qty = t.executed_qty;
+                let mut order_status = t.status.clone();
 
                 if (t.status == "NEW" && t.executed_qty == 0.0) || (t.status == "PARTIALLY_FILLED")
                 {
                     // limit order non complete instantly
                     let order_id = t.order_id;
                     let mut count: u64 = 0;
+                    let mut failed_requests = 0;
+                    let mut order_was_cancelled = false;
                     loop {
-                        let order = binance_account
-                            .order_status(symbol.0.clone(), t.order_id)
-                            .expect("cannot fetch order status");
+                        let order = match binance_account.order_status(symbol.0.clone(), order_id) {
+                            Ok(order) => order,
+                            Err(e) => {
+                                failed_requests += 1;
+                                if failed_requests >= ORDER_REQUEST_ATTEMPTS {
+                                    // order might be still open and lock qty we hold
+                                    return Err(format!(
+                                        "{symbol} status of SELL order {order_id} can't be read: {e:?}"
+                                    ));
+                                }
+                                warn!(
+                                    "{symbol} => SELL order {order_id} status can't be read: {e:?}"
+                                );
+                                thread::sleep(time::Duration::from_secs(FILL_BUY_ASK_DELAY));
+                                continue;
+                            }
+                        };
 
-                        if order.status == "FILLED" {
+                        qty_to_return = order.cummulative_quote_qty.parse().unwrap();
+                        sell_used_qty = order.executed_qty.parse().unwrap();
+                        order_status = order.status;
+
+                        if order_status == "FILLED" {
                             info!("{symbol}: LEAVE - order is now successfully filled.");
-                            qty_to_return = order.cummulative_quote_qty.parse().unwrap();
                             break;
                         }
 
+                        // order is closed (cancelled by us, or expired) - what's executed is final
+                        if matches!(order_status.as_str(), "CANCELED" | "EXPIRED" | "REJECTED") {
+                            // what was filled until cancel is sold, the rest is still held
+                            warn!("{symbol} => SELL ORDER CANCELLED, executed qty: {sell_used_qty} of {qty}");
+                            break;
+                        }
+
+                        if order_was_cancelled {
+                            // cancelled, but the exchange doesn't show it yet
+                            failed_requests += 1;
+                            if failed_requests >= ORDER_REQUEST_ATTEMPTS {
+                                return Err(format!(
+                                    "{symbol} SELL order {order_id} is still [{order_status}] after cancel"
+                                ));
+                            }
+                            thread::sleep(time::Duration::from_secs(FILL_BUY_ASK_DELAY));
+                            continue;
+                        }
+
                         if count >= LIMIT_SELL_ATTEMPTS {
-                            warn!("{symbol} LIMIT ORDER still not filled - leaving for later execution");
-                            let to_return_tuple = (decimal_zero, decimal_zero);
-                            return Ok(to_return_tuple);
+                            // order can't stay on the exchange - it would lock qty we still hold; cancel of
+                            // the order filled just before fails and the status read again tells what happened
+                            match binance_account.cancel_order(symbol.to_string(), order_id) {
+                                Ok(_) => {
+                                    order_was_cancelled = true;
+                                    warn!(
+                                        "{symbol} LIMIT ORDER still not filled - CANCELLING ORDER"
+                                    );
+                                }
+                                Err(e) => {
+                                    failed_requests += 1;
+                                    if failed_requests >= ORDER_REQUEST_ATTEMPTS {
+                                        return Err(format!(
+                                            "{symbol} SELL order {order_id} can't be cancelled: {e:?}"
+                                        ));
+                                    }
+                                    warn!("{symbol} => SELL order {order_id} can't be cancelled: {e:?} - reading its status again");
+                                }
+                            }
+                            continue;
                         }
 
                         warn!("{symbol} => requesting status for order_id={order_id} [NOT FILLED YET]...");
@@ -213,13 +586,14 @@ pub fn symbol_buy_or_sell(
                 }
 
                 info!("{symbol} LIMIT SELL (result) => to use my qty: {}, qty_to_return (cum) [{}], executed_qty: [{}], price: [{}], status: [{}], side: [{}]",
-                        qty, qty_to_return, t.executed_qty, t.price, t.status, t.side);
-
-                let to_return_tuple = (
-                    Decimal::try_from(qty_to_return).unwrap(),
-                    Decimal::try_from(sell_used_qty).unwrap(),
-                );
-                return Ok(to_return_tuple);
+                        qty, qty_to_return, sell_used_qty, t.price, order_status, t.side);
+
+                return Ok(OrderFill {
+                    executed_qty: Decimal::try_from(sell_used_qty).unwrap(),
+                    quote_qty: Decimal::try_from(qty_to_return).unwrap(),
+                    complete: order_status == "FILLED",
+                    commissions: order_commissions(&binance_account, &symbol, t.order_id),
+                });
             }
         }
     }
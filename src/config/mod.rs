@@ -1 +1,4 @@
+pub mod overrides;
+pub mod reload;
 pub mod settings;
+pub mod validation;
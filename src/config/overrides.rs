@@ -0,0 +1,143 @@
+use crate::config::settings::{ConfigOrderBookMonitor, ConfigStruct, ConfigSymbolMonitor};
+use log::error;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/*
+Options which replace the ones from global [symbol_monitor] and [orderbook_monitor] sections for
+some symbols only - any option from these sections can be used here (see config.toml):
+
+[symbols."XYZUSDT".orderbook_monitor]     - exact symbol
+[symbols."*BTC".symbol_monitor]           - pattern, "*" means any text
+[asset_groups.majors]                     - assets listed in |assets| traded against any of
+                                            |base_starting_assets|
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ConfigOverride {
+    #[serde(default)]
+    pub symbol_monitor: toml::Table,
+    #[serde(default)]
+    pub orderbook_monitor: toml::Table,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ConfigAssetGroupOverride {
+    pub assets: Vec<String>,
+    #[serde(flatten)]
+    pub options: ConfigOverride,
+}
+
+// "*" matches any (also empty) text
+pub fn symbol_matches_pattern(symbol: &str, pattern: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+
+    if parts.len() == 1 {
+        return symbol == pattern;
+    }
+
+    let first = parts[0];
+    let last = parts[parts.len() - 1];
+
+    if !symbol.starts_with(first) || !symbol[first.len()..].ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &symbol[first.len()..symbol.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+
+    true
+}
+
+impl ConfigAssetGroupOverride {
+    pub fn matches(&self, symbol: &str, config: &ConfigStruct) -> bool {
+        self.assets.iter().any(|asset| {
+            config.base_starting_assets.iter().any(|base| {
+                symbol == format!("{asset}{}", base.0) || symbol == format!("{}{asset}", base.0)
+            })
+        })
+    }
+}
+
+// section with override options applied, Err contains option name and reason
+pub fn apply_section_override<T: Serialize + DeserializeOwned>(
+    section: &T,
+    section_override: &toml::Table,
+) -> Result<T, (String, String)> {
+    let mut table = match toml::Value::try_from(section) {
+        Ok(toml::Value::Table(t)) => t,
+        _ => return Err((String::new(), "cannot serialize section".to_string())),
+    };
+
+    for (key, value) in section_override {
+        if !table.contains_key(key) {
+            return Err((key.clone(), "unknown option".to_string()));
+        }
+        table.insert(key.clone(), value.clone());
+    }
+
+    toml::Value::Table(table)
+        .try_into()
+        .map_err(|e: toml::de::Error| (String::new(), e.message().to_string()))
+}
+
+impl ConfigStruct {
+    // overrides matching the symbol, from the least to the most specific one:
+    // asset groups, patterns, exact symbol
+    pub fn overrides_for_symbol(&self, symbol: &str) -> Vec<(String, &ConfigOverride)> {
+        let mut overrides = Vec::new();
+
+        for (name, group) in &self.asset_groups {
+            if group.matches(symbol, self) {
+                overrides.push((format!("asset_groups.{name}"), &group.options));
+            }
+        }
+
+        for (pattern, o) in &self.symbols {
+            if pattern.contains('*') && symbol_matches_pattern(symbol, pattern) {
+                overrides.push((format!("symbols.\"{pattern}\""), o));
+            }
+        }
+
+        if let Some(o) = self.symbols.get(symbol) {
+            overrides.push((format!("symbols.\"{symbol}\""), o));
+        }
+
+        overrides
+    }
+
+    // effective config for the symbol - global sections with all matching overrides applied
+    pub fn for_symbol(&self, symbol: &str) -> ConfigStruct {
+        let mut config = self.clone();
+
+        for (name, o) in self.overrides_for_symbol(symbol) {
+            // overrides are validated when config is loaded, so this should never fail
+            match apply_section_override::<ConfigSymbolMonitor>(
+                &config.symbol_monitor,
+                &o.symbol_monitor,
+            ) {
+                Ok(section) => config.symbol_monitor = section,
+                Err((key, reason)) => error!("{symbol}: {name}.symbol_monitor.{key}: {reason}"),
+            }
+
+            match apply_section_override::<ConfigOrderBookMonitor>(
+                &config.orderbook_monitor,
+                &o.orderbook_monitor,
+            ) {
+                Ok(section) => config.orderbook_monitor = section,
+                Err((key, reason)) => error!("{symbol}: {name}.orderbook_monitor.{key}: {reason}"),
+            }
+        }
+
+        config
+    }
+}
+
+// BTreeMap to keep order of overrides always the same
+pub type SymbolOverrides = BTreeMap<String, ConfigOverride>;
+pub type AssetGroupOverrides = BTreeMap<String, ConfigAssetGroupOverride>;
@@ -0,0 +1,250 @@
+use crate::config::settings::ConfigStruct;
+use crate::config::validation::{load_config, validate_config};
+use log::{error, info, warn};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/*
+Config shared by all running parts of the bot: symbol monitor, engine and orderbook executors read it
+(instead of keeping their own copy) so every change is applied everywhere at the same time.
+Only config_watcher() writes it - it swaps the whole config, so readers take the inner Arc (cheap,
+no config clone) and know the config was reloaded when it's not the same Arc (Arc::ptr_eq) anymore.
+ */
+pub type SharedConfig = Arc<RwLock<Arc<ConfigStruct>>>;
+
+pub fn new_shared_config(config: ConfigStruct) -> SharedConfig {
+    Arc::new(RwLock::new(Arc::new(config)))
+}
+
+/*
+Effective configs of symbols (global config with symbol overrides, see ConfigStruct::for_symbol()) -
+every symbol is resolved once and kept until the shared config is swapped by reload, for_symbol()
+clones the whole config, so it's not called on every update.
+ */
+pub struct SymbolConfigs {
+    config: Arc<ConfigStruct>,
+    symbols: HashMap<String, Arc<ConfigStruct>>,
+}
+
+impl SymbolConfigs {
+    pub fn new(shared_config: &SharedConfig) -> Self {
+        Self {
+            config: shared_config.read().unwrap().clone(),
+            symbols: HashMap::new(),
+        }
+    }
+
+    // takes the current shared config, resolved symbols are dropped if it was reloaded
+    pub fn refresh(&mut self, shared_config: &SharedConfig) {
+        let config = shared_config.read().unwrap().clone();
+
+        if !Arc::ptr_eq(&config, &self.config) {
+            self.config = config;
+            self.symbols.clear();
+        }
+    }
+
+    pub fn global(&self) -> Arc<ConfigStruct> {
+        self.config.clone()
+    }
+
+    pub fn for_symbol(&mut self, symbol: &str) -> Arc<ConfigStruct> {
+        self.symbols
+            .entry(symbol.to_string())
+            .or_insert_with(|| Arc::new(self.config.for_symbol(symbol)))
+            .clone()
+    }
+}
+
+// options used only when the bot starts (pools, cmd file path, endpoints), changes are ignored
+// until restart
+pub const RESTART_REQUIRED_KEYS: [&str; 8] = [
+    "base_starting_assets",
+    "max_simultaneously_trading_pairs",
+    "starting_asset_value",
+    "cmd_dir",
+    "cmd_stop_and_sell_instantly",
+    "exchange_environment",
+    "rest_api_endpoint",
+    "ws_endpoint",
+];
+
+// changed option: key, old value, new value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigChange {
+    pub key: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl ConfigChange {
+    pub fn requires_restart(&self) -> bool {
+        RESTART_REQUIRED_KEYS.contains(&self.key.as_str())
+    }
+}
+
+fn flatten_table(prefix: &str, table: toml::Table, options: &mut BTreeMap<String, String>) {
+    for (key, value) in table {
+        let key = match prefix {
+            "" => key,
+            _ => format!("{prefix}.{key}"),
+        };
+
+        match value {
+            toml::Value::Table(t) => flatten_table(&key, t, options),
+            v => {
+                options.insert(key, v.to_string());
+            }
+        }
+    }
+}
+
+// "section.option" => value (also for nested sections, like symbol overrides)
+fn flatten_config(config: &ConfigStruct) -> BTreeMap<String, String> {
+    let mut options = BTreeMap::new();
+
+    match toml::Value::try_from(config) {
+        Ok(toml::Value::Table(table)) => flatten_table("", table, &mut options),
+        Ok(_) => error!("cannot serialize config: not a table"),
+        Err(e) => error!("cannot serialize config: {e}"),
+    }
+
+    options
+}
+
+pub fn config_diff(old: &ConfigStruct, new: &ConfigStruct) -> Vec<ConfigChange> {
+    let old_options = flatten_config(old);
+    let new_options = flatten_config(new);
+
+    let mut keys: Vec<&String> = old_options.keys().chain(new_options.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .filter_map(|key| {
+            let old_value = old_options.get(key).cloned().unwrap_or_default();
+            let new_value = new_options.get(key).cloned().unwrap_or_default();
+
+            if old_value == new_value {
+                return None;
+            }
+
+            Some(ConfigChange {
+                key: key.clone(),
+                old: old_value,
+                new: new_value,
+            })
+        })
+        .collect()
+}
+
+// new config with restart-required options taken from the one currently in use - |data_dir| is kept
+// too, it's not in RESTART_REQUIRED_KEYS as it's not a config file option (--data-dir), reloaded file
+// doesn't have it
+fn keep_restart_required_options(current: &ConfigStruct, new: ConfigStruct) -> ConfigStruct {
+    let mut config = new;
+
+    config.base_starting_assets = current.base_starting_assets.clone();
+    config.max_simultaneously_trading_pairs = current.max_simultaneously_trading_pairs;
+    config.starting_asset_value = current.starting_asset_value;
+    config.cmd_dir = current.cmd_dir.clone();
+    config.cmd_stop_and_sell_instantly = current.cmd_stop_and_sell_instantly.clone();
+    config.exchange_environment = current.exchange_environment.clone();
+    config.rest_api_endpoint = current.rest_api_endpoint.clone();
+    config.ws_endpoint = current.ws_endpoint.clone();
+    config.data_dir = current.data_dir.clone();
+
+    config
+}
+
+// validates changed config file and swaps it in, returns false if the file was rejected
+pub fn reload_config(path: &str, shared_config: &SharedConfig) -> bool {
+    let (new_config, warnings) = match load_config(path) {
+        Ok(v) => v,
+        Err(issues) => {
+            for issue in issues {
+                warn!("config reload: {issue}");
+            }
+            warn!("config reload: {path} REJECTED, previous config is still in use");
+            return false;
+        }
+    };
+
+    for issue in warnings {
+        warn!("config reload: {issue}");
+    }
+
+    let current_config = shared_config.read().unwrap().clone();
+    let changes = config_diff(&current_config, &new_config);
+
+    if changes.is_empty() {
+        info!("config reload: {path} changed, but there are no changes in options");
+        return true;
+    }
+
+    for change in &changes {
+        if change.requires_restart() {
+            warn!(
+                "config reload: |{}| changed ({} -> {}), but it REQUIRES RESTART - change is ignored until then",
+                change.key, change.old, change.new
+            );
+        } else {
+            info!(
+                "config reload: |{}| changed: {} -> {}",
+                change.key, change.old, change.new
+            );
+        }
+    }
+
+    let config = keep_restart_required_options(&current_config, new_config);
+
+    // new options may not fit to the ones which are kept until restart
+    let errors: Vec<_> = validate_config(&config)
+        .into_iter()
+        .filter(|i| i.is_error())
+        .collect();
+
+    if !errors.is_empty() {
+        for issue in errors {
+            warn!("config reload: {issue} (with options kept until restart)");
+        }
+        warn!("config reload: {path} REJECTED, previous config is still in use");
+        return false;
+    }
+
+    *shared_config.write().unwrap() = Arc::new(config);
+    info!("config reload: {path} applied");
+
+    true
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// thread: checks periodically if config file was changed and reloads it
+pub fn config_watcher(path: &str, shared_config: SharedConfig) {
+    info!("config watcher started for {path}");
+
+    let mut last_modified = modified_time(path);
+
+    loop {
+        let period = shared_config
+            .read()
+            .unwrap()
+            .config_reload_check_period_secs;
+        thread::sleep(Duration::from_secs(period));
+
+        let modified = modified_time(path);
+        if modified.is_none() || modified == last_modified {
+            continue;
+        }
+
+        last_modified = modified;
+        info!("config reload: {path} was modified, validating...");
+        reload_config(path, &shared_config);
+    }
+}
@@ -1,10 +1,18 @@
-use crate::core::types::Symbol;
+use crate::config::overrides::{AssetGroupOverrides, SymbolOverrides};
+use crate::core::cli::data_dir_path;
+use crate::core::types::{ExchangeEnvironment, OrderKind, Symbol};
 use rust_decimal::prelude::*;
-use serde::Deserialize;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 pub const CONFIG_FILENAME: &str = "config.toml";
 
-#[derive(Deserialize, Debug, Clone)]
+/*
+Options added after the first release have defaults, so older config files are still loaded. Features are off
+by default (old behaviour), other defaults are the values from config.toml.
+ */
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ConfigStruct {
     pub base_starting_assets: Vec<Symbol>,
     pub excluded_symbols: Vec<Symbol>,
@@ -12,47 +20,179 @@ pub struct ConfigStruct {
     pub symbol_monitor: ConfigSymbolMonitor,
     pub orderbook_monitor: ConfigOrderBookMonitor,
     pub exchange_info_apis: Vec<String>,
+    #[serde(default)]
+    pub exchange_environment: ExchangeEnvironment,
+    #[serde(default)]
+    pub rest_api_endpoint: Option<String>,
+    #[serde(default)]
+    pub ws_endpoint: Option<String>,
     pub exchange_info_fetch_delay_secs: u64,
     pub max_simultaneously_trading_pairs: Decimal,
     pub starting_asset_value: Decimal,
+    #[serde(default = "default_candidate_max_age_secs")]
+    pub candidate_max_age_secs: u64,
+    #[serde(default = "default_candidate_score_weight")]
+    pub candidate_score_window_weight: Decimal,
+    #[serde(default = "default_candidate_score_weight")]
+    pub candidate_score_activity_weight: Decimal,
+    #[serde(default = "default_candidate_score_spread_weight")]
+    pub candidate_score_spread_weight: Decimal,
     pub cmd_dir: String,
     pub cmd_read_period_secs: u64,
     pub cmd_stop_and_sell_instantly: String,
+    #[serde(default)]
+    pub cmd_symbols_monitor_health: String,
+    #[serde(default = "default_trade_history_file")]
+    pub trade_history_file: String,
+    #[serde(default = "default_symbols_monitor_stale_secs")]
+    pub symbols_monitor_stale_secs: u64,
+    #[serde(default = "default_symbols_monitor_reconnect_delay_ms")]
+    pub symbols_monitor_reconnect_delay_ms: u64,
+    #[serde(default)]
+    pub symbols_monitor_health_file: String,
+    #[serde(default = "default_config_reload_check_period_secs")]
+    pub config_reload_check_period_secs: u64,
+    #[serde(default)]
+    pub symbols: SymbolOverrides,
+    #[serde(default)]
+    pub asset_groups: AssetGroupOverrides,
+    // not a config file option - set from --data-dir command line option
+    #[serde(skip)]
+    pub data_dir: String,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+impl ConfigStruct {
+    // |trade_history_file| relative to data dir
+    pub fn trade_history_path(&self) -> String {
+        data_dir_path(&self.data_dir, &self.trade_history_file)
+    }
+
+    // |symbols_monitor_health_file| relative to data dir, None - health is not written
+    pub fn symbols_monitor_health_path(&self) -> Option<String> {
+        match self.symbols_monitor_health_file.is_empty() {
+            true => None,
+            false => Some(data_dir_path(
+                &self.data_dir,
+                &self.symbols_monitor_health_file,
+            )),
+        }
+    }
+}
+
+// some of the options are not implemented yet (see config.toml)
+#[allow(dead_code)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ConfigSymbolMonitor {
-    pub symbol_price_list_length: usize,
+    #[serde(default = "default_window_secs")]
+    pub pre_window_secs: u64,
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+    #[serde(default = "default_window_secs")]
+    pub post_window_secs: u64,
     pub symbol_price_trigger_percent_value_rise_min: Decimal,
     pub symbol_price_trigger_percent_value_rise_max: Decimal,
     pub symbol_price_trigger_percent_value_drop: Decimal,
     pub symbol_price_trigger_time_period_secs: u64,
     pub symbol_price_trigger_count_within_period: i32,
-    pub symbol_price_violatile_check_time_secs: u64,
-    pub symbol_price_violatile_required_count: u64,
+    #[serde(default = "default_activity_window_secs")]
+    pub activity_window_secs: u64,
+    #[serde(default)]
+    pub activity_min_trades_per_sec: Decimal,
+    #[serde(default)]
+    pub activity_min_quote_volume_per_sec: Decimal,
+    #[serde(default)]
+    pub activity_min_book_updates_per_sec: Decimal,
+    #[serde(default)]
+    pub activity_min_realized_volatility_percent: Decimal,
     pub symbol_stat_list_len: u64,
     pub symbol_stat_list_display_period_secs: u64,
     pub pre_window_analysis: bool,
     pub pre_window_price_value_rise_min_max_percent: [Decimal; 2],
     pub pre_window_price_value_drop_min_max_percent: [Decimal; 2],
     pub pre_window_price_value_monitor_min_max_percent: [Decimal; 2],
+    #[serde(default)]
+    pub pre_window_run_up_min_max_percent: Vec<Decimal>,
+    #[serde(default)]
+    pub pre_window_drawdown_min_max_percent: Vec<Decimal>,
+    #[serde(default)]
+    pub pre_window_slope_min_max_percent_per_sec: Vec<Decimal>,
+    #[serde(default)]
+    pub pre_window_std_dev_min_max_percent: Vec<Decimal>,
+    #[serde(default)]
+    pub pre_window_direction_changes_min_max: Vec<Decimal>,
+    #[serde(default)]
+    pub pre_window_min_quote_volume: Decimal,
+    #[serde(default)]
+    pub pre_window_min_trades: u64,
     pub window_price_value_rise_min_max_percent: [Decimal; 2],
     pub window_price_value_drop_min_max_percent: [Decimal; 2],
     pub window_price_value_monitor_min_max_percent: [Decimal; 2],
+    #[serde(default)]
+    pub window_run_up_min_max_percent: Vec<Decimal>,
+    #[serde(default)]
+    pub window_drawdown_min_max_percent: Vec<Decimal>,
+    #[serde(default)]
+    pub window_slope_min_max_percent_per_sec: Vec<Decimal>,
+    #[serde(default)]
+    pub window_std_dev_min_max_percent: Vec<Decimal>,
+    #[serde(default)]
+    pub window_direction_changes_min_max: Vec<Decimal>,
+    #[serde(default)]
+    pub window_min_quote_volume: Decimal,
+    #[serde(default)]
+    pub window_min_trades: u64,
+    #[serde(default)]
+    pub window_min_quote_volume_to_pre_window_ratio: Decimal,
     pub post_window_analysis: bool,
     pub post_window_price_value_rise_min_max_percent: [Decimal; 2],
     pub post_window_price_value_drop_min_max_percent: [Decimal; 2],
     pub post_window_price_value_monitor_min_max_percent: [Decimal; 2],
+    #[serde(default)]
+    pub post_window_run_up_min_max_percent: Vec<Decimal>,
+    #[serde(default)]
+    pub post_window_drawdown_min_max_percent: Vec<Decimal>,
+    #[serde(default)]
+    pub post_window_slope_min_max_percent_per_sec: Vec<Decimal>,
+    #[serde(default)]
+    pub post_window_std_dev_min_max_percent: Vec<Decimal>,
+    #[serde(default)]
+    pub post_window_direction_changes_min_max: Vec<Decimal>,
+    #[serde(default)]
+    pub post_window_min_quote_volume: Decimal,
+    #[serde(default)]
+    pub post_window_min_trades: u64,
+    #[serde(default)]
+    pub post_window_min_quote_volume_to_window_ratio: Decimal,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+// some of the options are not implemented yet (see config.toml)
+#[allow(dead_code)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ConfigOrderBookMonitor {
     pub allowed_buy_diff_from_symbol_monitor_percent: Decimal,
     pub ignore_if_percent_profit_changed_more_than_percent: Decimal,
     pub maximum_count_of_profit_changed_ignored_readings: u64,
     pub use_profits_to_trade: bool,
-    pub acceptable_liquidity_count: Decimal,
+    pub acceptable_liquidity_count: u64,
+    #[serde(default)]
+    pub liquidity_max_spread_percent: Decimal,
+    #[serde(default)]
+    pub liquidity_depth_range_percent: Decimal,
+    #[serde(default)]
+    pub liquidity_min_depth: Decimal,
+    #[serde(default)]
+    pub liquidity_max_exit_slippage_percent: Decimal,
+    #[serde(default)]
+    pub liquidity_wait_secs: u64,
     pub exchange_comission: Decimal,
+    #[serde(default)]
+    pub fee_tier: String,
+    #[serde(default)]
+    pub fee_tiers: BTreeMap<String, Decimal>,
+    #[serde(default)]
+    pub bnb_fee_payment_enabled: bool,
+    #[serde(default = "default_bnb_fee_discount_percent")]
+    pub bnb_fee_discount_percent: Decimal,
     pub absolute_minimal_profit_over_comission: Decimal,
     pub time_limit_secs: u64,
     pub time_limit_requires_profit: bool,
@@ -68,4 +208,125 @@ pub struct ConfigOrderBookMonitor {
     pub good_profit_crossed_allowed_drop_percent: Decimal,
     pub currently_trading_reminder_period_secs: u64,
     pub break_between_trading_same_symbol_secs: u64,
+    #[serde(default)]
+    pub entry_order_type: OrderKind,
+    #[serde(default)]
+    pub good_profit_exit_order_type: OrderKind,
+    #[serde(default)]
+    pub min_profit_exit_order_type: OrderKind,
+    #[serde(default)]
+    pub loss_limit_exit_order_type: OrderKind,
+    #[serde(default)]
+    pub timeout_exit_order_type: OrderKind,
+    #[serde(default)]
+    pub requested_exit_order_type: OrderKind,
+    #[serde(default)]
+    pub entry_chase_attempts: u64,
+    #[serde(default = "default_entry_chase_max_deviation_percent")]
+    pub entry_chase_max_deviation_percent: Decimal,
+    #[serde(default)]
+    pub exchange_protection_enabled: bool,
+    #[serde(default = "default_exchange_protection_stop_limit_offset_percent")]
+    pub exchange_protection_stop_limit_offset_percent: Decimal,
+    #[serde(default = "default_exchange_protection_min_stop_move_percent")]
+    pub exchange_protection_min_stop_move_percent: Decimal,
+    #[serde(default = "default_exchange_protection_check_period_secs")]
+    pub exchange_protection_check_period_secs: u64,
+    #[serde(default = "default_depth_stream_reconnect_backoff_ms")]
+    pub depth_stream_reconnect_backoff_ms: u64,
+    #[serde(default = "default_depth_stream_reconnect_max_backoff_secs")]
+    pub depth_stream_reconnect_max_backoff_secs: u64,
+    #[serde(default = "default_depth_stream_stale_secs")]
+    pub depth_stream_stale_secs: u64,
+    #[serde(default = "default_depth_rest_fallback_period_ms")]
+    pub depth_rest_fallback_period_ms: u64,
+    #[serde(default)]
+    pub depth_stream_stale_exit_secs: u64,
+}
+
+impl ConfigOrderBookMonitor {
+    // comission percent we really pay: |fee_tier| rate (|exchange_comission| without tier) with BNB discount
+    pub fn comission_percent(&self) -> Decimal {
+        let rate = match self.fee_tiers.get(&self.fee_tier) {
+            Some(rate) => *rate,
+            None => self.exchange_comission,
+        };
+
+        match self.bnb_fee_payment_enabled {
+            true => rate * (Decimal::ONE - self.bnb_fee_discount_percent / Decimal::ONE_HUNDRED),
+            false => rate,
+        }
+    }
+}
+
+fn default_candidate_max_age_secs() -> u64 {
+    5
+}
+
+fn default_candidate_score_weight() -> Decimal {
+    Decimal::ONE
+}
+
+fn default_candidate_score_spread_weight() -> Decimal {
+    dec!(10)
+}
+
+fn default_trade_history_file() -> String {
+    "trade_history.jsonl".to_string()
+}
+
+fn default_symbols_monitor_stale_secs() -> u64 {
+    10
+}
+
+fn default_symbols_monitor_reconnect_delay_ms() -> u64 {
+    1000
+}
+
+fn default_config_reload_check_period_secs() -> u64 {
+    5
+}
+
+fn default_window_secs() -> u64 {
+    15
+}
+
+fn default_activity_window_secs() -> u64 {
+    10
+}
+
+fn default_bnb_fee_discount_percent() -> Decimal {
+    dec!(25)
+}
+
+fn default_entry_chase_max_deviation_percent() -> Decimal {
+    dec!(0.5)
+}
+
+fn default_exchange_protection_stop_limit_offset_percent() -> Decimal {
+    dec!(0.5)
+}
+
+fn default_exchange_protection_min_stop_move_percent() -> Decimal {
+    dec!(0.2)
+}
+
+fn default_exchange_protection_check_period_secs() -> u64 {
+    5
+}
+
+fn default_depth_stream_reconnect_backoff_ms() -> u64 {
+    500
+}
+
+fn default_depth_stream_reconnect_max_backoff_secs() -> u64 {
+    30
+}
+
+fn default_depth_stream_stale_secs() -> u64 {
+    5
+}
+
+fn default_depth_rest_fallback_period_ms() -> u64 {
+    1000
 }
@@ -0,0 +1,1115 @@
+use crate::config::overrides::{apply_section_override, ConfigOverride};
+use crate::config::settings::{ConfigOrderBookMonitor, ConfigStruct, ConfigSymbolMonitor};
+use crate::core::types::{ExchangeEnvironment, OrderKind};
+use rust_decimal::Decimal;
+use std::fmt;
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigIssueLevel {
+    // bot refuses to start
+    Error,
+    // bot starts but the option probably doesn't do what you expect
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    pub level: ConfigIssueLevel,
+    pub key: String,
+    pub reason: String,
+}
+
+impl ConfigIssue {
+    pub fn error(key: &str, reason: String) -> Self {
+        Self {
+            level: ConfigIssueLevel::Error,
+            key: key.to_string(),
+            reason,
+        }
+    }
+
+    pub fn warning(key: &str, reason: String) -> Self {
+        Self {
+            level: ConfigIssueLevel::Warning,
+            key: key.to_string(),
+            reason,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.level == ConfigIssueLevel::Error
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.level {
+            ConfigIssueLevel::Error => "ERROR",
+            ConfigIssueLevel::Warning => "WARNING",
+        };
+        write!(f, "[{level}] |{}|: {}", self.key, self.reason)
+    }
+}
+
+// options which are not used anymore and what replaced them - serde ignores unknown options, so
+// config files from older versions would be silently changed
+const REMOVED_OPTIONS: [(&str, &str, &str); 3] = [
+    (
+        "symbol_monitor",
+        "symbol_price_list_length",
+        "pre_window_secs, window_secs and post_window_secs",
+    ),
+    (
+        "symbol_monitor",
+        "symbol_price_violatile_check_time_secs",
+        "activity_window_secs",
+    ),
+    (
+        "symbol_monitor",
+        "symbol_price_violatile_required_count",
+        "activity_min_trades_per_sec (and other activity_min_* options)",
+    ),
+];
+
+/// Reads, parses and validates config file - Err contains every problem found (at least one is
+/// an error), Ok contains the config and warnings (if any).
+pub fn load_config(path: &str) -> Result<(ConfigStruct, Vec<ConfigIssue>), Vec<ConfigIssue>> {
+    match fs::read_to_string(path) {
+        Ok(config_data) => parse_config(path, &config_data),
+        Err(e) => Err(vec![ConfigIssue::error(
+            path,
+            format!("cannot read file: {e}"),
+        )]),
+    }
+}
+
+// load_config() of already read file, |path| is used only in issues
+fn parse_config(
+    path: &str,
+    config_data: &str,
+) -> Result<(ConfigStruct, Vec<ConfigIssue>), Vec<ConfigIssue>> {
+    let config: ConfigStruct = match toml::from_str(config_data) {
+        Ok(c) => c,
+        Err(e) => {
+            // toml error message already contains line, column and the key
+            let reason = e.message().to_string();
+            let key = match e.span() {
+                Some(span) => format!("{path}:{}", line_number(config_data, span.start)),
+                None => path.to_string(),
+            };
+            return Err(vec![ConfigIssue::error(&key, reason)]);
+        }
+    };
+
+    let mut issues = validate_config(&config);
+    validate_removed_options(config_data, &mut issues);
+
+    if issues.iter().any(|i| i.is_error()) {
+        return Err(issues);
+    }
+
+    Ok((config, issues))
+}
+
+// 1-based number of the line with |offset|
+fn line_number(data: &str, offset: usize) -> usize {
+    data[..offset.min(data.len())].matches('\n').count() + 1
+}
+
+fn validate_removed_options(config_data: &str, issues: &mut Vec<ConfigIssue>) {
+    let table: toml::Table = match toml::from_str(config_data) {
+        Ok(t) => t,
+        Err(_) => return,
+    };
+
+    for (section, key, replacement) in REMOVED_OPTIONS {
+        if let Some(toml::Value::Table(options)) = table.get(section) {
+            if options.contains_key(key) {
+                issues.push(ConfigIssue::warning(
+                    key,
+                    format!("is not used anymore (ignored), use {replacement} instead"),
+                ));
+            }
+        }
+    }
+}
+
+/// Checks all consistency rules, returns every problem found (not only the first one).
+pub fn validate_config(config: &ConfigStruct) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+
+    validate_general(config, &mut issues);
+    validate_symbol_monitor(config, &mut issues);
+    validate_orderbook_monitor(config, &mut issues);
+    validate_overrides(config, &mut issues);
+
+    issues
+}
+
+fn validate_general(config: &ConfigStruct, issues: &mut Vec<ConfigIssue>) {
+    if config.base_starting_assets.is_empty() {
+        issues.push(ConfigIssue::error(
+            "base_starting_assets",
+            "at least one base asset is required".to_string(),
+        ));
+    }
+
+    validate_endpoints(config, issues);
+
+    if config.exchange_info_fetch_delay_secs == 0 {
+        issues.push(ConfigIssue::error(
+            "exchange_info_fetch_delay_secs",
+            "has to be larger than 0 (API calls are limited)".to_string(),
+        ));
+    }
+
+    if config.max_simultaneously_trading_pairs <= Decimal::ZERO
+        || !config.max_simultaneously_trading_pairs.fract().is_zero()
+    {
+        issues.push(ConfigIssue::error(
+            "max_simultaneously_trading_pairs",
+            format!(
+                "has to be a positive integer, got {}",
+                config.max_simultaneously_trading_pairs
+            ),
+        ));
+    }
+
+    if config.candidate_max_age_secs == 0 {
+        issues.push(ConfigIssue::error(
+            "candidate_max_age_secs",
+            "has to be larger than 0".to_string(),
+        ));
+    }
+
+    let candidate_weights = [
+        (
+            "candidate_score_window_weight",
+            config.candidate_score_window_weight,
+        ),
+        (
+            "candidate_score_activity_weight",
+            config.candidate_score_activity_weight,
+        ),
+        (
+            "candidate_score_spread_weight",
+            config.candidate_score_spread_weight,
+        ),
+    ];
+
+    for (key, value) in candidate_weights {
+        if value < Decimal::ZERO {
+            issues.push(ConfigIssue::error(
+                key,
+                format!("{value} can't be negative"),
+            ));
+        }
+    }
+
+    if config.starting_asset_value <= Decimal::ZERO {
+        issues.push(ConfigIssue::error(
+            "starting_asset_value",
+            format!("has to be positive, got {}", config.starting_asset_value),
+        ));
+    }
+
+    if config.cmd_dir.is_empty() {
+        issues.push(ConfigIssue::error(
+            "cmd_dir",
+            "directory for commands can't be empty".to_string(),
+        ));
+    }
+
+    if config.config_reload_check_period_secs == 0 {
+        issues.push(ConfigIssue::error(
+            "config_reload_check_period_secs",
+            "has to be larger than 0".to_string(),
+        ));
+    }
+
+    if config.trade_history_file.is_empty() {
+        issues.push(ConfigIssue::error(
+            "trade_history_file",
+            "file name can't be empty".to_string(),
+        ));
+    }
+
+    if config.symbols_monitor_stale_secs == 0 {
+        issues.push(ConfigIssue::error(
+            "symbols_monitor_stale_secs",
+            "has to be larger than 0".to_string(),
+        ));
+    }
+
+    if config.symbols_monitor_reconnect_delay_ms == 0 {
+        issues.push(ConfigIssue::error(
+            "symbols_monitor_reconnect_delay_ms",
+            "has to be larger than 0".to_string(),
+        ));
+    }
+}
+
+fn validate_endpoints(config: &ConfigStruct, issues: &mut Vec<ConfigIssue>) {
+    let is_http = |a: &str| a.starts_with("http://") || a.starts_with("https://");
+    let is_ws = |a: &str| a.starts_with("ws://") || a.starts_with("wss://");
+
+    if config.exchange_environment == ExchangeEnvironment::Production {
+        if config.exchange_info_apis.is_empty() {
+            issues.push(ConfigIssue::error(
+                "exchange_info_apis",
+                "at least one exchangeInfo API address is required".to_string(),
+            ));
+        }
+
+        for api in &config.exchange_info_apis {
+            if !is_http(api) {
+                issues.push(ConfigIssue::error(
+                    "exchange_info_apis",
+                    format!("{api} is not a http(s) address"),
+                ));
+            }
+        }
+    }
+
+    if config.exchange_environment != ExchangeEnvironment::Custom {
+        for (key, value) in [
+            ("rest_api_endpoint", &config.rest_api_endpoint),
+            ("ws_endpoint", &config.ws_endpoint),
+        ] {
+            if value.is_some() {
+                issues.push(ConfigIssue::warning(
+                    key,
+                    format!(
+                        "ignored, it's used only when |exchange_environment| is \"custom\" (now: {})",
+                        config.exchange_environment
+                    ),
+                ));
+            }
+        }
+        return;
+    }
+
+    match &config.rest_api_endpoint {
+        Some(a) if is_http(a) => (),
+        Some(a) => issues.push(ConfigIssue::error(
+            "rest_api_endpoint",
+            format!("{a} is not a http(s) address"),
+        )),
+        None => issues.push(ConfigIssue::error(
+            "rest_api_endpoint",
+            "required when |exchange_environment| is \"custom\"".to_string(),
+        )),
+    }
+
+    match &config.ws_endpoint {
+        Some(a) if is_ws(a) => (),
+        Some(a) => issues.push(ConfigIssue::error(
+            "ws_endpoint",
+            format!("{a} is not a ws(s) address"),
+        )),
+        None => issues.push(ConfigIssue::error(
+            "ws_endpoint",
+            "required when |exchange_environment| is \"custom\"".to_string(),
+        )),
+    }
+}
+
+fn validate_min_max_percent(key: &str, min_max: &[Decimal; 2], issues: &mut Vec<ConfigIssue>) {
+    let [min, max] = min_max;
+
+    if min > max {
+        issues.push(ConfigIssue::error(
+            key,
+            format!("min value ({min}) is larger than max value ({max})"),
+        ));
+    }
+}
+
+fn validate_symbol_monitor(config: &ConfigStruct, issues: &mut Vec<ConfigIssue>) {
+    let sm = &config.symbol_monitor;
+
+    // enabled windows have to be at least 1 second long to compare the first with the last price
+    // (see doc/Algorithm.md)
+    let windows = [
+        ("window_secs", sm.window_secs, true),
+        (
+            "pre_window_secs",
+            sm.pre_window_secs,
+            sm.pre_window_analysis,
+        ),
+        (
+            "post_window_secs",
+            sm.post_window_secs,
+            sm.post_window_analysis,
+        ),
+    ];
+
+    for (key, secs, enabled) in windows {
+        if enabled && secs == 0 {
+            issues.push(ConfigIssue::error(
+                key,
+                "has to be larger than 0".to_string(),
+            ));
+        }
+    }
+
+    if sm.activity_window_secs == 0 {
+        issues.push(ConfigIssue::error(
+            "activity_window_secs",
+            "has to be larger than 0".to_string(),
+        ));
+    }
+
+    let activity_thresholds = [
+        (
+            "activity_min_trades_per_sec",
+            sm.activity_min_trades_per_sec,
+        ),
+        (
+            "activity_min_quote_volume_per_sec",
+            sm.activity_min_quote_volume_per_sec,
+        ),
+        (
+            "activity_min_book_updates_per_sec",
+            sm.activity_min_book_updates_per_sec,
+        ),
+        (
+            "activity_min_realized_volatility_percent",
+            sm.activity_min_realized_volatility_percent,
+        ),
+    ];
+
+    for (key, value) in activity_thresholds {
+        if value < Decimal::ZERO {
+            issues.push(ConfigIssue::error(
+                key,
+                format!("{value} can't be negative (0 turns the threshold off)"),
+            ));
+        }
+    }
+
+    let volume_thresholds = [
+        (
+            "pre_window_min_quote_volume",
+            sm.pre_window_min_quote_volume,
+        ),
+        ("window_min_quote_volume", sm.window_min_quote_volume),
+        (
+            "post_window_min_quote_volume",
+            sm.post_window_min_quote_volume,
+        ),
+        (
+            "window_min_quote_volume_to_pre_window_ratio",
+            sm.window_min_quote_volume_to_pre_window_ratio,
+        ),
+        (
+            "post_window_min_quote_volume_to_window_ratio",
+            sm.post_window_min_quote_volume_to_window_ratio,
+        ),
+    ];
+
+    for (key, value) in volume_thresholds {
+        if value < Decimal::ZERO {
+            issues.push(ConfigIssue::error(
+                key,
+                format!("{value} can't be negative (0 turns the check off)"),
+            ));
+        }
+    }
+
+    // volume ratio compares neighbouring windows, both have to be enabled
+    if !sm.pre_window_analysis && sm.window_min_quote_volume_to_pre_window_ratio > Decimal::ZERO {
+        issues.push(ConfigIssue::warning(
+            "window_min_quote_volume_to_pre_window_ratio",
+            "is ignored, pre_window_analysis is disabled".to_string(),
+        ));
+    }
+    if !sm.post_window_analysis && sm.post_window_min_quote_volume_to_window_ratio > Decimal::ZERO {
+        issues.push(ConfigIssue::warning(
+            "post_window_min_quote_volume_to_window_ratio",
+            "is ignored, post_window_analysis is disabled".to_string(),
+        ));
+    }
+
+    let min_max_options = [
+        (
+            "pre_window_price_value_rise_min_max_percent",
+            &sm.pre_window_price_value_rise_min_max_percent,
+        ),
+        (
+            "pre_window_price_value_drop_min_max_percent",
+            &sm.pre_window_price_value_drop_min_max_percent,
+        ),
+        (
+            "pre_window_price_value_monitor_min_max_percent",
+            &sm.pre_window_price_value_monitor_min_max_percent,
+        ),
+        (
+            "window_price_value_rise_min_max_percent",
+            &sm.window_price_value_rise_min_max_percent,
+        ),
+        (
+            "window_price_value_drop_min_max_percent",
+            &sm.window_price_value_drop_min_max_percent,
+        ),
+        (
+            "window_price_value_monitor_min_max_percent",
+            &sm.window_price_value_monitor_min_max_percent,
+        ),
+        (
+            "post_window_price_value_rise_min_max_percent",
+            &sm.post_window_price_value_rise_min_max_percent,
+        ),
+        (
+            "post_window_price_value_drop_min_max_percent",
+            &sm.post_window_price_value_drop_min_max_percent,
+        ),
+        (
+            "post_window_price_value_monitor_min_max_percent",
+            &sm.post_window_price_value_monitor_min_max_percent,
+        ),
+    ];
+
+    for (key, min_max) in min_max_options {
+        validate_min_max_percent(key, min_max, issues);
+    }
+
+    // window statistics thresholds: [] (not checked) or [min, max]
+    let window_stats_options: [(&str, &Vec<Decimal>); 15] = [
+        (
+            "pre_window_run_up_min_max_percent",
+            &sm.pre_window_run_up_min_max_percent,
+        ),
+        (
+            "pre_window_drawdown_min_max_percent",
+            &sm.pre_window_drawdown_min_max_percent,
+        ),
+        (
+            "pre_window_slope_min_max_percent_per_sec",
+            &sm.pre_window_slope_min_max_percent_per_sec,
+        ),
+        (
+            "pre_window_std_dev_min_max_percent",
+            &sm.pre_window_std_dev_min_max_percent,
+        ),
+        (
+            "pre_window_direction_changes_min_max",
+            &sm.pre_window_direction_changes_min_max,
+        ),
+        (
+            "window_run_up_min_max_percent",
+            &sm.window_run_up_min_max_percent,
+        ),
+        (
+            "window_drawdown_min_max_percent",
+            &sm.window_drawdown_min_max_percent,
+        ),
+        (
+            "window_slope_min_max_percent_per_sec",
+            &sm.window_slope_min_max_percent_per_sec,
+        ),
+        (
+            "window_std_dev_min_max_percent",
+            &sm.window_std_dev_min_max_percent,
+        ),
+        (
+            "window_direction_changes_min_max",
+            &sm.window_direction_changes_min_max,
+        ),
+        (
+            "post_window_run_up_min_max_percent",
+            &sm.post_window_run_up_min_max_percent,
+        ),
+        (
+            "post_window_drawdown_min_max_percent",
+            &sm.post_window_drawdown_min_max_percent,
+        ),
+        (
+            "post_window_slope_min_max_percent_per_sec",
+            &sm.post_window_slope_min_max_percent_per_sec,
+        ),
+        (
+            "post_window_std_dev_min_max_percent",
+            &sm.post_window_std_dev_min_max_percent,
+        ),
+        (
+            "post_window_direction_changes_min_max",
+            &sm.post_window_direction_changes_min_max,
+        ),
+    ];
+
+    for (key, list) in window_stats_options {
+        match list.as_slice() {
+            [] => (),
+            [min, max] => validate_min_max_percent(key, &[*min, *max], issues),
+            _ => issues.push(ConfigIssue::error(
+                key,
+                format!("has {} values, [] or [min, max] is expected", list.len()),
+            )),
+        }
+    }
+}
+
+fn validate_orderbook_monitor(config: &ConfigStruct, issues: &mut Vec<ConfigIssue>) {
+    let om = &config.orderbook_monitor;
+    let one = Decimal::ONE;
+    let hundred = Decimal::ONE_HUNDRED;
+
+    if om.exchange_comission < Decimal::ZERO || om.exchange_comission >= hundred {
+        issues.push(ConfigIssue::error(
+            "exchange_comission",
+            format!("{} is not a valid percent value", om.exchange_comission),
+        ));
+    }
+
+    for (tier, rate) in &om.fee_tiers {
+        if *rate < Decimal::ZERO || *rate >= hundred {
+            issues.push(ConfigIssue::error(
+                "fee_tiers",
+                format!("{tier}: {rate} is not a valid percent value"),
+            ));
+        }
+    }
+
+    if !om.fee_tier.is_empty() && !om.fee_tiers.contains_key(&om.fee_tier) {
+        issues.push(ConfigIssue::error(
+            "fee_tier",
+            format!("\"{}\" is not listed in fee_tiers", om.fee_tier),
+        ));
+    }
+
+    if om.bnb_fee_discount_percent < Decimal::ZERO || om.bnb_fee_discount_percent >= hundred {
+        issues.push(ConfigIssue::error(
+            "bnb_fee_discount_percent",
+            format!(
+                "{} is not a valid percent value",
+                om.bnb_fee_discount_percent
+            ),
+        ));
+    }
+
+    let liquidity_thresholds = [
+        (
+            "liquidity_max_spread_percent",
+            om.liquidity_max_spread_percent,
+        ),
+        (
+            "liquidity_depth_range_percent",
+            om.liquidity_depth_range_percent,
+        ),
+        ("liquidity_min_depth", om.liquidity_min_depth),
+        (
+            "liquidity_max_exit_slippage_percent",
+            om.liquidity_max_exit_slippage_percent,
+        ),
+    ];
+
+    for (key, value) in liquidity_thresholds {
+        if value < Decimal::ZERO {
+            issues.push(ConfigIssue::error(
+                key,
+                format!("{value} can't be negative"),
+            ));
+        }
+    }
+
+    if om.exchange_protection_stop_limit_offset_percent < Decimal::ZERO
+        || om.exchange_protection_stop_limit_offset_percent >= hundred
+    {
+        issues.push(ConfigIssue::error(
+            "exchange_protection_stop_limit_offset_percent",
+            format!(
+                "{} is not a valid percent value",
+                om.exchange_protection_stop_limit_offset_percent
+            ),
+        ));
+    }
+
+    if om.exchange_protection_min_stop_move_percent < Decimal::ZERO {
+        issues.push(ConfigIssue::error(
+            "exchange_protection_min_stop_move_percent",
+            format!(
+                "{} can't be negative",
+                om.exchange_protection_min_stop_move_percent
+            ),
+        ));
+    }
+
+    if om.entry_chase_max_deviation_percent < Decimal::ZERO {
+        issues.push(ConfigIssue::error(
+            "entry_chase_max_deviation_percent",
+            format!(
+                "can't be negative, got {}",
+                om.entry_chase_max_deviation_percent
+            ),
+        ));
+    }
+
+    if om.entry_chase_attempts > 0 && om.entry_order_type != OrderKind::Limit {
+        issues.push(ConfigIssue::warning(
+            "entry_chase_attempts",
+            format!(
+                "only limit entries are chased, entry_order_type is \"{}\"",
+                om.entry_order_type
+            ),
+        ));
+    }
+
+    if om.exchange_protection_enabled && om.exchange_protection_check_period_secs == 0 {
+        issues.push(ConfigIssue::error(
+            "exchange_protection_check_period_secs",
+            "has to be positive when exchange_protection_enabled is true".to_string(),
+        ));
+    }
+
+    let depth_stream_periods = [
+        (
+            "depth_stream_reconnect_backoff_ms",
+            om.depth_stream_reconnect_backoff_ms,
+        ),
+        (
+            "depth_stream_reconnect_max_backoff_secs",
+            om.depth_stream_reconnect_max_backoff_secs,
+        ),
+        ("depth_stream_stale_secs", om.depth_stream_stale_secs),
+        (
+            "depth_rest_fallback_period_ms",
+            om.depth_rest_fallback_period_ms,
+        ),
+    ];
+
+    for (key, value) in depth_stream_periods {
+        if value == 0 {
+            issues.push(ConfigIssue::error(key, "has to be positive".to_string()));
+        }
+    }
+
+    if om.depth_stream_stale_exit_secs > 0
+        && om.depth_stream_stale_exit_secs <= om.depth_stream_stale_secs
+    {
+        issues.push(ConfigIssue::warning(
+            "depth_stream_stale_exit_secs",
+            format!(
+                "{}s is not longer than depth_stream_stale_secs ({}s) - position is left on the first stale stream",
+                om.depth_stream_stale_exit_secs, om.depth_stream_stale_secs
+            ),
+        ));
+    }
+
+    if om.min_profit_percent <= Decimal::ZERO {
+        issues.push(ConfigIssue::error(
+            "min_profit_percent",
+            format!("has to be positive, got {}", om.min_profit_percent),
+        ));
+    }
+
+    if om.good_profit_percent <= om.min_profit_percent {
+        issues.push(ConfigIssue::error(
+            "good_profit_percent",
+            format!(
+                "{} has to be larger than min_profit_percent ({})",
+                om.good_profit_percent, om.min_profit_percent
+            ),
+        ));
+    }
+
+    // after good profit drop we sell only if price is still above min profit price, so the good
+    // profit leave price should be above min profit price - otherwise margins overlap (see
+    // config.toml) and only loss limit or timeout can finish such trade
+    let good_profit_leave_price = (one + om.good_profit_percent / hundred)
+        * (one - om.good_profit_crossed_allowed_drop_percent / hundred);
+    let min_profit_price = one + om.min_profit_percent / hundred;
+
+    if good_profit_leave_price <= min_profit_price {
+        issues.push(ConfigIssue::warning(
+            "good_profit_crossed_allowed_drop_percent",
+            format!(
+                "drop {}% from good_profit_percent ({}%) falls below min_profit_percent ({}%) - \
+                margins overlap and good profit leave never happens",
+                om.good_profit_crossed_allowed_drop_percent,
+                om.good_profit_percent,
+                om.min_profit_percent
+            ),
+        ));
+    }
+
+    if om.min_profit_crossed_allowed_drop_percent <= Decimal::ZERO {
+        issues.push(ConfigIssue::error(
+            "min_profit_crossed_allowed_drop_percent",
+            format!(
+                "has to be positive, got {}",
+                om.min_profit_crossed_allowed_drop_percent
+            ),
+        ));
+    }
+
+    let absolute_minimal_profit =
+        om.comission_percent() + om.absolute_minimal_profit_over_comission;
+
+    if absolute_minimal_profit > om.min_profit_percent {
+        issues.push(ConfigIssue::warning(
+            "absolute_minimal_profit_over_comission",
+            format!(
+                "absolute minimal profit ({absolute_minimal_profit}%) is larger than min_profit_percent ({}%)",
+                om.min_profit_percent
+            ),
+        ));
+    }
+
+    if om.loss_limit_enabled {
+        if om.loss_limit_percent <= Decimal::ZERO {
+            issues.push(ConfigIssue::error(
+                "loss_limit_percent",
+                format!("has to be positive, got {}", om.loss_limit_percent),
+            ));
+        }
+
+        if om.loss_limit_sudden_drop_to_percent < om.loss_limit_percent {
+            issues.push(ConfigIssue::error(
+                "loss_limit_sudden_drop_to_percent",
+                format!(
+                    "{} is smaller than loss_limit_percent ({}) - loss limit would never be used",
+                    om.loss_limit_sudden_drop_to_percent, om.loss_limit_percent
+                ),
+            ));
+        }
+    }
+
+    if om.time_limit_secs == 0 {
+        issues.push(ConfigIssue::warning(
+            "time_limit_secs",
+            "0 means every trade is in timeout state instantly".to_string(),
+        ));
+    }
+
+    if om.currently_trading_reminder_period_secs == 0 {
+        issues.push(ConfigIssue::error(
+            "currently_trading_reminder_period_secs",
+            "has to be larger than 0".to_string(),
+        ));
+    }
+}
+
+// "check-config" subcommand, error if the config has errors
+pub fn run_check_config(path: &str) -> Result<(), String> {
+    let issues = match load_config(path) {
+        Ok((_, warnings)) => warnings,
+        Err(issues) => issues,
+    };
+
+    for issue in &issues {
+        println!("{issue}");
+    }
+
+    let errors = issues.iter().filter(|i| i.is_error()).count();
+    let warnings = issues.len() - errors;
+
+    if errors > 0 {
+        return Err(format!("{path}: {errors} error(s), {warnings} warning(s)"));
+    }
+
+    println!("{path}: OK ({warnings} warning(s))");
+
+    Ok(())
+}
+
+// checks every override applied to global sections (exact symbol also with matching groups and
+// patterns), only problems caused by the override are reported (global ones are already there)
+fn validate_override(
+    config: &ConfigStruct,
+    name: &str,
+    o: &ConfigOverride,
+    exact_symbol: Option<&str>,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    let mut effective = config.clone();
+
+    match apply_section_override::<ConfigSymbolMonitor>(&config.symbol_monitor, &o.symbol_monitor) {
+        Ok(section) => effective.symbol_monitor = section,
+        Err((key, reason)) => {
+            issues.push(ConfigIssue::error(
+                &format!("{name}.symbol_monitor.{key}"),
+                reason,
+            ));
+            return;
+        }
+    }
+
+    match apply_section_override::<ConfigOrderBookMonitor>(
+        &config.orderbook_monitor,
+        &o.orderbook_monitor,
+    ) {
+        Ok(section) => effective.orderbook_monitor = section,
+        Err((key, reason)) => {
+            issues.push(ConfigIssue::error(
+                &format!("{name}.orderbook_monitor.{key}"),
+                reason,
+            ));
+            return;
+        }
+    }
+
+    if let Some(symbol) = exact_symbol {
+        effective = config.for_symbol(symbol);
+    }
+
+    let mut global_issues = Vec::new();
+    validate_symbol_monitor(config, &mut global_issues);
+    validate_orderbook_monitor(config, &mut global_issues);
+
+    let mut override_issues = Vec::new();
+    validate_symbol_monitor(&effective, &mut override_issues);
+    validate_orderbook_monitor(&effective, &mut override_issues);
+
+    for issue in override_issues {
+        if global_issues.contains(&issue) {
+            continue;
+        }
+
+        issues.push(ConfigIssue {
+            key: format!("{name} -> {}", issue.key),
+            ..issue
+        });
+    }
+}
+
+fn validate_overrides(config: &ConfigStruct, issues: &mut Vec<ConfigIssue>) {
+    for (symbol, o) in &config.symbols {
+        let exact_symbol = match symbol.contains('*') {
+            true => None,
+            false => Some(symbol.as_str()),
+        };
+        validate_override(
+            config,
+            &format!("symbols.\"{symbol}\""),
+            o,
+            exact_symbol,
+            issues,
+        );
+    }
+
+    for (group, o) in &config.asset_groups {
+        let name = format!("asset_groups.{group}");
+
+        if o.assets.is_empty() {
+            issues.push(ConfigIssue::warning(
+                &format!("{name}.assets"),
+                "no assets listed, this group is never used".to_string(),
+            ));
+        }
+
+        validate_override(config, &name, &o.options, None, issues);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = include_str!("../../config.toml");
+
+    // config.toml with |changes| ("option = value" lines replaced) and |extra| appended
+    fn config_data(changes: &[(&str, &str)], extra: &str) -> String {
+        let mut lines: Vec<String> = CONFIG.lines().map(String::from).collect();
+
+        for (key, value) in changes {
+            let line = lines
+                .iter_mut()
+                .find(|l| l.starts_with(&format!("{key} = ")))
+                .unwrap_or_else(|| panic!("{key} is not in config.toml"));
+            *line = format!("{key} = {value}");
+        }
+
+        format!("{}\n{extra}", lines.join("\n"))
+    }
+
+    fn issues_of(changes: &[(&str, &str)], extra: &str) -> Vec<ConfigIssue> {
+        let config: ConfigStruct = toml::from_str(&config_data(changes, extra)).unwrap();
+        validate_config(&config)
+    }
+
+    fn find_issue<'a>(issues: &'a [ConfigIssue], key: &str) -> Option<&'a ConfigIssue> {
+        issues.iter().find(|i| i.key == key)
+    }
+
+    fn issue_of_key_missing(issues: &[ConfigIssue], key: &str) -> bool {
+        find_issue(issues, key).is_none()
+    }
+
+    #[test]
+    fn shipped_config_has_no_errors() {
+        let issues = issues_of(&[], "");
+
+        assert!(!issues.iter().any(|i| i.is_error()), "{issues:?}");
+    }
+
+    #[test]
+    fn line_number_of_offset() {
+        let data = "a = 1\nb = 2\n\nc = 3";
+
+        assert_eq!(line_number(data, 0), 1);
+        assert_eq!(line_number(data, 4), 1);
+        // the first character of a line belongs to that line
+        assert_eq!(line_number(data, 6), 2);
+        assert_eq!(line_number(data, 13), 4);
+        assert_eq!(line_number(data, 1000), 4);
+    }
+
+    #[test]
+    fn parse_error_reports_line_of_the_option() {
+        let data = config_data(&[("time_limit_secs", "\"300\"")], "");
+        let line = data
+            .lines()
+            .position(|l| l.starts_with("time_limit_secs = "))
+            .unwrap()
+            + 1;
+
+        let issues = parse_config("config.toml", &data).unwrap_err();
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].key, format!("config.toml:{line}"));
+        assert!(issues[0].is_error());
+    }
+
+    #[test]
+    fn removed_options_are_warnings() {
+        let data = config_data(&[], "").replace(
+            "[symbol_monitor]\n",
+            "[symbol_monitor]\nsymbol_price_list_length = 30\nsymbol_price_violatile_check_time_secs = 10\n",
+        );
+
+        let (_, warnings) = parse_config("config.toml", &data).unwrap();
+
+        let issue = find_issue(&warnings, "symbol_price_list_length").unwrap();
+        assert!(issue.reason.contains("window_secs"));
+        let issue = find_issue(&warnings, "symbol_price_violatile_check_time_secs").unwrap();
+        assert!(issue.reason.contains("activity_window_secs"));
+        assert!(issue_of_key_missing(
+            &warnings,
+            "symbol_price_violatile_required_count"
+        ));
+    }
+
+    #[test]
+    fn min_larger_than_max_is_error() {
+        let issues = issues_of(&[("window_price_value_rise_min_max_percent", "[5, 1]")], "");
+
+        let issue = find_issue(&issues, "window_price_value_rise_min_max_percent").unwrap();
+        assert!(issue.is_error());
+
+        let issues = issues_of(&[("window_price_value_rise_min_max_percent", "[1, 1]")], "");
+        assert!(issue_of_key_missing(
+            &issues,
+            "window_price_value_rise_min_max_percent"
+        ));
+    }
+
+    #[test]
+    fn sudden_drop_smaller_than_loss_limit_is_error() {
+        let changes = [
+            ("loss_limit_percent", "2.4"),
+            ("loss_limit_sudden_drop_to_percent", "2.0"),
+        ];
+
+        let issues = issues_of(&changes, "");
+        assert!(find_issue(&issues, "loss_limit_sudden_drop_to_percent")
+            .unwrap()
+            .is_error());
+
+        // only when loss limit is used
+        let issues = issues_of(
+            &[changes[0], changes[1], ("loss_limit_enabled", "false")],
+            "",
+        );
+        assert!(issue_of_key_missing(
+            &issues,
+            "loss_limit_sudden_drop_to_percent"
+        ));
+    }
+
+    #[test]
+    fn overlapping_profit_margins_are_warning() {
+        let key = "good_profit_crossed_allowed_drop_percent";
+
+        // 103 * (1 - 2.5%) = 100.425 < 102
+        let issues = issues_of(
+            &[
+                ("min_profit_percent", "2"),
+                ("good_profit_percent", "3"),
+                (key, "2.5"),
+            ],
+            "",
+        );
+        let issue_found = find_issue(&issues, key).unwrap();
+        assert!(!issue_found.is_error());
+
+        // 103 * (1 - 0.5%) = 102.485 > 102
+        let issues = issues_of(
+            &[
+                ("min_profit_percent", "2"),
+                ("good_profit_percent", "3"),
+                (key, "0.5"),
+            ],
+            "",
+        );
+        assert!(issue_of_key_missing(&issues, key));
+    }
+
+    #[test]
+    fn unknown_override_option_is_error() {
+        let issues = issues_of(
+            &[],
+            "[symbols.XYZUSDT.orderbook_monitor]\nno_such_option = 1\n",
+        );
+
+        let issue = find_issue(
+            &issues,
+            "symbols.\"XYZUSDT\".orderbook_monitor.no_such_option",
+        )
+        .unwrap();
+        assert!(issue.is_error());
+        assert_eq!(issue.reason, "unknown option");
+    }
+
+    #[test]
+    fn invalid_override_value_is_error() {
+        let issues = issues_of(
+            &[],
+            "[asset_groups.meme]\nassets = [\"XYZ\"]\n[asset_groups.meme.orderbook_monitor]\ntime_limit_secs = \"long\"\n",
+        );
+
+        assert!(issues
+            .iter()
+            .any(|i| i.is_error() && i.key.starts_with("asset_groups.meme.orderbook_monitor")));
+    }
+
+    #[test]
+    fn override_breaking_a_rule_is_reported_with_its_name() {
+        let issues = issues_of(
+            &[],
+            "[symbols.XYZUSDT.orderbook_monitor]\nloss_limit_sudden_drop_to_percent = 1\n",
+        );
+
+        let issue = find_issue(
+            &issues,
+            "symbols.\"XYZUSDT\" -> loss_limit_sudden_drop_to_percent",
+        )
+        .unwrap();
+        assert!(issue.is_error());
+        // global config is fine
+        assert!(issue_of_key_missing(
+            &issues,
+            "loss_limit_sudden_drop_to_percent"
+        ));
+    }
+
+    #[test]
+    fn asset_group_without_assets_is_warning() {
+        let issues = issues_of(&[], "[asset_groups.empty]\nassets = []\n");
+
+        assert!(!find_issue(&issues, "asset_groups.empty.assets")
+            .unwrap()
+            .is_error());
+    }
+}
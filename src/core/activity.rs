@@ -0,0 +1,297 @@
+use crate::config::settings::ConfigStruct;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+// single ticker event of the symbol
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivitySample {
+    // event time (ms)
+    pub time: u64,
+    // trades within last 24h (counter from the ticker)
+    pub num_trades: u64,
+    // quote asset volume within last 24h
+    pub quote_volume: Decimal,
+    pub best_bid: Decimal,
+    pub best_bid_qty: Decimal,
+    pub best_ask: Decimal,
+    pub best_ask_qty: Decimal,
+}
+
+/*
+How active the symbol is within last |activity_window_secs| (see config.toml):
+- trades_per_sec: trade count delta per second
+- quote_volume_per_sec: quote asset volume delta per second
+- book_updates_per_sec: how often best bid/ask (price or qty) changes, per second
+- realized_volatility_percent: standard deviation of tick-to-tick percent changes of best bid
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityMetrics {
+    pub trades_per_sec: Decimal,
+    pub quote_volume_per_sec: Decimal,
+    pub book_updates_per_sec: Decimal,
+    pub realized_volatility_percent: Decimal,
+}
+
+// samples of the symbol within the activity window (+ the last one before it as a base)
+#[derive(Debug, Clone, Default)]
+pub struct SymbolActivity {
+    samples: VecDeque<ActivitySample>,
+}
+
+impl SymbolActivity {
+    pub fn add(&mut self, sample: ActivitySample, window_secs: u64) {
+        // stream may deliver events out of order after reconnect - old ones are useless here
+        if let Some(last) = self.samples.back() {
+            if sample.time < last.time {
+                return;
+            }
+        }
+        self.samples.push_back(sample);
+
+        let window_start = self.newest_time().saturating_sub(window_secs * 1000);
+        while self.samples.len() > 2 && self.samples[1].time <= window_start {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn last(&self) -> Option<&ActivitySample> {
+        self.samples.back()
+    }
+
+    fn newest_time(&self) -> u64 {
+        self.samples.back().map(|s| s.time).unwrap_or_default()
+    }
+
+    fn span_ms(&self) -> u64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0,
+        }
+    }
+
+    // metrics are meaningful only once the whole window is covered by samples
+    pub fn is_complete(&self, window_secs: u64) -> bool {
+        window_secs > 0 && self.span_ms() >= window_secs * 1000
+    }
+
+    pub fn metrics(&self) -> ActivityMetrics {
+        let span_ms = self.span_ms();
+        if span_ms == 0 {
+            return ActivityMetrics::default();
+        }
+
+        let first = self.samples.front().unwrap();
+        let last = self.samples.back().unwrap();
+        let secs = Decimal::from(span_ms) / Decimal::ONE_THOUSAND;
+        let per_sec = |v: Decimal| (v / secs).round_dp(4);
+
+        // 24h counters go down when old trades leave the 24h window, that's not activity
+        let trades = last.num_trades.saturating_sub(first.num_trades);
+        let quote_volume = (last.quote_volume - first.quote_volume).max(Decimal::ZERO);
+
+        let pairs = || self.samples.iter().zip(self.samples.iter().skip(1));
+
+        let book_updates = pairs()
+            .filter(|(a, b)| {
+                a.best_bid != b.best_bid
+                    || a.best_ask != b.best_ask
+                    || a.best_bid_qty != b.best_bid_qty
+                    || a.best_ask_qty != b.best_ask_qty
+            })
+            .count();
+
+        let changes: Vec<f64> = pairs()
+            .filter(|(a, _)| !a.best_bid.is_zero())
+            .filter_map(|(a, b)| {
+                ((b.best_bid - a.best_bid) / a.best_bid * Decimal::ONE_HUNDRED).to_f64()
+            })
+            .collect();
+
+        let realized_volatility = match changes.len() {
+            0 => 0.0,
+            n => {
+                let mean = changes.iter().sum::<f64>() / n as f64;
+                let variance = changes.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / n as f64;
+                variance.sqrt()
+            }
+        };
+
+        ActivityMetrics {
+            trades_per_sec: per_sec(Decimal::from(trades)),
+            quote_volume_per_sec: per_sec(quote_volume),
+            book_updates_per_sec: per_sec(Decimal::from(book_updates)),
+            realized_volatility_percent: Decimal::from_f64(realized_volatility)
+                .unwrap_or_default()
+                .round_dp(4),
+        }
+    }
+}
+
+impl ActivityMetrics {
+    // (metric, threshold) pairs - threshold 0 means the metric is not checked
+    fn with_thresholds(&self, config: &ConfigStruct) -> [(Decimal, Decimal); 4] {
+        let sm = &config.symbol_monitor;
+        [
+            (self.trades_per_sec, sm.activity_min_trades_per_sec),
+            (
+                self.quote_volume_per_sec,
+                sm.activity_min_quote_volume_per_sec,
+            ),
+            (
+                self.book_updates_per_sec,
+                sm.activity_min_book_updates_per_sec,
+            ),
+            (
+                self.realized_volatility_percent,
+                sm.activity_min_realized_volatility_percent,
+            ),
+        ]
+    }
+
+    // every enabled threshold has to be reached
+    pub fn thresholds_reached(&self, config: &ConfigStruct) -> bool {
+        self.with_thresholds(config)
+            .iter()
+            .all(|(value, threshold)| value >= threshold)
+    }
+
+    // average of metric/threshold ratios (enabled thresholds only) - 1 means the symbol is exactly
+    // at the thresholds, larger is more active; used to rank candidates
+    pub fn score(&self, config: &ConfigStruct) -> Decimal {
+        let ratios: Vec<Decimal> = self
+            .with_thresholds(config)
+            .iter()
+            .filter(|(_, threshold)| *threshold > Decimal::ZERO)
+            .map(|(value, threshold)| value / threshold)
+            .collect();
+
+        match ratios.len() {
+            0 => Decimal::ONE,
+            n => (ratios.iter().sum::<Decimal>() / Decimal::from(n)).round_dp(4),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn sample(
+        time: u64,
+        num_trades: u64,
+        quote_volume: Decimal,
+        best_bid: Decimal,
+    ) -> ActivitySample {
+        ActivitySample {
+            time,
+            num_trades,
+            quote_volume,
+            best_bid,
+            best_bid_qty: dec!(1),
+            best_ask: best_bid + dec!(0.1),
+            best_ask_qty: dec!(1),
+        }
+    }
+
+    fn config_with_thresholds(thresholds: [Decimal; 4]) -> ConfigStruct {
+        let mut config: ConfigStruct = toml::from_str(include_str!("../../config.toml")).unwrap();
+        let sm = &mut config.symbol_monitor;
+        sm.activity_min_trades_per_sec = thresholds[0];
+        sm.activity_min_quote_volume_per_sec = thresholds[1];
+        sm.activity_min_book_updates_per_sec = thresholds[2];
+        sm.activity_min_realized_volatility_percent = thresholds[3];
+        config
+    }
+
+    // 2 seconds: 10 trades, 30 quote volume, best bid moves once by 1%
+    fn two_secs_activity() -> SymbolActivity {
+        let mut activity = SymbolActivity::default();
+        activity.add(sample(0, 100, dec!(1000), dec!(10)), 10);
+        activity.add(sample(1_000, 104, dec!(1010), dec!(10.1)), 10);
+        activity.add(sample(2_000, 110, dec!(1030), dec!(10.1)), 10);
+        activity
+    }
+
+    #[test]
+    fn metrics_are_per_second_of_covered_span() {
+        let metrics = two_secs_activity().metrics();
+
+        assert_eq!(metrics.trades_per_sec, dec!(5));
+        assert_eq!(metrics.quote_volume_per_sec, dec!(15));
+        assert_eq!(metrics.book_updates_per_sec, dec!(0.5));
+        // tick changes 1% and 0%
+        assert_eq!(metrics.realized_volatility_percent, dec!(0.5));
+    }
+
+    #[test]
+    fn single_sample_has_no_metrics() {
+        let mut activity = SymbolActivity::default();
+        activity.add(sample(0, 100, dec!(1000), dec!(10)), 10);
+
+        assert_eq!(activity.metrics(), ActivityMetrics::default());
+        assert!(!activity.is_complete(10));
+    }
+
+    #[test]
+    fn decreasing_24h_counters_are_not_activity() {
+        let mut activity = SymbolActivity::default();
+        activity.add(sample(0, 100, dec!(1000), dec!(10)), 10);
+        activity.add(sample(1_000, 90, dec!(900), dec!(10)), 10);
+
+        let metrics = activity.metrics();
+
+        assert_eq!(metrics.trades_per_sec, Decimal::ZERO);
+        assert_eq!(metrics.quote_volume_per_sec, Decimal::ZERO);
+    }
+
+    #[test]
+    fn window_keeps_base_sample_and_ignores_old_events() {
+        let mut activity = SymbolActivity::default();
+        for sec in 0..=5 {
+            activity.add(sample(sec * 1000, sec * 10, dec!(0), dec!(10)), 2);
+        }
+        // out of order after reconnect
+        activity.add(sample(1_000, 0, dec!(0), dec!(10)), 2);
+
+        assert!(activity.is_complete(2));
+        assert!(!activity.is_complete(3));
+        assert_eq!(activity.last().unwrap().time, 5_000);
+        // seconds 3..5
+        assert_eq!(activity.metrics().trades_per_sec, dec!(10));
+    }
+
+    #[test]
+    fn zero_threshold_is_not_checked() {
+        let metrics = two_secs_activity().metrics();
+
+        assert!(metrics.thresholds_reached(&config_with_thresholds([Decimal::ZERO; 4])));
+        assert!(metrics.thresholds_reached(&config_with_thresholds([
+            dec!(5),
+            Decimal::ZERO,
+            Decimal::ZERO,
+            dec!(0.5)
+        ])));
+        assert!(!metrics.thresholds_reached(&config_with_thresholds([
+            dec!(5.1),
+            Decimal::ZERO,
+            Decimal::ZERO,
+            Decimal::ZERO
+        ])));
+    }
+
+    #[test]
+    fn score_is_average_ratio_of_enabled_thresholds() {
+        let metrics = two_secs_activity().metrics();
+
+        // 5 / 2.5 and 15 / 10
+        let config = config_with_thresholds([dec!(2.5), dec!(10), Decimal::ZERO, Decimal::ZERO]);
+        assert_eq!(metrics.score(&config), dec!(1.75));
+
+        // nothing to compare with
+        let config = config_with_thresholds([Decimal::ZERO; 4]);
+        assert_eq!(metrics.score(&config), Decimal::ONE);
+    }
+}
@@ -1,10 +1,10 @@
 use crate::binance::prices::process_symbol_qty;
-use crate::config::settings::ConfigStruct;
 use crate::core::trading::TradingSymbol;
 use log::info;
 pub use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
+/// Percent change from `base` to `new` (negative for a drop).
 pub fn percent_diff(base: Decimal, new: Decimal) -> Decimal {
     let one_hundred = Decimal::ONE_HUNDRED;
 
@@ -13,24 +13,38 @@ pub fn percent_diff(base: Decimal, new: Decimal) -> Decimal {
     (difference * one_hundred) / base
 }
 
-pub fn calculate_exit_qty(config: &ConfigStruct, trading_symbol: &TradingSymbol) -> Option<Decimal> {
-    let comission = config.orderbook_monitor.exchange_comission / Decimal::ONE_HUNDRED;
-
-    let my_current_qty = trading_symbol.qty;
-    let exit_qty_tmp = my_current_qty - (my_current_qty * comission);
+/// Qty to sell when leaving the position: held qty (fees are already taken, see Position) adjusted to
+/// LOT_SIZE filter, the rest is dust. None if it doesn't pass the filters.
+pub fn calculate_exit_qty(trading_symbol: &TradingSymbol) -> Option<Decimal> {
+    let held_qty = trading_symbol.qty;
 
     let symbol = trading_symbol.symbol.clone();
 
-    let exit_qty_res = process_symbol_qty(symbol.clone(), exit_qty_tmp, &trading_symbol.filters_map);
+    let exit_qty_res = process_symbol_qty(symbol.clone(), held_qty, &trading_symbol.filters_map);
 
     if let Some(exit_qty) = exit_qty_res {
-        info!("{symbol} calculate exit qty, initial: {my_current_qty}, initial-comission: {exit_qty_tmp}, final exit qty: {exit_qty}");
+        info!("{symbol} calculate exit qty, held: {held_qty}, final exit qty: {exit_qty}");
         return Some(exit_qty);
     }
 
     None
 }
 
+/// Held qty can be sold at |price|: exit qty passes LOT_SIZE and its value is at least MIN_NOTIONAL.
+pub fn can_exit_position(trading_symbol: &TradingSymbol, price: Decimal) -> bool {
+    let min_notional = trading_symbol
+        .filters_map
+        .get(&format!("{}-min-notional-min", trading_symbol.symbol))
+        .copied()
+        .unwrap_or_default();
+
+    match calculate_exit_qty(trading_symbol) {
+        Some(exit_qty) => exit_qty > Decimal::ZERO && exit_qty * price >= min_notional,
+        None => false,
+    }
+}
+
+/// Percent change between the first and the last price of the window.
 pub fn percentage_change_between_first_and_last_element(list: &&[Decimal]) -> Decimal {
     let before_last = list[0];
     let last = list[list.len() - 1];
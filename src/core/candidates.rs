@@ -0,0 +1,117 @@
+use crate::config::settings::ConfigStruct;
+use crate::core::structs::TradeCandidate;
+use crate::core::types::Symbol;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use tokio::time::Instant;
+
+/*
+Symbols sent by symbols monitor wait here for a free pool. When a pool is free the engine takes the
+best ranked candidate (not the first one which came), candidates which were not refreshed by symbols
+monitor within |candidate_max_age_secs| are dropped - their signal is not valid anymore.
+
+score = |window percent change| * |candidate_score_window_weight|
+        + activity score * |candidate_score_activity_weight|
+        - spread percent * |candidate_score_spread_weight|
+ */
+pub fn candidate_score(config: &ConfigStruct, candidate: &TradeCandidate) -> Decimal {
+    let signal = &candidate.entry_signal;
+
+    (signal.window_percent_changes.window.abs() * config.candidate_score_window_weight
+        + signal.activity_score * config.candidate_score_activity_weight
+        - candidate.spread_percent * config.candidate_score_spread_weight)
+        .round_dp(4)
+}
+
+#[derive(Debug, Clone)]
+pub struct QueuedCandidate {
+    pub candidate: TradeCandidate,
+    pub score: Decimal,
+    // last time symbols monitor sent this symbol
+    pub refreshed: Instant,
+}
+
+#[derive(Debug, Default)]
+pub struct CandidateQueue {
+    candidates: HashMap<Symbol, QueuedCandidate>,
+}
+
+impl CandidateQueue {
+    // adds the candidate or refreshes it (newer price and signal), true if it was not queued yet
+    pub fn push(&mut self, candidate: TradeCandidate, score: Decimal) -> bool {
+        let symbol = candidate.symbol.clone();
+
+        self.candidates
+            .insert(
+                symbol,
+                QueuedCandidate {
+                    candidate,
+                    score,
+                    refreshed: Instant::now(),
+                },
+            )
+            .is_none()
+    }
+
+    // removes candidates older than |max_age_secs| and returns them
+    pub fn remove_expired(&mut self, max_age_secs: u64) -> Vec<QueuedCandidate> {
+        let expired: Vec<Symbol> = self
+            .candidates
+            .iter()
+            .filter(|(_, c)| c.refreshed.elapsed().as_secs() >= max_age_secs)
+            .map(|(s, _)| s.clone())
+            .collect();
+
+        expired
+            .iter()
+            .filter_map(|s| self.candidates.remove(s))
+            .collect()
+    }
+
+    pub fn remove(&mut self, symbol: &Symbol) -> Option<QueuedCandidate> {
+        self.candidates.remove(symbol)
+    }
+
+    // the best ranked candidate (the freshest one if scores are equal)
+    pub fn pop_best(&mut self) -> Option<QueuedCandidate> {
+        let best = self
+            .candidates
+            .iter()
+            .max_by(|(_, a), (_, b)| {
+                a.score
+                    .cmp(&b.score)
+                    .then_with(|| a.refreshed.cmp(&b.refreshed))
+            })
+            .map(|(s, _)| s.clone())?;
+
+        self.candidates.remove(&best)
+    }
+
+    // symbols with scores, the best first
+    pub fn ranking(&self) -> Vec<(Symbol, Decimal)> {
+        let mut ranking: Vec<(Symbol, Decimal)> = self
+            .candidates
+            .iter()
+            .map(|(s, c)| (s.clone(), c.score))
+            .collect();
+        ranking.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+
+        ranking
+    }
+
+    // removes all candidates and returns how many there were
+    pub fn clear(&mut self) -> usize {
+        let count = self.candidates.len();
+        self.candidates.clear();
+
+        count
+    }
+
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+}
@@ -1,22 +1,144 @@
+use crate::config::settings::CONFIG_FILENAME;
 use crate::core::types::TradingMode;
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+
+pub const LOG_CONFIG_FILENAME: &str = "log4rs.yaml";
 
 #[derive(Parser, Debug)]
-#[command(author, long_about = None)]
-struct CliArgs {
-    /// Perform real trading actions on the exchange (be careful!)
-    #[arg(long)]
-    real_trading_actions: bool,
+#[command(author, version, long_about = None)]
+pub struct CliArgs {
+    /// Config file
+    #[arg(long, global = true, default_value = CONFIG_FILENAME)]
+    pub config: String,
+
+    /// Logging config file (log4rs)
+    #[arg(long, global = true, default_value = LOG_CONFIG_FILENAME)]
+    pub log_config: String,
+
+    /// Directory for trade history (relative |trade_history_file|), recordings and backtest results
+    #[arg(long, global = true, default_value = ".")]
+    pub data_dir: String,
+
+    // no subcommand starts the bot in simulation mode, like it always did - see command()
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
 }
 
-pub fn determine_bot_trading_mode() -> TradingMode {
-    let mut trading_mode = TradingMode::Simulation;
+impl CliArgs {
+    pub fn command(&self) -> CliCommand {
+        self.command.clone().unwrap_or(CliCommand::Simulate)
+    }
+}
 
-    let args = CliArgs::parse();
+// trading mode of "run" subcommand
+#[derive(ValueEnum, Debug, Clone, PartialEq, Eq)]
+pub enum RunMode {
+    /// No real orders are placed, trades are only simulated
+    Simulation,
+    /// Real orders on the exchange (be careful!)
+    Real,
+}
 
-    if args.real_trading_actions {
-        trading_mode = TradingMode::RealTrading
+impl From<RunMode> for TradingMode {
+    fn from(mode: RunMode) -> Self {
+        match mode {
+            RunMode::Simulation => TradingMode::Simulation,
+            RunMode::Real => TradingMode::RealTrading,
+        }
     }
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CliCommand {
+    /// Start the bot
+    Run {
+        /// Trading mode
+        #[arg(long, value_enum)]
+        mode: RunMode,
+
+        /// Confirm real trading on Binance production (required with --mode real there)
+        #[arg(long)]
+        confirm_production: bool,
+    },
+
+    /// Start the bot in simulation mode (same as "run --mode simulation"), default without a subcommand
+    Simulate,
+
+    /// Record market data (all symbols tickers) to a file in data dir for backtesting
+    Record {
+        /// Stop recording after this many seconds (records until killed if not set)
+        #[arg(long)]
+        duration_secs: Option<u64>,
+
+        /// Record only these symbols (comma separated), all tradable symbols if not set
+        #[arg(long, value_delimiter = ',')]
+        symbols: Vec<String>,
+    },
+
+    /// Replay recorded market data through the bot in simulation mode and print the report
+    Backtest {
+        /// Recording made by "record" subcommand
+        #[arg(long)]
+        recording: String,
+
+        /// Address of the mock server the recording is replayed by
+        #[arg(long, default_value = "127.0.0.1:9901")]
+        listen: String,
+
+        /// How long the bot keeps running after the recording ends (open trades can finish)
+        #[arg(long, default_value_t = 60)]
+        finish_secs: u64,
+    },
+
+    /// Aggregate trade history (|trade_history_file|) by symbol, hour of day and exit reason
+    Report {
+        /// Print the report as JSON instead of tables
+        #[arg(long)]
+        json: bool,
+
+        /// Export all trade records to this CSV file
+        #[arg(long)]
+        export_csv: Option<String>,
+
+        /// Export all trade records to this JSON file
+        #[arg(long)]
+        export_json: Option<String>,
+    },
+
+    /// Validate config file and print every problem found (exit code 1 on errors)
+    CheckConfig,
+
+    /// List symbols from exchangeInfo - which of them the bot would trade (and why not), filters
+    Symbols {
+        /// Print the list as JSON instead of a table
+        #[arg(long)]
+        json: bool,
+
+        /// Show only symbols with this asset (base or quote)
+        #[arg(long)]
+        asset: Option<String>,
+    },
+
+    /// Start local mock of Binance REST API and websocket streams driven by scenario file
+    MockServer {
+        /// Scenario file (JSON), see doc/MockServer.md
+        #[arg(long)]
+        scenario: String,
+
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:9900")]
+        listen: String,
+    },
+}
+
+pub fn parse_cli_args() -> CliArgs {
+    CliArgs::parse()
+}
 
-    trading_mode
+// paths relative to data dir, absolute paths are left as they are
+pub fn data_dir_path(data_dir: &str, path: &str) -> String {
+    std::path::Path::new(data_dir)
+        .join(path)
+        .to_string_lossy()
+        .to_string()
 }
@@ -1,12 +1,15 @@
 use crate::binance::orderbook::orderbook_executor;
-use crate::config::settings::{ConfigStruct, CONFIG_FILENAME};
+use crate::config::reload::{SharedConfig, SymbolConfigs};
 use crate::core::calc::percent_diff;
-use crate::core::structs::OrderBookCommand;
+use crate::core::candidates::{candidate_score, CandidateQueue};
+use crate::core::journal::{append_trade_record, TradeRecord};
+use crate::core::structs::{OrderBookCommand, SharedStreamHealth, TradeCandidate};
 use crate::core::trading::TradingSymbol;
 use crate::core::types::{KlineSignal, OrderBookCmd, Symbol, SymbolAction, TradingMode};
-use log::{info, warn};
+use log::{error, info, warn};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::{Decimal, RoundingStrategy};
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::fs;
 use std::ops::Not;
@@ -17,20 +20,21 @@ use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::task;
 use tokio::time::Instant;
 
-/*
-Takes symbols from symbol monitor and starts threads with trading tasks
- */
-
+/// Takes symbols from symbol monitor and starts threads with trading tasks.
 pub async fn engine(
-    config: ConfigStruct,
+    shared_config: SharedConfig,
     filters_map: Arc<RwLock<HashMap<String, Decimal>>>,
     symbol_actions: Arc<RwLock<HashMap<Symbol, SymbolAction>>>,
-    mut channel_from_monitor: UnboundedReceiver<(Symbol, Decimal)>,
+    mut channel_from_monitor: UnboundedReceiver<TradeCandidate>,
+    symbols_monitor_health: SharedStreamHealth,
     api_keys: (String, String),
     trading_mode: TradingMode,
 ) {
     info!("engine started");
 
+    // options used only here at start are not hot-reloadable (see RESTART_REQUIRED_KEYS)
+    let config = shared_config.read().unwrap().clone();
+
     if trading_mode == TradingMode::Simulation {
         warn!("!!! !!! !!! !!! !!! !!! !!! !!! !!! !!! !!! !!! !!! !!! !!! !! !!! !!! !!!");
         warn!("!!! !!! !!! !!! !!! !!! !!! !!! !!! !!! !!! !!! !!! !!! !!! !! !!! !!! !!!");
@@ -48,6 +52,8 @@ pub async fn engine(
     let mut symbols_trades_recently_msg: HashMap<Symbol, bool> = HashMap::new();
     let mut previous_cmd_read_time = Instant::now();
     let mut stop_accepting_symbols = false;
+    let mut candidate_queue = CandidateQueue::default();
+    let mut symbols_monitor_was_healthy = false;
 
     // channel
     #[allow(clippy::type_complexity)]
@@ -74,9 +80,14 @@ pub async fn engine(
     let initial_pool_value: Decimal = available_pools_list.clone().iter().sum();
     let initial_pool_length = Decimal::from(available_pools_list.len());
     let decimal_zero = Decimal::ZERO;
-    let mut c = config;
+    // symbol overrides are resolved again only when config is reloaded
+    let mut symbol_configs = SymbolConfigs::new(&shared_config);
+    let mut c;
     loop {
-        if previous_cmd_read_time.elapsed().as_secs() >= c.clone().cmd_read_period_secs {
+        // config is swapped as a whole on reload - only its Arc is taken here, not a copy
+        symbol_configs.refresh(&shared_config);
+        c = symbol_configs.global();
+        if previous_cmd_read_time.elapsed().as_secs() >= c.cmd_read_period_secs {
             previous_cmd_read_time = Instant::now();
             // read cmd for instant sell
             if Path::new(&cmd_instant_sell_file).exists() {
@@ -98,24 +109,30 @@ pub async fn engine(
                     println!("problem with removing file: {cmd_instant_sell_file}");
                 }
             }
+
+            // symbols monitor health requested
+            if !c.cmd_symbols_monitor_health.is_empty() {
+                let cmd_health_file =
+                    format!("{}/{}", config.cmd_dir, c.cmd_symbols_monitor_health);
+                if Path::new(&cmd_health_file).exists() {
+                    answer_health_cmd(&cmd_health_file, &symbols_monitor_health);
+                }
+            }
         }
 
         if currently_trading_reminder_time.elapsed().as_secs()
-            >= c.clone()
-                .orderbook_monitor
-                .currently_trading_reminder_period_secs
+            >= c.orderbook_monitor.currently_trading_reminder_period_secs
         {
-            let config_data =
-                fs::read_to_string(CONFIG_FILENAME).expect("Cannot read config file {}");
-            let config_new: ConfigStruct = toml::from_str(config_data.as_str()).unwrap();
-            c = config_new;
-
             let map_copy = symbols_already_processing.clone();
             let keys = map_copy.keys();
             let list_to_sum = available_pools_list.clone();
             let list_sum: Decimal = list_to_sum.iter().sum();
             info!("---");
             info!("STATUS: currently trading {currently_trading_pairs} pairs => {keys:?}");
+            info!(
+                "STATUS: symbols monitor stream: {:?}",
+                symbols_monitor_health.read().unwrap()
+            );
             info!(
                 "STATUS: currently available base pools: {:?}",
                 available_pools_list.clone()
@@ -138,7 +155,7 @@ pub async fn engine(
                 }
             }
 
-            if c.clone().orderbook_monitor.use_profits_to_trade.not() {
+            if c.orderbook_monitor.use_profits_to_trade.not() {
                 let profits: Decimal = profits_list.iter().sum();
                 info!(
                     "STATUS: profits so far (use_profits_to_trade=false): ===> {profits} USDT <==="
@@ -147,25 +164,25 @@ pub async fn engine(
             info!("---");
             currently_trading_reminder_time = Instant::now();
         }
-        let cfg = c.clone();
         //
         // BEGIN: symbols finished trading
         //
         if let Ok(msg) = executor_signal_receiver.try_recv() {
             let trading_symbol = msg;
 
-            let symbol = trading_symbol.symbol;
+            let symbol = trading_symbol.symbol.clone();
             let received_qty = trading_symbol.qty;
             let used_qty = trading_symbol.used_qty;
             let sum_qty =
                 (received_qty - used_qty).round_dp_with_strategy(2, RoundingStrategy::ToZero);
 
             info!(
-                "[from orderbook executor]: finished trading: {}, qty: {}, used: {}, profit: {}",
+                "[from orderbook executor]: finished trading: {}, qty: {}, used: {}, profit: {}, exit reason: {}",
                 symbol.clone(),
                 received_qty,
                 used_qty,
-                sum_qty
+                sum_qty,
+                trading_symbol.exit_reason
             );
 
             // received_qty == decimal_zero means LIMIT SELL ORDER is left - no profit now, but
@@ -175,6 +192,14 @@ pub async fn engine(
                 let profit =
                     (received_qty - used_qty).round_dp_with_strategy(2, RoundingStrategy::ToZero);
                 profits_list.push(profit);
+
+                if trading_symbol.exit_reason.entered() {
+                    let record = TradeRecord::new(&trading_symbol);
+
+                    if let Err(e) = append_trade_record(&c.trade_history_path(), &record) {
+                        error!("{symbol}: cannot write trade record to the history file: {e}");
+                    }
+                }
             }
 
             // symbol is returned so get back to the pool
@@ -192,13 +217,12 @@ pub async fn engine(
         }
 
         //
-        // BEGIN: RECEIVING FROM CHANNEL: symbol to trade
+        // BEGIN: RECEIVING FROM CHANNEL: symbols to trade go to candidate queue
         //
-        if let Ok(msg) = channel_from_monitor.try_recv() {
+        while let Ok(msg) = channel_from_monitor.try_recv() {
             // symbol received
-            let price = msg.1;
-            let symbol_string = msg.0.to_string();
-            let symbol = msg.0;
+            let symbol_string = msg.symbol.to_string();
+            let symbol = msg.symbol.clone();
 
             let mut symbol_is_allowed_to_trade_now = true;
 
@@ -213,15 +237,16 @@ pub async fn engine(
                 let traded_time_ago_sec = *symbols_traded_recently.get(&symbol.clone()).unwrap();
 
                 if traded_time_ago_sec.elapsed().as_secs()
-                    < c.clone()
+                    < symbol_configs
+                        .for_symbol(&symbol_string)
                         .orderbook_monitor
                         .break_between_trading_same_symbol_secs
                 {
                     symbol_is_allowed_to_trade_now = false;
 
-                    if !symbols_trades_recently_msg.contains_key(&symbol.clone()) {
+                    if let Entry::Vacant(e) = symbols_trades_recently_msg.entry(symbol.clone()) {
                         warn!("{symbol} REJECTED: delay between past and next trading for this symbol is still in force.");
-                        symbols_trades_recently_msg.insert(symbol.clone(), true);
+                        e.insert(true);
                     }
                 } else {
                     // remove
@@ -230,77 +255,149 @@ pub async fn engine(
                 }
             }
 
-            if available_pools_list.is_empty() {
-                warn!(
-                    "currently trading {}/{} pairs, so {} is REJECTED for now",
-                    currently_trading_pairs, cfg.max_simultaneously_trading_pairs, symbol_string
-                );
-                symbol_is_allowed_to_trade_now = false;
-            }
-
             if symbol_is_allowed_to_trade_now {
-                info!("[from symbol_monitor]: symbol: {symbol}, price: {price} - TRADING");
-                let task_map = Arc::clone(&tasks);
+                let score = candidate_score(&c, &msg);
 
-                // channel: => executor
-                let (signal_sender, _signal_receiver): (
-                    Sender<KlineSignal>,
-                    Receiver<KlineSignal>,
-                ) = mpsc::channel();
+                if candidate_queue.push(msg, score) && available_pools_list.is_empty() {
+                    info!(
+                        "currently trading {}/{} pairs, so {} (score: {score}) is QUEUED for now",
+                        currently_trading_pairs, c.max_simultaneously_trading_pairs, symbol_string
+                    );
+                }
+            }
+        }
 
-                let map_filters;
-                let map_symbols;
-                {
-                    map_filters = filters_map.read().unwrap().clone();
-                    map_symbols = symbol_actions.read().unwrap().clone();
+        for expired in candidate_queue.remove_expired(c.candidate_max_age_secs) {
+            info!(
+                "{}: candidate EXPIRED (score: {}), symbol monitor doesn't send it anymore",
+                expired.candidate.symbol, expired.score
+            );
+        }
+        //
+        // END: RECEIVING FROM CHANNEL: symbols to trade go to candidate queue
+        //
+
+        //
+        // BEGIN: symbols monitor health - no new trades while its stream is down or stale
+        //
+        let symbols_monitor_healthy = symbols_monitor_health.read().unwrap().healthy();
+        if symbols_monitor_healthy != symbols_monitor_was_healthy {
+            match symbols_monitor_healthy {
+                true => info!("symbols monitor stream is up - new trades are allowed"),
+                false => {
+                    warn!("symbols monitor stream is down or stale - no new trades until it's back")
                 }
-                let s = symbol.clone();
-                let trading_mode = trading_mode.clone();
-                let executor_ch = executor_signal_out.clone();
-
-                // take something from pool and remove
-                let trading_symbol_qty_pool = available_pools_list.remove(0);
-                let api_keys_tuple = api_keys.clone();
-
-                // channel for orderbook
-                #[allow(clippy::type_complexity)]
-                let (orderbook_sender, orderbook_receiver): (
-                    Sender<OrderBookCommand>,
-                    Receiver<OrderBookCommand>,
-                ) = mpsc::channel();
-
-                driving_channels_map.insert(s.clone(), orderbook_sender);
-
-                task::spawn_blocking(move || {
-                    orderbook_executor(
-                        cfg.clone(),
-                        s,
-                        map_filters,
-                        map_symbols,
-                        trading_symbol_qty_pool,
-                        price,
-                        orderbook_receiver,
-                        executor_ch,
-                        api_keys_tuple,
-                        trading_mode,
-                    );
-                });
+            }
+            symbols_monitor_was_healthy = symbols_monitor_healthy;
+        }
 
-                // workaround for tokio::spawn to spawn previous task always (!)
-                task::spawn(async move {});
+        if !symbols_monitor_healthy && !candidate_queue.is_empty() {
+            // signals came before the stream gap
+            let dropped = candidate_queue.clear();
+            info!("{dropped} queued candidates dropped - symbols monitor stream is not healthy");
+        }
+        //
+        // END: symbols monitor health
+        //
 
-                task_map
-                    .lock()
-                    .unwrap()
-                    .insert(symbol.clone(), signal_sender);
+        //
+        // BEGIN: the best ranked candidate gets free pool
+        //
+        if !available_pools_list.is_empty()
+            && !candidate_queue.is_empty()
+            && !stop_accepting_symbols
+            && symbols_monitor_healthy
+        {
+            let ranking = candidate_queue.ranking();
+            let queued = candidate_queue.pop_best().unwrap();
+            let msg = queued.candidate;
+            let price = msg.price;
+            let symbol = msg.symbol.clone();
 
-                // increment list of trading pairs
-                currently_trading_pairs += 1;
-                symbols_already_processing.insert(symbol.clone(), true);
+            info!(
+                "[from symbol_monitor]: symbol: {symbol}, price: {price}, signal: {}, score: {} - TRADING",
+                msg.entry_signal.decision, queued.score
+            );
+            if ranking.len() > 1 {
+                info!("candidates ranking: {ranking:?}");
             }
+            let task_map = Arc::clone(&tasks);
+
+            // channel: => executor
+            let (signal_sender, _signal_receiver): (Sender<KlineSignal>, Receiver<KlineSignal>) =
+                mpsc::channel();
+
+            let map_filters;
+            let map_symbols;
+            {
+                map_filters = filters_map.read().unwrap().clone();
+                map_symbols = symbol_actions.read().unwrap().clone();
+            }
+            let s = symbol.clone();
+            let trading_mode = trading_mode.clone();
+            let executor_ch = executor_signal_out.clone();
+            let executor_config = shared_config.clone();
+
+            // take something from pool and remove
+            let trading_symbol_qty_pool = available_pools_list.remove(0);
+            let api_keys_tuple = api_keys.clone();
+
+            // channel for orderbook
+            #[allow(clippy::type_complexity)]
+            let (orderbook_sender, orderbook_receiver): (
+                Sender<OrderBookCommand>,
+                Receiver<OrderBookCommand>,
+            ) = mpsc::channel();
+
+            driving_channels_map.insert(s.clone(), orderbook_sender);
+
+            task::spawn_blocking(move || {
+                orderbook_executor(
+                    executor_config,
+                    msg,
+                    map_filters,
+                    map_symbols,
+                    trading_symbol_qty_pool,
+                    orderbook_receiver,
+                    executor_ch,
+                    api_keys_tuple,
+                    trading_mode,
+                );
+            });
+
+            // workaround for tokio::spawn to spawn previous task always (!)
+            task::spawn(async move {});
+
+            task_map
+                .lock()
+                .unwrap()
+                .insert(symbol.clone(), signal_sender);
+
+            // increment list of trading pairs
+            currently_trading_pairs += 1;
+            symbols_already_processing.insert(symbol.clone(), true);
         }
         //
-        // END: RECEIVING FROM CHANNEL: symbol to trade
+        // END: the best ranked candidate gets free pool
         //
     }
 }
+
+// writes symbols monitor health to "<cmd file>.json" and removes the cmd file
+fn answer_health_cmd(cmd_file: &str, symbols_monitor_health: &SharedStreamHealth) {
+    let mut health = symbols_monitor_health.read().unwrap().clone();
+    health.refresh_age();
+
+    let health_file = format!("{cmd_file}.json");
+    match serde_json::to_string_pretty(&health) {
+        Ok(json) => match fs::write(&health_file, json) {
+            Ok(_) => info!("symbols monitor health written to {health_file}"),
+            Err(e) => error!("cannot write symbols monitor health to {health_file}: {e}"),
+        },
+        Err(e) => error!("cannot serialize symbols monitor health: {e}"),
+    }
+
+    if let Err(e) = fs::remove_file(cmd_file) {
+        error!("problem with removing file: {cmd_file}: {e}");
+    }
+}
@@ -0,0 +1,191 @@
+use crate::core::activity::ActivityMetrics;
+use crate::core::calc::percent_diff;
+use crate::core::structs::WindowPercentChanges;
+use crate::core::trading::TradingSymbol;
+use crate::core::types::{ExitReason, SendToTradeDecision};
+use chrono::{SecondsFormat, Utc};
+use log::warn;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+pub const TRADE_RECORD_CSV_HEADER: &str = "symbol,entry_time,exit_time,monitored_price,entry_price,\
+exit_price,qty,used_qty,received_qty,profit,profit_percent,fees,exit_reason,peak_profit_percent,entry_decision,\
+pre_window_percent_change,window_percent_change,post_window_percent_change,trades_per_sec,quote_volume_per_sec,book_updates_per_sec,\
+realized_volatility_percent,activity_score,net_profit,net_profit_percent,dust_qty";
+
+/*
+Complete record of a single finished trade - one record is appended to |trade_history_file| (JSON
+per line) by the engine once orderbook executor returns the symbol.
+
+Prices are in quote asset ("monitored_price" is the one sent by symbols monitor), "used_qty" is
+what we spent from the pool, "received_qty" is what we got back after the sell, so
+profit = received_qty - used_qty (fees in traded assets are already there).
+
+"fees" are all fees charged by the exchange valued in quote asset ("fee_assets" as they were charged),
+"net_profit" is profit after fees paid in other assets too (BNB valued by its price at the exit),
+"dust_qty" is base qty left unsold (below LOT_SIZE).
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeRecord {
+    pub symbol: String,
+    pub entry_time: String,
+    pub exit_time: String,
+    pub monitored_price: Decimal,
+    pub entry_price: Decimal,
+    pub exit_price: Decimal,
+    pub qty: Decimal,
+    pub used_qty: Decimal,
+    pub received_qty: Decimal,
+    pub profit: Decimal,
+    pub profit_percent: Decimal,
+    pub fees: Decimal,
+    pub exit_reason: ExitReason,
+    pub peak_profit_percent: Decimal,
+    pub entry_decision: SendToTradeDecision,
+    pub window_percent_changes: WindowPercentChanges,
+    #[serde(default)]
+    pub activity: ActivityMetrics,
+    #[serde(default)]
+    pub activity_score: Decimal,
+    #[serde(default)]
+    pub fee_assets: BTreeMap<String, Decimal>,
+    // None in records written before fees were read from fills
+    #[serde(default)]
+    pub net_profit: Option<Decimal>,
+    #[serde(default)]
+    pub net_profit_percent: Option<Decimal>,
+    #[serde(default)]
+    pub dust_qty: Decimal,
+}
+
+impl TradeRecord {
+    pub fn new(trading_symbol: &TradingSymbol) -> Self {
+        // after the sell "qty" field holds what we received back
+        let received_qty = trading_symbol.qty;
+        let used_qty = trading_symbol.used_qty;
+        let position = &trading_symbol.position;
+        let net_profit = position.net_profit();
+
+        Self {
+            symbol: trading_symbol.symbol.to_string(),
+            entry_time: trading_symbol
+                .trading_started_at
+                .to_rfc3339_opts(SecondsFormat::Secs, true),
+            exit_time: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            monitored_price: trading_symbol.monitored_price,
+            entry_price: trading_symbol.entry_price,
+            exit_price: trading_symbol.price,
+            qty: trading_symbol.entry_qty,
+            used_qty,
+            received_qty,
+            profit: received_qty - used_qty,
+            profit_percent: percent_diff(used_qty, received_qty)
+                .round_dp_with_strategy(2, RoundingStrategy::ToZero),
+            fees: position.fees_in_quote(),
+            exit_reason: trading_symbol.exit_reason.clone(),
+            peak_profit_percent: trading_symbol.peak_profit_percent,
+            entry_decision: trading_symbol.entry_signal.decision.clone(),
+            window_percent_changes: trading_symbol.entry_signal.window_percent_changes.clone(),
+            activity: trading_symbol.entry_signal.activity.clone(),
+            activity_score: trading_symbol.entry_signal.activity_score,
+            fee_assets: position.fees.clone(),
+            net_profit: Some(net_profit),
+            net_profit_percent: Some(
+                percent_diff(used_qty, used_qty + net_profit)
+                    .round_dp_with_strategy(2, RoundingStrategy::ToZero),
+            ),
+            dust_qty: position.dust_qty,
+        }
+    }
+
+    pub fn to_csv_line(&self) -> String {
+        let optional = |v: Option<Decimal>| v.map(|d| d.to_string()).unwrap_or_default();
+
+        [
+            self.symbol.clone(),
+            self.entry_time.clone(),
+            self.exit_time.clone(),
+            self.monitored_price.to_string(),
+            self.entry_price.to_string(),
+            self.exit_price.to_string(),
+            self.qty.to_string(),
+            self.used_qty.to_string(),
+            self.received_qty.to_string(),
+            self.profit.to_string(),
+            self.profit_percent.to_string(),
+            self.fees.to_string(),
+            self.exit_reason.to_string(),
+            self.peak_profit_percent.to_string(),
+            self.entry_decision.to_string(),
+            optional(self.window_percent_changes.pre_window),
+            self.window_percent_changes.window.to_string(),
+            optional(self.window_percent_changes.post_window),
+            self.activity.trades_per_sec.to_string(),
+            self.activity.quote_volume_per_sec.to_string(),
+            self.activity.book_updates_per_sec.to_string(),
+            self.activity.realized_volatility_percent.to_string(),
+            self.activity_score.to_string(),
+            optional(self.net_profit),
+            optional(self.net_profit_percent),
+            self.dust_qty.to_string(),
+        ]
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+// RFC 4180 - field with a separator, quote or line break is quoted, quotes inside are doubled
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+pub fn append_trade_record(path: &str, record: &TradeRecord) -> std::io::Result<()> {
+    let line = serde_json::to_string(record)?;
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{line}")
+}
+
+// reads whole trade history, broken lines (for instance after a crash while writing) are skipped
+pub fn read_trade_records(path: &str) -> std::io::Result<Vec<TradeRecord>> {
+    let data = fs::read_to_string(path)?;
+    let mut records = Vec::new();
+
+    for (n, line) in data.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str::<TradeRecord>(line) {
+            Ok(record) => records.push(record),
+            Err(e) => warn!("{path}:{}: skipping invalid trade record: {e}", n + 1),
+        }
+    }
+
+    Ok(records)
+}
+
+pub fn export_trade_records_csv(path: &str, records: &[TradeRecord]) -> std::io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    writeln!(file, "{TRADE_RECORD_CSV_HEADER}")?;
+
+    for record in records {
+        writeln!(file, "{}", record.to_csv_line())?;
+    }
+
+    Ok(())
+}
+
+pub fn export_trade_records_json(path: &str, records: &[TradeRecord]) -> std::io::Result<()> {
+    let data = serde_json::to_string_pretty(records)?;
+    fs::write(path, data)
+}
@@ -0,0 +1,210 @@
+use crate::config::settings::ConfigStruct;
+use crate::core::calc::percent_diff;
+use crate::core::order_book::{BookSide, LocalOrderBook};
+use rust_decimal::Decimal;
+
+// liquidity of the book at one update
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Liquidity {
+    // (best ask - best bid) / best bid in percent
+    pub spread_percent: Decimal,
+    // quote asset value of levels within |liquidity_depth_range_percent| from the mid price
+    pub bid_depth: Decimal,
+    pub ask_depth: Decimal,
+    // how much worse than the best bid is the average price of selling our qty to bids
+    pub exit_slippage_percent: Decimal,
+}
+
+/*
+Pre-entry liquidity check - before the trade is started the book has to:
+- have spread within |liquidity_max_spread_percent|
+- have at least |liquidity_min_depth| (quote asset) on both sides within |liquidity_depth_range_percent|
+  from the mid price
+- be able to absorb our exit qty (bought for |quote_amount|) by bids with slippage within
+  |liquidity_max_exit_slippage_percent|
+Threshold 0 means the check is not done (older config files without these options).
+Err contains the reason why the book is not liquid enough.
+ */
+pub fn check_liquidity(
+    config: &ConfigStruct,
+    book: &LocalOrderBook,
+    quote_amount: Decimal,
+) -> Result<Liquidity, String> {
+    let om = &config.orderbook_monitor;
+
+    let (best_bid, best_ask) = match (book.best_bid(), book.best_ask()) {
+        (Some((bid, _)), Some((ask, _))) if !bid.is_zero() => (bid, ask),
+        _ => return Err("empty book".to_string()),
+    };
+
+    let spread_percent = percent_diff(best_bid, best_ask).round_dp(4);
+    if !om.liquidity_max_spread_percent.is_zero()
+        && spread_percent > om.liquidity_max_spread_percent
+    {
+        return Err(format!(
+            "spread {spread_percent}% > {}%",
+            om.liquidity_max_spread_percent
+        ));
+    }
+
+    let mid = (best_bid + best_ask) / Decimal::TWO;
+    let range = mid * om.liquidity_depth_range_percent / Decimal::ONE_HUNDRED;
+    let depth_value = |side: BookSide, limit: Decimal| -> Decimal {
+        book.levels(side)
+            .take_while(|(price, _)| match side {
+                BookSide::Bids => *price >= limit,
+                BookSide::Asks => *price <= limit,
+            })
+            .map(|(price, qty)| price * qty)
+            .sum::<Decimal>()
+            .round_dp(4)
+    };
+    let bid_depth = depth_value(BookSide::Bids, mid - range);
+    let ask_depth = depth_value(BookSide::Asks, mid + range);
+
+    if bid_depth < om.liquidity_min_depth || ask_depth < om.liquidity_min_depth {
+        return Err(format!(
+            "depth within {}% from mid: bids {bid_depth}, asks {ask_depth} < {}",
+            om.liquidity_depth_range_percent, om.liquidity_min_depth
+        ));
+    }
+
+    if om.liquidity_max_exit_slippage_percent.is_zero() {
+        return Ok(Liquidity {
+            spread_percent,
+            bid_depth,
+            ask_depth,
+            exit_slippage_percent: Decimal::ZERO,
+        });
+    }
+
+    // qty we would buy now is the qty we will have to sell later
+    let exit_qty = quote_amount / best_ask;
+    let exit_vwap = book.vwap(BookSide::Bids, exit_qty).ok_or(format!(
+        "bids can't absorb exit qty {}",
+        exit_qty.round_dp(8)
+    ))?;
+    let exit_slippage_percent =
+        ((best_bid - exit_vwap) / best_bid * Decimal::ONE_HUNDRED).round_dp(4);
+
+    if exit_slippage_percent > om.liquidity_max_exit_slippage_percent {
+        return Err(format!(
+            "exit slippage {exit_slippage_percent}% > {}%",
+            om.liquidity_max_exit_slippage_percent
+        ));
+    }
+
+    Ok(Liquidity {
+        spread_percent,
+        bid_depth,
+        ask_depth,
+        exit_slippage_percent,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn test_config(
+        max_spread: Decimal,
+        range: Decimal,
+        min_depth: Decimal,
+        max_slippage: Decimal,
+    ) -> ConfigStruct {
+        let mut config = toml::from_str::<ConfigStruct>(include_str!("../../config.toml")).unwrap();
+        config.orderbook_monitor.liquidity_max_spread_percent = max_spread;
+        config.orderbook_monitor.liquidity_depth_range_percent = range;
+        config.orderbook_monitor.liquidity_min_depth = min_depth;
+        config.orderbook_monitor.liquidity_max_exit_slippage_percent = max_slippage;
+        config
+    }
+
+    // spread 0.1%, within 1% from mid: bids 1995, asks 2006
+    fn book() -> LocalOrderBook {
+        let mut book = LocalOrderBook::default();
+        book.apply_snapshot(
+            1,
+            &[
+                (dec!(10.00), dec!(100)),
+                (dec!(9.95), dec!(100)),
+                (dec!(9.00), dec!(1000)),
+            ],
+            &[(dec!(10.01), dec!(100)), (dec!(10.05), dec!(100))],
+        );
+        book
+    }
+
+    #[test]
+    fn liquid_book_passes() {
+        let config = test_config(dec!(0.5), dec!(1), dec!(1000), dec!(0.3));
+        // exit qty 10 is absorbed by the best bid
+        let liquidity = check_liquidity(&config, &book(), dec!(100.1)).unwrap();
+        assert_eq!(liquidity.spread_percent, dec!(0.1));
+        assert_eq!(liquidity.bid_depth, dec!(1995));
+        assert_eq!(liquidity.ask_depth, dec!(2006));
+        assert_eq!(liquidity.exit_slippage_percent, Decimal::ZERO);
+    }
+
+    #[test]
+    fn wide_spread_is_rejected() {
+        let config = test_config(dec!(0.05), dec!(1), dec!(1000), dec!(0.3));
+        let err = check_liquidity(&config, &book(), dec!(100.1)).unwrap_err();
+        assert!(err.starts_with("spread 0.10% > 0.05%"), "{err}");
+    }
+
+    #[test]
+    fn thin_depth_is_rejected() {
+        // bids have only 1995 within 1% from mid
+        let config = test_config(dec!(0.5), dec!(1), dec!(2000), dec!(0.3));
+        let err = check_liquidity(&config, &book(), dec!(100.1)).unwrap_err();
+        assert!(err.starts_with("depth within 1% from mid"), "{err}");
+
+        // wider range includes the 9.00 bid level
+        let config = test_config(dec!(0.5), dec!(15), dec!(2000), dec!(0.3));
+        assert!(check_liquidity(&config, &book(), dec!(100.1)).is_ok());
+    }
+
+    #[test]
+    fn exit_slippage_is_checked() {
+        // exit qty 150: 100 at 10.00 and 50 at 9.95 -> vwap 9.98333
+        let config = test_config(dec!(0.5), dec!(1), dec!(1000), dec!(0.1));
+        let err = check_liquidity(&config, &book(), dec!(1501.5)).unwrap_err();
+        assert!(err.starts_with("exit slippage 0.1667% > 0.1%"), "{err}");
+
+        let config = test_config(dec!(0.5), dec!(1), dec!(1000), dec!(0.2));
+        let liquidity = check_liquidity(&config, &book(), dec!(1501.5)).unwrap();
+        assert_eq!(liquidity.exit_slippage_percent, dec!(0.1667));
+    }
+
+    #[test]
+    fn exit_qty_larger_than_bids_is_rejected() {
+        let config = test_config(dec!(0.5), dec!(1), dec!(1000), dec!(50));
+        // exit qty 2000 > 1200 on bids
+        let err = check_liquidity(&config, &book(), dec!(20020)).unwrap_err();
+        assert!(err.starts_with("bids can't absorb exit qty"), "{err}");
+    }
+
+    #[test]
+    fn zero_thresholds_are_not_checked() {
+        let config = test_config(dec!(0), dec!(1), dec!(0), dec!(0));
+        let liquidity = check_liquidity(&config, &book(), dec!(20020)).unwrap();
+        assert_eq!(liquidity.exit_slippage_percent, Decimal::ZERO);
+    }
+
+    #[test]
+    fn empty_book_is_rejected() {
+        let config = test_config(dec!(0), dec!(1), dec!(0), dec!(0));
+        let mut book = LocalOrderBook::default();
+        book.apply_snapshot(1, &[(dec!(10), dec!(1))], &[]);
+        assert_eq!(
+            check_liquidity(&config, &LocalOrderBook::default(), dec!(10)),
+            Err("empty book".to_string())
+        );
+        assert_eq!(
+            check_liquidity(&config, &book, dec!(10)),
+            Err("empty book".to_string())
+        );
+    }
+}
@@ -1,11 +1,20 @@
+pub mod activity;
 pub mod calc;
+pub mod candidates;
 pub mod cli;
 pub mod engine;
+pub mod journal;
 pub mod json;
-pub mod pre_window_monitor;
-pub mod window_monitor;
+pub mod liquidity;
+pub mod order_book;
+pub mod position;
 pub mod post_window_monitor;
+pub mod pre_window_monitor;
+pub mod price_history;
 pub mod prices;
+pub mod report;
 pub mod structs;
 pub mod trading;
 pub mod types;
+pub mod window_monitor;
+pub mod window_stats;
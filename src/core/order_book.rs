@@ -0,0 +1,373 @@
+use rust_decimal::Decimal;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSide {
+    Bids,
+    Asks,
+}
+
+// result of walking the side for some qty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookFill {
+    // average price of the whole qty
+    pub avg_price: Decimal,
+    // the worst level reached - limit order for this price takes all walked levels
+    pub limit_price: Decimal,
+    // how many levels were needed
+    pub levels: usize,
+}
+
+// what happened with the diff applied to the book
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BookUpdate {
+    Applied,
+    // the diff is older than the book (it was already in the snapshot)
+    Ignored,
+    // some diffs were missed or the book broke - the book has to be snapshotted again
+    ResyncNeeded(String),
+}
+
+/*
+Local copy of the symbol order book: REST snapshot + depth stream diffs, merged the way Binance
+describes it (web-socket-streams.md, "How to manage a local order book correctly"):
+- diffs with final update id <= snapshot's last update id are dropped
+- the first diff applied after the snapshot has to contain last update id + 1
+- every next diff has to start right after the previous one, otherwise some diffs were missed
+- qty in the diff is the new absolute qty of the level, 0 removes the level
+
+After every diff the book is checked (not crossed, no empty levels), broken book needs a new
+snapshot like a missed diff does.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct LocalOrderBook {
+    // price -> qty
+    bids: BTreeMap<Reverse<Decimal>, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+    last_update_id: u64,
+    // snapshot is taken and no diff is missing since then
+    synced: bool,
+    // at least one diff was applied on top of the snapshot
+    diff_applied: bool,
+}
+
+impl LocalOrderBook {
+    // replaces the book with REST snapshot, levels are (price, qty)
+    pub fn apply_snapshot(
+        &mut self,
+        last_update_id: u64,
+        bids: &[(Decimal, Decimal)],
+        asks: &[(Decimal, Decimal)],
+    ) {
+        self.bids = bids
+            .iter()
+            .filter(|(_, qty)| !qty.is_zero())
+            .map(|(price, qty)| (Reverse(*price), *qty))
+            .collect();
+        self.asks = asks
+            .iter()
+            .filter(|(_, qty)| !qty.is_zero())
+            .copied()
+            .collect();
+        self.last_update_id = last_update_id;
+        self.synced = true;
+        self.diff_applied = false;
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.synced
+    }
+
+    pub fn apply_diff(
+        &mut self,
+        first_update_id: u64,
+        final_update_id: u64,
+        bids: &[(Decimal, Decimal)],
+        asks: &[(Decimal, Decimal)],
+    ) -> BookUpdate {
+        if !self.synced {
+            return BookUpdate::ResyncNeeded("no snapshot".to_string());
+        }
+
+        if final_update_id <= self.last_update_id {
+            return BookUpdate::Ignored;
+        }
+
+        let next_update_id = self.last_update_id + 1;
+        let in_sequence = match self.diff_applied {
+            // snapshot may be in the middle of the diff
+            false => first_update_id <= next_update_id,
+            true => first_update_id == next_update_id,
+        };
+
+        if !in_sequence {
+            self.synced = false;
+            return BookUpdate::ResyncNeeded(format!(
+                "gap in updates: expected {next_update_id}, received {first_update_id}"
+            ));
+        }
+
+        for (price, qty) in bids {
+            match qty.is_zero() {
+                true => self.bids.remove(&Reverse(*price)),
+                false => self.bids.insert(Reverse(*price), *qty),
+            };
+        }
+        for (price, qty) in asks {
+            match qty.is_zero() {
+                true => self.asks.remove(price),
+                false => self.asks.insert(*price, *qty),
+            };
+        }
+        self.last_update_id = final_update_id;
+        self.diff_applied = true;
+
+        if let Err(e) = self.check_invariants() {
+            self.synced = false;
+            return BookUpdate::ResyncNeeded(e);
+        }
+
+        BookUpdate::Applied
+    }
+
+    // book has to be never crossed and never contain empty levels
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if let (Some((bid, _)), Some((ask, _))) = (self.best_bid(), self.best_ask()) {
+            if bid >= ask {
+                return Err(format!("crossed book: best bid {bid} >= best ask {ask}"));
+            }
+        }
+
+        let empty_bid = self.bids.iter().find(|(_, qty)| **qty <= Decimal::ZERO);
+        let empty_ask = self.asks.iter().find(|(_, qty)| **qty <= Decimal::ZERO);
+
+        match (empty_bid, empty_ask) {
+            (Some((Reverse(price), _)), _) => Err(format!("empty bid level {price}")),
+            (_, Some((price, _))) => Err(format!("empty ask level {price}")),
+            _ => Ok(()),
+        }
+    }
+
+    // (price, qty) of the best level
+    pub fn best_bid(&self) -> Option<(Decimal, Decimal)> {
+        self.bids.iter().next().map(|(Reverse(p), q)| (*p, *q))
+    }
+
+    pub fn best_ask(&self) -> Option<(Decimal, Decimal)> {
+        self.asks.iter().next().map(|(p, q)| (*p, *q))
+    }
+
+    // (price, qty) levels from the best to the worst one
+    pub fn levels(&self, side: BookSide) -> Box<dyn Iterator<Item = (Decimal, Decimal)> + '_> {
+        match side {
+            BookSide::Bids => Box::new(self.bids.iter().map(|(Reverse(p), q)| (*p, *q))),
+            BookSide::Asks => Box::new(self.asks.iter().map(|(p, q)| (*p, *q))),
+        }
+    }
+
+    // qty available at this price or better (bids >= price, asks <= price)
+    pub fn depth_to_price(&self, side: BookSide, price: Decimal) -> Decimal {
+        self.levels(side)
+            .take_while(|(p, _)| match side {
+                BookSide::Bids => *p >= price,
+                BookSide::Asks => *p <= price,
+            })
+            .map(|(_, q)| q)
+            .sum()
+    }
+
+    /*
+    Fills |qty| by walking the side from the best level (selling to bids, buying from asks), None if
+    the book doesn't have enough qty.
+     */
+    pub fn fill(&self, side: BookSide, qty: Decimal) -> Option<BookFill> {
+        if qty <= Decimal::ZERO {
+            return None;
+        }
+
+        let mut remaining = qty;
+        let mut cost = Decimal::ZERO;
+
+        for (levels, (price, level_qty)) in self.levels(side).enumerate() {
+            let filled = remaining.min(level_qty);
+            cost += filled * price;
+            remaining -= filled;
+
+            if remaining.is_zero() {
+                return Some(BookFill {
+                    avg_price: cost / qty,
+                    limit_price: price,
+                    levels: levels + 1,
+                });
+            }
+        }
+
+        None
+    }
+
+    // average price of filling |qty|, see fill()
+    pub fn vwap(&self, side: BookSide, qty: Decimal) -> Option<Decimal> {
+        self.fill(side, qty).map(|fill| fill.avg_price)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    // snapshot 100: bids 10.0/9.9, asks 10.1/10.2
+    fn snapshot_book() -> LocalOrderBook {
+        let mut book = LocalOrderBook::default();
+        book.apply_snapshot(
+            100,
+            &[(dec!(10.0), dec!(1)), (dec!(9.9), dec!(2))],
+            &[(dec!(10.1), dec!(1)), (dec!(10.2), dec!(3))],
+        );
+        book
+    }
+
+    #[test]
+    fn diff_without_snapshot_needs_resync() {
+        let mut book = LocalOrderBook::default();
+
+        let update = book.apply_diff(1, 2, &[(dec!(10), dec!(1))], &[]);
+
+        assert!(matches!(update, BookUpdate::ResyncNeeded(_)));
+        assert!(!book.is_synced());
+    }
+
+    #[test]
+    fn snapshot_then_diffs_are_merged() {
+        let mut book = snapshot_book();
+
+        // the first diff can start before the snapshot
+        assert_eq!(
+            book.apply_diff(95, 101, &[(dec!(10.05), dec!(4))], &[(dec!(10.2), dec!(5))]),
+            BookUpdate::Applied
+        );
+        assert_eq!(
+            book.apply_diff(102, 103, &[(dec!(9.8), dec!(1))], &[(dec!(10.3), dec!(1))]),
+            BookUpdate::Applied
+        );
+
+        assert_eq!(book.best_bid(), Some((dec!(10.05), dec!(4))));
+        assert_eq!(book.best_ask(), Some((dec!(10.1), dec!(1))));
+        assert_eq!(
+            book.levels(BookSide::Bids).collect::<Vec<_>>(),
+            vec![
+                (dec!(10.05), dec!(4)),
+                (dec!(10.0), dec!(1)),
+                (dec!(9.9), dec!(2)),
+                (dec!(9.8), dec!(1)),
+            ]
+        );
+        assert_eq!(
+            book.levels(BookSide::Asks).collect::<Vec<_>>(),
+            vec![
+                (dec!(10.1), dec!(1)),
+                (dec!(10.2), dec!(5)),
+                (dec!(10.3), dec!(1))
+            ]
+        );
+        assert_eq!(book.last_update_id, 103);
+    }
+
+    #[test]
+    fn stale_diffs_are_dropped() {
+        let mut book = snapshot_book();
+
+        assert_eq!(
+            book.apply_diff(90, 99, &[(dec!(10.0), dec!(7))], &[]),
+            BookUpdate::Ignored
+        );
+        assert_eq!(
+            book.apply_diff(100, 100, &[(dec!(10.0), dec!(7))], &[]),
+            BookUpdate::Ignored
+        );
+
+        assert_eq!(book.best_bid(), Some((dec!(10.0), dec!(1))));
+        assert_eq!(book.last_update_id, 100);
+        assert!(book.is_synced());
+    }
+
+    #[test]
+    fn gap_forces_resync() {
+        let mut book = snapshot_book();
+
+        // the first diff has to contain last update id + 1
+        assert!(matches!(
+            book.apply_diff(102, 105, &[], &[]),
+            BookUpdate::ResyncNeeded(_)
+        ));
+        assert!(!book.is_synced());
+
+        // every next diff has to continue the previous one
+        let mut book = snapshot_book();
+        assert_eq!(book.apply_diff(101, 101, &[], &[]), BookUpdate::Applied);
+        assert!(matches!(
+            book.apply_diff(103, 104, &[], &[]),
+            BookUpdate::ResyncNeeded(_)
+        ));
+        assert!(!book.is_synced());
+
+        // and the book stays out of sync until the next snapshot
+        assert!(matches!(
+            book.apply_diff(102, 102, &[], &[]),
+            BookUpdate::ResyncNeeded(_)
+        ));
+        book.apply_snapshot(200, &[(dec!(10.0), dec!(1))], &[(dec!(10.1), dec!(1))]);
+        assert_eq!(book.apply_diff(201, 201, &[], &[]), BookUpdate::Applied);
+    }
+
+    #[test]
+    fn zero_qty_removes_level() {
+        let mut book = snapshot_book();
+
+        assert_eq!(
+            book.apply_diff(101, 101, &[(dec!(10.0), dec!(0))], &[(dec!(10.1), dec!(0))]),
+            BookUpdate::Applied
+        );
+
+        assert_eq!(book.best_bid(), Some((dec!(9.9), dec!(2))));
+        assert_eq!(book.best_ask(), Some((dec!(10.2), dec!(3))));
+        assert!(book.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn snapshot_skips_zero_qty_levels() {
+        let mut book = LocalOrderBook::default();
+        book.apply_snapshot(1, &[(dec!(10), dec!(0)), (dec!(9), dec!(1))], &[]);
+
+        assert_eq!(book.best_bid(), Some((dec!(9), dec!(1))));
+    }
+
+    #[test]
+    fn crossed_book_is_rejected() {
+        let mut book = snapshot_book();
+
+        // bid above the best ask
+        let update = book.apply_diff(101, 101, &[(dec!(10.15), dec!(1))], &[]);
+
+        assert!(matches!(update, BookUpdate::ResyncNeeded(reason) if reason.contains("crossed")));
+        assert!(!book.is_synced());
+
+        let mut crossed = LocalOrderBook::default();
+        crossed.apply_snapshot(1, &[(dec!(10), dec!(1))], &[(dec!(10), dec!(1))]);
+        assert!(crossed.check_invariants().is_err());
+    }
+
+    #[test]
+    fn fill_walks_levels() {
+        let book = snapshot_book();
+
+        let fill = book.fill(BookSide::Bids, dec!(2)).unwrap();
+        assert_eq!(fill.avg_price, dec!(9.95));
+        assert_eq!(fill.limit_price, dec!(9.9));
+        assert_eq!(fill.levels, 2);
+
+        assert_eq!(book.depth_to_price(BookSide::Asks, dec!(10.2)), dec!(4));
+        assert_eq!(book.fill(BookSide::Asks, dec!(5)), None);
+    }
+}
@@ -0,0 +1,305 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/*
+What the order really did (not what was requested): base qty filled and quote qty paid/received.
+|complete| is false when only part of the requested qty was filled (buy cancelled after partial fill,
+IOC order, limit sell cancelled after timeout).
+
+|commissions| are fees charged by the exchange (commission and commissionAsset of the order trades),
+empty if they are not known (simulation) - the fee is estimated then.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderFill {
+    pub executed_qty: Decimal,
+    pub quote_qty: Decimal,
+    pub complete: bool,
+    pub commissions: BTreeMap<String, Decimal>,
+}
+
+impl OrderFill {
+    // fill of the next order for the same qty (chased entry) - complete if that one was filled
+    pub fn merge(&mut self, other: &OrderFill) {
+        self.executed_qty += other.executed_qty;
+        self.quote_qty += other.quote_qty;
+        self.complete = other.complete;
+
+        for (asset, fee) in &other.commissions {
+            *self.commissions.entry(asset.clone()).or_default() += fee;
+        }
+    }
+}
+
+/*
+Position of one trade built from order fills:
+- bought_qty/spent_quote - all buy fills, sold_qty/received_quote - all sell fills
+- fees paid in every asset as the exchange charged them - received asset (base asset of buys, quote
+  asset of sells) or BNB when fees are paid with BNB; estimated fee (comission percent) is taken from
+  the received asset
+- held qty is what we really have: bought - sold - fees paid in base asset
+- dust_qty is held qty left after the trade, it can't be sold (below LOT_SIZE)
+- fee_prices are prices (in quote asset) of other fee assets (BNB), set when the trade is finished
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub bought_qty: Decimal,
+    pub spent_quote: Decimal,
+    pub sold_qty: Decimal,
+    pub received_quote: Decimal,
+    pub fees: BTreeMap<String, Decimal>,
+    pub dust_qty: Decimal,
+    pub fee_prices: BTreeMap<String, Decimal>,
+}
+
+impl Position {
+    pub fn new(base_asset: &str, quote_asset: &str) -> Self {
+        Self {
+            base_asset: base_asset.to_string(),
+            quote_asset: quote_asset.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn add_fee(&mut self, asset: String, fee: Decimal) {
+        *self.fees.entry(asset).or_default() += fee;
+    }
+
+    fn fee(&self, asset: &str) -> Decimal {
+        self.fees.get(asset).copied().unwrap_or_default()
+    }
+
+    // fees charged for the order, |estimated_fee| in |received_asset| if the exchange didn't tell
+    fn add_fill_fees(&mut self, fill: &OrderFill, received_asset: String, estimated_fee: Decimal) {
+        if fill.commissions.is_empty() {
+            self.add_fee(received_asset, estimated_fee);
+            return;
+        }
+
+        for (asset, fee) in &fill.commissions {
+            self.add_fee(asset.clone(), *fee);
+        }
+    }
+
+    // |comission| is a fraction (0.001 for 0.1%)
+    pub fn apply_buy(&mut self, fill: &OrderFill, comission: Decimal) {
+        self.bought_qty += fill.executed_qty;
+        self.spent_quote += fill.quote_qty;
+        self.add_fill_fees(fill, self.base_asset.clone(), fill.executed_qty * comission);
+    }
+
+    pub fn apply_sell(&mut self, fill: &OrderFill, comission: Decimal) {
+        self.sold_qty += fill.executed_qty;
+        self.received_quote += fill.quote_qty;
+        self.add_fill_fees(fill, self.quote_asset.clone(), fill.quote_qty * comission);
+    }
+
+    // base qty we have now
+    pub fn held_qty(&self) -> Decimal {
+        (self.bought_qty - self.sold_qty - self.fee(&self.base_asset)).max(Decimal::ZERO)
+    }
+
+    // quote qty we got back from sells after fees
+    pub fn net_received_quote(&self) -> Decimal {
+        self.received_quote - self.fee(&self.quote_asset)
+    }
+
+    pub fn avg_entry_price(&self) -> Decimal {
+        match self.bought_qty.is_zero() {
+            true => Decimal::ZERO,
+            false => (self.spent_quote / self.bought_qty).round_dp(8),
+        }
+    }
+
+    pub fn avg_exit_price(&self) -> Decimal {
+        match self.sold_qty.is_zero() {
+            true => Decimal::ZERO,
+            false => (self.received_quote / self.sold_qty).round_dp(8),
+        }
+    }
+
+    // fee assets other than traded ones (BNB) - their price is needed for the net profit
+    pub fn other_fee_assets(&self) -> Vec<String> {
+        self.fees
+            .keys()
+            .filter(|asset| **asset != self.base_asset && **asset != self.quote_asset)
+            .cloned()
+            .collect()
+    }
+
+    // base asset is valued by the exit price (entry price if nothing was sold)
+    fn asset_price(&self, asset: &str) -> Option<Decimal> {
+        if asset == self.quote_asset {
+            return Some(Decimal::ONE);
+        }
+        if asset == self.base_asset {
+            return match self.sold_qty.is_zero() {
+                true => Some(self.avg_entry_price()),
+                false => Some(self.avg_exit_price()),
+            };
+        }
+
+        self.fee_prices.get(asset).copied()
+    }
+
+    // all fees in quote asset, fees of assets without price are not counted
+    pub fn fees_in_quote(&self) -> Decimal {
+        self.fees
+            .iter()
+            .filter_map(|(asset, fee)| self.asset_price(asset).map(|price| fee * price))
+            .sum::<Decimal>()
+            .round_dp(8)
+    }
+
+    /*
+    Profit in quote asset after all fees. Fees in traded assets are already out of our quote qty
+    (base fee lowered the sold qty, quote fee the received qty), fees in other assets are subtracted
+    by their price.
+     */
+    pub fn net_profit(&self) -> Decimal {
+        let other_fees: Decimal = self
+            .other_fee_assets()
+            .iter()
+            .filter_map(|asset| self.asset_price(asset).map(|price| self.fee(asset) * price))
+            .sum();
+
+        (self.net_received_quote() - self.spent_quote - other_fees).round_dp(8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn fill(executed_qty: Decimal, quote_qty: Decimal, complete: bool) -> OrderFill {
+        OrderFill {
+            executed_qty,
+            quote_qty,
+            complete,
+            commissions: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn merged_fills_add_up() {
+        let mut first = fill(dec!(4), dec!(40), false);
+        first.commissions.insert("ABC".to_string(), dec!(0.004));
+        let mut second = fill(dec!(6), dec!(60.6), true);
+        second.commissions.insert("ABC".to_string(), dec!(0.006));
+
+        first.merge(&second);
+        assert_eq!(first.executed_qty, dec!(10));
+        assert_eq!(first.quote_qty, dec!(100.6));
+        assert!(first.complete);
+        assert_eq!(first.commissions.get("ABC"), Some(&dec!(0.010)));
+    }
+
+    #[test]
+    fn partial_sells_leave_held_qty() {
+        let mut position = Position::new("ABC", "USDT");
+        // estimated fee 0.1% taken from the bought base qty
+        position.apply_buy(&fill(dec!(10), dec!(100), true), dec!(0.001));
+        assert_eq!(position.held_qty(), dec!(9.99));
+        assert_eq!(position.avg_entry_price(), dec!(10));
+
+        position.apply_sell(&fill(dec!(5), dec!(55), false), dec!(0.001));
+        assert_eq!(position.held_qty(), dec!(4.99));
+
+        // the rest below LOT_SIZE can't be sold
+        position.apply_sell(&fill(dec!(4.98), dec!(54.78), true), dec!(0.001));
+        assert_eq!(position.held_qty(), dec!(0.01));
+        assert_eq!(position.avg_exit_price(), dec!(11));
+        assert_eq!(position.net_received_quote(), dec!(109.67022));
+        assert_eq!(position.net_profit(), dec!(9.67022));
+    }
+
+    #[test]
+    fn held_qty_is_never_negative() {
+        let mut position = Position::new("ABC", "USDT");
+        position.apply_buy(&fill(dec!(10), dec!(100), true), dec!(0.001));
+        position.apply_sell(&fill(dec!(10), dec!(110), true), dec!(0));
+        assert_eq!(position.held_qty(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn no_fills_no_prices() {
+        let position = Position::new("ABC", "USDT");
+        assert_eq!(position.avg_entry_price(), Decimal::ZERO);
+        assert_eq!(position.avg_exit_price(), Decimal::ZERO);
+        assert_eq!(position.held_qty(), Decimal::ZERO);
+        assert_eq!(position.net_profit(), Decimal::ZERO);
+    }
+
+    fn fill_with_fee(
+        executed_qty: Decimal,
+        quote_qty: Decimal,
+        asset: &str,
+        fee: Decimal,
+    ) -> OrderFill {
+        let mut fill = fill(executed_qty, quote_qty, true);
+        fill.commissions.insert(asset.to_string(), fee);
+        fill
+    }
+
+    #[test]
+    fn exchange_fee_replaces_estimate() {
+        let mut position = Position::new("ABC", "USDT");
+        // tier fee 0.2% charged, estimated 0.1% is not used
+        position.apply_buy(
+            &fill_with_fee(dec!(10), dec!(100), "ABC", dec!(0.02)),
+            dec!(0.001),
+        );
+        assert_eq!(position.fees.get("ABC"), Some(&dec!(0.02)));
+        assert_eq!(position.held_qty(), dec!(9.98));
+        // base fee valued by the entry price until something is sold
+        assert_eq!(position.fees_in_quote(), dec!(0.2));
+    }
+
+    #[test]
+    fn fees_in_traded_assets() {
+        let mut position = Position::new("ABC", "USDT");
+        position.apply_buy(
+            &fill_with_fee(dec!(10), dec!(100), "ABC", dec!(0.01)),
+            dec!(0.001),
+        );
+        position.apply_sell(
+            &fill_with_fee(dec!(9.99), dec!(109.89), "USDT", dec!(0.10989)),
+            dec!(0.001),
+        );
+
+        assert_eq!(position.held_qty(), Decimal::ZERO);
+        assert!(position.other_fee_assets().is_empty());
+        // base fee valued by the exit price 11
+        assert_eq!(position.fees_in_quote(), dec!(0.21989));
+        assert_eq!(position.net_profit(), dec!(9.78011));
+    }
+
+    #[test]
+    fn bnb_fees() {
+        let mut position = Position::new("ABC", "USDT");
+        position.apply_buy(
+            &fill_with_fee(dec!(10), dec!(100), "BNB", dec!(0.0002)),
+            dec!(0.001),
+        );
+        // nothing taken from the bought qty
+        assert_eq!(position.held_qty(), dec!(10));
+
+        position.apply_sell(
+            &fill_with_fee(dec!(10), dec!(110), "BNB", dec!(0.00022)),
+            dec!(0.001),
+        );
+        assert_eq!(position.net_received_quote(), dec!(110));
+        assert_eq!(position.other_fee_assets(), vec!["BNB".to_string()]);
+
+        // BNB price not known yet - its fee is not counted
+        assert_eq!(position.fees_in_quote(), Decimal::ZERO);
+        assert_eq!(position.net_profit(), dec!(10));
+
+        position.fee_prices.insert("BNB".to_string(), dec!(500));
+        assert_eq!(position.fees_in_quote(), dec!(0.21));
+        assert_eq!(position.net_profit(), dec!(9.79));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::config::settings::ConfigStruct;
 use crate::core::calc::percentage_change_between_first_and_last_element;
+use crate::core::window_stats::{window_stats, WindowStats};
 use rust_decimal::Decimal;
 use std::cell::Cell;
 use std::collections::HashMap;
@@ -7,14 +8,18 @@ use std::rc::Rc;
 
 pub struct PostWindowStatus {
     pub symbols_post_window_with_percent_changes: Rc<Cell<HashMap<String, Decimal>>>,
+    pub percent_change: Decimal,
     pub monitor_threshold_reached: bool,
     pub rise_threshold_reached: bool,
     pub drop_threshold_reached: bool,
+    // run up, drawdown, slope, ... of the window and whether they are within configured thresholds
+    pub stats: WindowStats,
+    pub stats_thresholds_reached: bool,
 }
 
 // this function is pure
 pub fn calculate_post_window(
-    config: ConfigStruct,
+    config: &ConfigStruct,
     symbol: &str,
     post_window_list: &[Decimal],
     symbols_post_window_with_percent_changes: Rc<Cell<HashMap<String, Decimal>>>,
@@ -66,11 +71,18 @@ pub fn calculate_post_window(
 
     symbols_post_window_with_percent_changes.set(new_symbols_post_window_map);
 
+    let stats = window_stats(post_window_list);
+    let stats_thresholds_reached =
+        stats.thresholds_reached(&config.symbol_monitor.post_window_stats_thresholds());
+
     let mut post_window_status = PostWindowStatus {
         symbols_post_window_with_percent_changes,
+        percent_change: percent_change_post_window,
         monitor_threshold_reached: false,
         rise_threshold_reached: false,
         drop_threshold_reached: false,
+        stats_thresholds_reached,
+        stats,
     };
 
     // monitor is enabled = both values should not be zero
@@ -0,0 +1,371 @@
+use crate::config::settings::ConfigSymbolMonitor;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/*
+Prices of the symbol keyed by event time - one price per second (the last one received within the
+second). Ticker stream doesn't deliver every symbol every second (no change, stream gaps, reconnects),
+missing seconds are forward-filled with the last known price, so windows of every symbol always
+compare the same real-time spans.
+
+History is [pre window|main window|post window] (oldest first), neighbouring windows share the
+boundary price.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct PriceHistory {
+    // event time (secs) of the newest price
+    newest_sec: Option<u64>,
+    // one price per second, the newest at the end
+    prices: VecDeque<PricePoint>,
+}
+
+// price with 24h counters from the ticker - differences of counters are volume within a window
+// (counters of forward-filled seconds are repeated, so there were no trades then)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PricePoint {
+    pub price: Decimal,
+    pub num_trades: u64,
+    pub quote_volume: Decimal,
+}
+
+// traded within a window
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowVolume {
+    pub quote_volume: Decimal,
+    pub trades: u64,
+}
+
+// volumes of windows at the moment when the symbol was sent to the engine, None means that window
+// analysis is disabled
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowVolumes {
+    pub pre_window: Option<WindowVolume>,
+    pub window: WindowVolume,
+    pub post_window: Option<WindowVolume>,
+}
+
+// lengths (secs) of pre, main and post window, disabled windows have 0
+pub fn window_spans(sm: &ConfigSymbolMonitor) -> (u64, u64, u64) {
+    let pre_secs = match sm.pre_window_analysis {
+        true => sm.pre_window_secs,
+        false => 0,
+    };
+    let post_secs = match sm.post_window_analysis {
+        true => sm.post_window_secs,
+        false => 0,
+    };
+
+    (pre_secs, sm.window_secs, post_secs)
+}
+
+impl PriceHistory {
+    // adds price of event at |time_ms|, keeps last |span_secs| seconds
+    pub fn add(&mut self, time_ms: u64, point: PricePoint, span_secs: u64) {
+        let sec = time_ms / 1000;
+
+        match self.newest_sec {
+            // stream may deliver events out of order after reconnect - old ones are useless here
+            Some(newest) if sec < newest => return,
+            Some(newest) if sec == newest => {
+                *self.prices.back_mut().unwrap() = point;
+            }
+            _ => {
+                self.fill_to_sec(sec, span_secs);
+                self.prices.push_back(point);
+            }
+        }
+        self.newest_sec = Some(sec);
+
+        self.trim(span_secs);
+    }
+
+    // symbol wasn't in the update at |time_ms| - its price didn't change, the last one is repeated
+    pub fn fill_to(&mut self, time_ms: u64, span_secs: u64) {
+        let sec = time_ms / 1000;
+
+        if self.newest_sec.is_some_and(|newest| sec > newest) {
+            self.fill_to_sec(sec + 1, span_secs);
+            self.newest_sec = Some(sec);
+            self.trim(span_secs);
+        }
+    }
+
+    // forward-fills seconds between the newest price and |sec| (|sec| itself not included)
+    fn fill_to_sec(&mut self, sec: u64, span_secs: u64) {
+        let (Some(newest), Some(&last)) = (self.newest_sec, self.prices.back()) else {
+            return;
+        };
+
+        // older ones would be trimmed anyway
+        let missing = (sec - newest - 1).min(span_secs + 1);
+        self.prices
+            .extend(std::iter::repeat_n(last, missing as usize));
+    }
+
+    fn trim(&mut self, span_secs: u64) {
+        // |span_secs| seconds are between |span_secs| + 1 prices
+        while self.prices.len() as u64 > span_secs + 1 {
+            self.prices.pop_front();
+        }
+    }
+
+    // history covers whole |span_secs|
+    pub fn is_complete(&self, span_secs: u64) -> bool {
+        self.prices.len() as u64 > span_secs
+    }
+
+    fn window_points(
+        &self,
+        start_secs_ago: u64,
+        end_secs_ago: u64,
+    ) -> impl Iterator<Item = &PricePoint> {
+        let len = self.prices.len();
+        let start = len.saturating_sub(start_secs_ago as usize + 1);
+        let end = len.saturating_sub(end_secs_ago as usize);
+
+        self.prices.range(start..end)
+    }
+
+    // prices from |start_secs_ago| to |end_secs_ago| seconds before the newest one (both included)
+    pub fn window(&self, start_secs_ago: u64, end_secs_ago: u64) -> Vec<Decimal> {
+        self.window_points(start_secs_ago, end_secs_ago)
+            .map(|p| p.price)
+            .collect()
+    }
+
+    // volume traded between |start_secs_ago| and |end_secs_ago| seconds before the newest price
+    pub fn window_volume(&self, start_secs_ago: u64, end_secs_ago: u64) -> WindowVolume {
+        let mut points = self.window_points(start_secs_ago, end_secs_ago);
+        let (first, last) = match (points.next(), points.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return WindowVolume::default(),
+        };
+
+        // 24h counters go down when old trades leave the 24h window, that's not volume
+        WindowVolume {
+            quote_volume: (last.quote_volume - first.quote_volume).max(Decimal::ZERO),
+            trades: last.num_trades.saturating_sub(first.num_trades),
+        }
+    }
+}
+
+impl ConfigSymbolMonitor {
+    /*
+    Price moves made by a couple of tiny trades are not signals - every enabled window has to have
+    at least |*_min_quote_volume| and |*_min_trades|, main window quote volume has to be at least
+    |window_min_quote_volume_to_pre_window_ratio| times pre window's one and post window's at least
+    |post_window_min_quote_volume_to_window_ratio| times main window's one (0 turns the check off).
+     */
+    pub fn volume_confirmed(&self, volumes: &WindowVolumes) -> bool {
+        let enough = |volume: &WindowVolume, min_quote_volume: Decimal, min_trades: u64| {
+            volume.quote_volume >= min_quote_volume && volume.trades >= min_trades
+        };
+        // ratio 0 (or no volume in the base window) always passes
+        let ratio_reached = |volume: &WindowVolume, base: &WindowVolume, ratio: Decimal| {
+            volume.quote_volume >= base.quote_volume * ratio
+        };
+
+        if !enough(
+            &volumes.window,
+            self.window_min_quote_volume,
+            self.window_min_trades,
+        ) {
+            return false;
+        }
+
+        if let Some(pre_window) = &volumes.pre_window {
+            if !enough(
+                pre_window,
+                self.pre_window_min_quote_volume,
+                self.pre_window_min_trades,
+            ) || !ratio_reached(
+                &volumes.window,
+                pre_window,
+                self.window_min_quote_volume_to_pre_window_ratio,
+            ) {
+                return false;
+            }
+        }
+
+        if let Some(post_window) = &volumes.post_window {
+            if !enough(
+                post_window,
+                self.post_window_min_quote_volume,
+                self.post_window_min_trades,
+            ) || !ratio_reached(
+                post_window,
+                &volumes.window,
+                self.post_window_min_quote_volume_to_window_ratio,
+            ) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::ConfigStruct;
+    use rust_decimal_macros::dec;
+
+    fn point(price: Decimal, num_trades: u64, quote_volume: Decimal) -> PricePoint {
+        PricePoint {
+            price,
+            num_trades,
+            quote_volume,
+        }
+    }
+
+    fn symbol_monitor() -> ConfigSymbolMonitor {
+        let config: ConfigStruct = toml::from_str(include_str!("../../config.toml")).unwrap();
+        config.symbol_monitor
+    }
+
+    #[test]
+    fn missing_seconds_are_forward_filled() {
+        let mut history = PriceHistory::default();
+
+        history.add(1_000, point(dec!(1), 0, dec!(0)), 10);
+        history.add(4_500, point(dec!(2), 0, dec!(0)), 10);
+
+        assert_eq!(
+            history.window(3, 0),
+            vec![dec!(1), dec!(1), dec!(1), dec!(2)]
+        );
+    }
+
+    #[test]
+    fn last_price_within_second_wins_and_old_events_are_ignored() {
+        let mut history = PriceHistory::default();
+
+        history.add(1_000, point(dec!(1), 0, dec!(0)), 10);
+        history.add(2_100, point(dec!(2), 0, dec!(0)), 10);
+        history.add(2_900, point(dec!(3), 0, dec!(0)), 10);
+        // out of order after reconnect
+        history.add(1_500, point(dec!(9), 0, dec!(0)), 10);
+
+        assert_eq!(history.window(1, 0), vec![dec!(1), dec!(3)]);
+    }
+
+    #[test]
+    fn fill_to_repeats_last_price() {
+        let mut history = PriceHistory::default();
+
+        // nothing to repeat yet
+        history.fill_to(1_000, 10);
+        assert_eq!(history.window(10, 0), Vec::<Decimal>::new());
+
+        history.add(1_000, point(dec!(1), 0, dec!(0)), 10);
+        history.fill_to(3_000, 10);
+
+        assert_eq!(history.window(10, 0), vec![dec!(1), dec!(1), dec!(1)]);
+    }
+
+    #[test]
+    fn history_keeps_span_and_is_complete_when_it_covers_it() {
+        let mut history = PriceHistory::default();
+
+        history.add(0, point(dec!(1), 0, dec!(0)), 3);
+        history.add(2_000, point(dec!(2), 0, dec!(0)), 3);
+        assert!(!history.is_complete(3));
+
+        history.add(5_000, point(dec!(3), 0, dec!(0)), 3);
+        assert!(history.is_complete(3));
+        // 3 seconds are between 4 prices, seconds 2..5
+        assert_eq!(
+            history.window(10, 0),
+            vec![dec!(2), dec!(2), dec!(2), dec!(3)]
+        );
+    }
+
+    #[test]
+    fn neighbouring_windows_share_boundary_price() {
+        let mut history = PriceHistory::default();
+        for sec in 0..=4 {
+            history.add(sec * 1000, point(Decimal::from(sec), 0, dec!(0)), 10);
+        }
+
+        // [pre 2s | main 2s]
+        assert_eq!(history.window(4, 2), vec![dec!(0), dec!(1), dec!(2)]);
+        assert_eq!(history.window(2, 0), vec![dec!(2), dec!(3), dec!(4)]);
+    }
+
+    #[test]
+    fn window_volume_is_difference_of_24h_counters() {
+        let mut history = PriceHistory::default();
+        history.add(0, point(dec!(1), 100, dec!(1000)), 10);
+        history.add(1_000, point(dec!(1), 110, dec!(1500)), 10);
+        history.add(2_000, point(dec!(1), 130, dec!(1700)), 10);
+
+        assert_eq!(
+            history.window_volume(2, 0),
+            WindowVolume {
+                quote_volume: dec!(700),
+                trades: 30,
+            }
+        );
+        assert_eq!(
+            history.window_volume(1, 0),
+            WindowVolume {
+                quote_volume: dec!(200),
+                trades: 20,
+            }
+        );
+    }
+
+    #[test]
+    fn decreasing_24h_counters_are_not_volume() {
+        let mut history = PriceHistory::default();
+        history.add(0, point(dec!(1), 100, dec!(1000)), 10);
+        history.add(1_000, point(dec!(1), 90, dec!(800)), 10);
+
+        assert_eq!(history.window_volume(1, 0), WindowVolume::default());
+        assert_eq!(
+            PriceHistory::default().window_volume(1, 0),
+            WindowVolume::default()
+        );
+    }
+
+    #[test]
+    fn disabled_windows_have_zero_span() {
+        let mut sm = symbol_monitor();
+        sm.pre_window_analysis = false;
+        sm.post_window_analysis = true;
+
+        assert_eq!(window_spans(&sm), (0, sm.window_secs, sm.post_window_secs));
+    }
+
+    #[test]
+    fn volume_confirmation_checks_minimums_and_ratios() {
+        let mut sm = symbol_monitor();
+        sm.window_min_quote_volume = dec!(100);
+        sm.window_min_trades = 10;
+        sm.window_min_quote_volume_to_pre_window_ratio = dec!(2);
+
+        let volume = |quote_volume: Decimal, trades: u64| WindowVolume {
+            quote_volume,
+            trades,
+        };
+        let mut volumes = WindowVolumes {
+            pre_window: Some(volume(dec!(50), 5)),
+            window: volume(dec!(100), 10),
+            post_window: None,
+        };
+        assert!(sm.volume_confirmed(&volumes));
+
+        // main window has to be 2x pre window
+        volumes.pre_window = Some(volume(dec!(60), 5));
+        assert!(!sm.volume_confirmed(&volumes));
+
+        // disabled pre window is not compared
+        volumes.pre_window = None;
+        assert!(sm.volume_confirmed(&volumes));
+
+        volumes.window.trades = 9;
+        assert!(!sm.volume_confirmed(&volumes));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::core::journal::{
+    export_trade_records_csv, export_trade_records_json, read_trade_records, TradeRecord,
+};
+use chrono::{DateTime, Timelike};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TradeStats {
+    pub trades: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub profit: Decimal,
+    pub fees: Decimal,
+    pub net_profit: Decimal,
+    pub avg_profit_percent: Decimal,
+    pub avg_peak_profit_percent: Decimal,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TradeReport {
+    pub total: TradeStats,
+    pub by_symbol: BTreeMap<String, TradeStats>,
+    pub by_hour_of_day: BTreeMap<String, TradeStats>,
+    pub by_exit_reason: BTreeMap<String, TradeStats>,
+}
+
+impl TradeStats {
+    fn add(&mut self, record: &TradeRecord) {
+        // averages are kept as sums until finish() is called
+        // old records have no net profit - their profit is net of estimated fees already
+        let net_profit = record.net_profit.unwrap_or(record.profit);
+
+        self.trades += 1;
+        if net_profit > Decimal::ZERO {
+            self.wins += 1;
+        }
+        if net_profit < Decimal::ZERO {
+            self.losses += 1;
+        }
+        self.profit += record.profit;
+        self.fees += record.fees;
+        self.net_profit += net_profit;
+        self.avg_profit_percent += record.profit_percent;
+        self.avg_peak_profit_percent += record.peak_profit_percent;
+    }
+
+    fn finish(&mut self) {
+        if self.trades == 0 {
+            return;
+        }
+
+        let trades = Decimal::from(self.trades);
+        self.avg_profit_percent =
+            (self.avg_profit_percent / trades).round_dp_with_strategy(2, RoundingStrategy::ToZero);
+        self.avg_peak_profit_percent = (self.avg_peak_profit_percent / trades)
+            .round_dp_with_strategy(2, RoundingStrategy::ToZero);
+    }
+}
+
+// hour (UTC) when we entered the trade, "??" if the time can't be parsed
+fn hour_of_day(record: &TradeRecord) -> String {
+    match DateTime::parse_from_rfc3339(&record.entry_time) {
+        Ok(t) => format!("{:02}", t.hour()),
+        Err(_) => "??".to_string(),
+    }
+}
+
+pub fn build_trade_report(records: &[TradeRecord]) -> TradeReport {
+    let mut report = TradeReport::default();
+
+    for record in records {
+        report.total.add(record);
+        report
+            .by_symbol
+            .entry(record.symbol.clone())
+            .or_default()
+            .add(record);
+        report
+            .by_hour_of_day
+            .entry(hour_of_day(record))
+            .or_default()
+            .add(record);
+        report
+            .by_exit_reason
+            .entry(record.exit_reason.to_string())
+            .or_default()
+            .add(record);
+    }
+
+    report.total.finish();
+    for stats in report
+        .by_symbol
+        .values_mut()
+        .chain(report.by_hour_of_day.values_mut())
+        .chain(report.by_exit_reason.values_mut())
+    {
+        stats.finish();
+    }
+
+    report
+}
+
+fn print_section(title: &str, section: &BTreeMap<String, TradeStats>) {
+    println!();
+    println!("{title}");
+    println!(
+        "{:<16} {:>7} {:>6} {:>6} {:>14} {:>12} {:>14} {:>10} {:>10}",
+        "", "trades", "wins", "losses", "profit", "fees", "net profit", "avg %", "avg peak %"
+    );
+    for (key, stats) in section {
+        print_stats_line(key, stats);
+    }
+}
+
+fn print_stats_line(key: &str, stats: &TradeStats) {
+    println!(
+        "{:<16} {:>7} {:>6} {:>6} {:>14} {:>12} {:>14} {:>10} {:>10}",
+        key,
+        stats.trades,
+        stats.wins,
+        stats.losses,
+        stats.profit,
+        stats.fees,
+        stats.net_profit,
+        stats.avg_profit_percent,
+        stats.avg_peak_profit_percent
+    );
+}
+
+pub fn print_trade_report(report: &TradeReport) {
+    print_section(
+        "TOTAL",
+        &BTreeMap::from([("all".to_string(), report.total.clone())]),
+    );
+    print_section("BY SYMBOL", &report.by_symbol);
+    print_section("BY HOUR OF DAY (UTC, entry time)", &report.by_hour_of_day);
+    print_section("BY EXIT REASON", &report.by_exit_reason);
+}
+
+// "report" subcommand, error means the history can't be read or exported (non-zero exit code)
+pub fn run_report(
+    trade_history_file: &str,
+    json: bool,
+    export_csv: Option<String>,
+    export_json: Option<String>,
+) -> Result<(), String> {
+    let records = read_trade_records(trade_history_file)
+        .map_err(|e| format!("cannot read trade history file {trade_history_file}: {e}"))?;
+
+    if let Some(path) = export_csv {
+        export_trade_records_csv(&path, &records)
+            .map_err(|e| format!("cannot export trade records to {path}: {e}"))?;
+        println!("{} trade records exported to {path}", records.len());
+    }
+
+    if let Some(path) = export_json {
+        export_trade_records_json(&path, &records)
+            .map_err(|e| format!("cannot export trade records to {path}: {e}"))?;
+        println!("{} trade records exported to {path}", records.len());
+    }
+
+    let report = build_trade_report(&records);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return Ok(());
+    }
+
+    print_trade_report(&report);
+    Ok(())
+}
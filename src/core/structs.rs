@@ -1,6 +1,98 @@
-use crate::core::types::OrderBookCmd;
+use crate::core::activity::ActivityMetrics;
+use crate::core::price_history::WindowVolumes;
+use crate::core::types::{OrderBookCmd, SendToTradeDecision, Symbol};
+use crate::core::window_stats::WindowStats;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct OrderBookCommand {
     pub cmd: OrderBookCmd,
 }
+
+// percent changes (and other statistics, volumes) of windows at the moment when the symbol was sent
+// to the engine, None means that window analysis is disabled (or it was not needed)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowPercentChanges {
+    pub pre_window: Option<Decimal>,
+    pub window: Decimal,
+    pub post_window: Option<Decimal>,
+    #[serde(default)]
+    pub pre_window_stats: Option<WindowStats>,
+    #[serde(default)]
+    pub window_stats: WindowStats,
+    #[serde(default)]
+    pub post_window_stats: Option<WindowStats>,
+    #[serde(default)]
+    pub volumes: WindowVolumes,
+}
+
+// why symbols monitor has chosen the symbol - it travels with the symbol from symbols monitor
+// through the engine to orderbook executor and back (in TradingSymbol)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntrySignal {
+    pub decision: SendToTradeDecision,
+    pub window_percent_changes: WindowPercentChanges,
+    pub activity: ActivityMetrics,
+    pub activity_score: Decimal,
+}
+
+/// Symbol sent from symbols monitor to the engine.
+#[derive(Debug, Clone)]
+pub struct TradeCandidate {
+    pub symbol: Symbol,
+    pub price: Decimal,
+    // (best ask - best bid) / best bid in percent
+    pub spread_percent: Decimal,
+    pub entry_signal: EntrySignal,
+}
+
+/*
+Health of symbols monitor stream (!ticker@arr). Updated by symbols monitor, read by the engine, written
+to |symbols_monitor_health_file| and returned by |cmd_symbols_monitor_health| cmd.
+ */
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StreamHealth {
+    pub connected: bool,
+    // no update for |symbols_monitor_stale_secs| - candidates can't be trusted
+    pub stale: bool,
+    // event time (ms) of the last update
+    pub last_event_time: u64,
+    pub last_event_age_secs: u64,
+    pub reconnects: u64,
+    pub last_error: Option<String>,
+    // when the last update was received
+    #[serde(skip)]
+    pub last_event: Option<Instant>,
+}
+
+impl StreamHealth {
+    pub fn healthy(&self) -> bool {
+        self.connected && !self.stale
+    }
+
+    // |last_event_age_secs| is not counted between updates - it's refreshed before health is shown
+    pub fn refresh_age(&mut self) {
+        self.last_event_age_secs = self
+            .last_event
+            .map(|t| t.elapsed().as_secs())
+            .unwrap_or_default();
+    }
+
+    pub fn status(&self) -> StreamStatus {
+        (
+            self.connected,
+            self.stale,
+            self.reconnects,
+            self.last_error.clone(),
+        )
+    }
+}
+
+pub type SharedStreamHealth = Arc<RwLock<StreamHealth>>;
+
+// connected, stale, reconnects, last error - what tells the state of the stream, the rest of StreamHealth
+// changes with every update
+pub type StreamStatus = (bool, bool, u64, Option<String>);
@@ -1,11 +1,17 @@
 use crate::binance::prices::process_symbol_qty;
 use crate::binance::trading::reverse_symbol_action;
 use crate::core::calc::percent_diff;
-use crate::core::types::{CurrentTradingProfit, Symbol, SymbolAction, TradingDecision, TradingNextStep};
+use crate::core::position::{OrderFill, Position};
+use crate::core::structs::EntrySignal;
+use crate::core::types::{
+    CurrentTradingProfit, ExitReason, Symbol, SymbolAction, TradingDecision, TradingNextStep,
+};
+use chrono::{DateTime, Utc};
 use rust_decimal::{Decimal, RoundingStrategy};
 use std::collections::{BTreeMap, HashMap};
 use tokio::time::Instant;
 
+/// Symbol traded by orderbook executor: entry, qty, filters and what happened with it.
 #[derive(Debug, Clone)]
 pub struct TradingSymbol {
     pub symbol: Symbol,
@@ -17,9 +23,11 @@ pub struct TradingSymbol {
     pub good_profit_price: Decimal,
     pub absolute_minimal_profit_percent: Decimal,
     pub trading_started: Instant,
+    pub trading_started_at: DateTime<Utc>,
     pub highest_price_since_min_profit: Decimal,
     pub highest_price_since_good_profit: Decimal,
     pub last_best_price: Decimal,
+    #[allow(dead_code)]
     pub best_price_now: Decimal,
     pub trading_next_step: TradingNextStep,
     pub previous_profit_percent: Decimal,
@@ -32,6 +40,61 @@ pub struct TradingSymbol {
     pub started_qty: Decimal,
     pub used_qty: Decimal,
     pub monitored_price: Decimal,
+    pub entry_price: Decimal,
+    pub entry_qty: Decimal,
+    pub peak_profit_percent: Decimal,
+    pub entry_signal: EntrySignal,
+    pub exit_reason: ExitReason,
+    // limit price of the exit order - the worst bid level needed for the whole qty (|price| is
+    // the average price we expect to get), 0 means |price| is used
+    pub exit_limit_price: Decimal,
+    // what was really bought and sold, |qty| is the held qty of the position while we are in
+    pub position: Position,
+}
+
+impl TradingSymbol {
+    /*
+    Applies the sell to the position. True means the position is closed - what's held now can't be
+    sold (below LOT_SIZE) and it's kept as dust, |qty| is then what we received back and |price| the
+    average exit price. False means part of the qty is still held (partial fill) and trading goes on.
+     */
+    pub fn apply_sell(&mut self, fill: &OrderFill, comission: Decimal) -> bool {
+        self.position.apply_sell(fill, comission);
+
+        let held_qty = self.position.held_qty();
+        let lot_size_min = self
+            .filters_map
+            .get(&format!("{}-lot-size-min-qty", self.symbol))
+            .copied()
+            .unwrap_or_default();
+
+        if held_qty > Decimal::ZERO
+            && held_qty >= lot_size_min
+            && process_symbol_qty(self.symbol.clone(), held_qty, &self.filters_map)
+                .is_some_and(|qty| qty > Decimal::ZERO)
+        {
+            self.qty = held_qty;
+            return false;
+        }
+
+        self.position.dust_qty = held_qty;
+        self.qty = self.position.net_received_quote();
+        self.price = self.position.avg_exit_price();
+
+        true
+    }
+
+    /*
+    Held qty can't be sold (below LOT_SIZE or MIN_NOTIONAL) - it's kept as dust and the trade is
+    finished, |qty| and |price| are set like for a closed position.
+     */
+    pub fn close_as_dust(&mut self) {
+        self.position.dust_qty = self.position.held_qty();
+        self.qty = self.position.net_received_quote();
+        self.price = self.position.avg_exit_price();
+        self.exit_reason = ExitReason::DustPosition;
+        self.trade_decision = TradingDecision::Stop;
+    }
 }
 
 // return value: Some(qty, price) - it shows if order action is possible, if yes for what price and qty
@@ -118,7 +181,9 @@ pub fn check_current_profit_percent(
 
     // when SIDE=SELL quantity means: I want to use (sell) this my "quantity" for "current_symbol_price"
     if symbol_action == SymbolAction::Sell {
-        let qty_tmp = trading_symbol.qty * best_bid_price;
+        // what was already sold (partial exit) counts too
+        let qty_tmp =
+            trading_symbol.position.net_received_quote() + trading_symbol.qty * best_bid_price;
 
         return percent_diff(trading_symbol.used_qty, qty_tmp)
             .round_dp_with_strategy(2, RoundingStrategy::ToZero);
@@ -1,8 +1,8 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::fmt;
-use strum_macros::EnumString;
+use strum_macros::{Display, EnumString};
 
-#[derive(Deserialize, Debug, Clone, Eq, Hash)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Symbol(pub String);
 
 unsafe impl Sync for Symbol {}
@@ -13,15 +13,31 @@ pub enum TradingMode {
     RealTrading,
 }
 
-#[derive(Default, Debug, Clone, Hash)]
-pub struct EmptyAsset(pub String);
+/*
+Where the bot connects to:
+- Production: Binance (real money!), default
+- Testnet: Binance spot testnet (https://testnet.binance.vision), requires testnet API keys
+- Custom: addresses from |rest_api_endpoint| and |ws_endpoint|, like local mock server
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash, Display, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExchangeEnvironment {
+    #[strum(serialize = "PRODUCTION")]
+    #[default]
+    Production,
+    #[strum(serialize = "TESTNET")]
+    Testnet,
+    #[strum(serialize = "CUSTOM")]
+    Custom,
+}
 
-#[derive(Default, Debug, Clone, Eq, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BaseAsset(pub String);
 
-#[derive(Default, Debug, Clone, Eq, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct QuoteAsset(pub String);
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, EnumString)]
 pub enum Asset {
     BaseAsset(BaseAsset),
@@ -36,7 +52,7 @@ pub enum KlineSignal {
     KillNow,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, EnumString)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash, EnumString)]
 pub enum SymbolAction {
     Buy,
     Sell,
@@ -79,7 +95,7 @@ Possible decision:
   Pair has the threshold within the specified limit for the main window nevertheless settings for a pre or
   post window.
  */
-#[derive(Debug, Clone, PartialEq, Eq, Hash, EnumString)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EnumString, Display, Serialize, Deserialize)]
 pub enum SendToTradeDecision {
     Negative,
     MainWindowPositiveAnalysis,
@@ -88,19 +104,105 @@ pub enum SendToTradeDecision {
     MainWindowAndBothWindowsPositiveAnalysis,
 }
 
+/*
+ExitReason tells why orderbook executor finished trading a pair. It travels back to the engine in
+TradingSymbol and it's stored in the trade history.
+
+Reasons when we didn't join the trade:
+- NotEntered
+  Default value - executor finished before any decision has been made
+- SpreadRejected
+  Price for buy differs too much from the monitored price
+  (|allowed_buy_diff_from_symbol_monitor_percent|)
+- LiquidityRejected
+  Order book failed pre-entry liquidity checks (spread, depth, exit slippage) after
+  |liquidity_wait_secs|, see |acceptable_liquidity_count|
+- PriceFilterRejected
+  Profit prices can't be used because of exchange PRICE_FILTER
+- BuyNotFilled
+  Buy order has not been filled (nothing bought)
+
+Reasons when we left the trade:
+- GoodProfit, MinProfit
+  Price dropped by the allowed percent since good/min profit has been reached
+- LossLimit
+  Loss reached |loss_limit_percent|
+- Timeout
+  |time_limit_secs| passed and the required profit (if any) was there
+- Requested
+  Leave requested from the engine (cmd file)
+- ExchangeProtection
+  OCO placed on the exchange was filled (|exchange_protection_enabled|)
+- StreamStale
+  Depth stream was down longer than |depth_stream_stale_exit_secs|
+- DustPosition
+  What we hold can't be sold (below LOT_SIZE or MIN_NOTIONAL, e.g. after a partial buy) - it's kept
+  as dust
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EnumString, Display, Serialize, Deserialize)]
+pub enum ExitReason {
+    NotEntered,
+    SpreadRejected,
+    LiquidityRejected,
+    PriceFilterRejected,
+    BuyNotFilled,
+    GoodProfit,
+    MinProfit,
+    LossLimit,
+    Timeout,
+    Requested,
+    ExchangeProtection,
+    StreamStale,
+    DustPosition,
+}
+
+impl ExitReason {
+    // true if we were in the trade (something was bought)
+    pub fn entered(&self) -> bool {
+        !matches!(
+            self,
+            ExitReason::NotEntered
+                | ExitReason::SpreadRejected
+                | ExitReason::LiquidityRejected
+                | ExitReason::PriceFilterRejected
+                | ExitReason::BuyNotFilled
+        )
+    }
+}
+
+/*
+Order type used by orderbook executor, selectable for the entry and for every exit reason:
+- Limit
+  Default - GTC limit order, executor waits for it and cancels it (buy) or leaves it on the exchange
+  (sell) if it's not filled in time
+- LimitIoc
+  limit order filled immediately as much as possible, the rest is expired by the exchange
+- LimitFok
+  limit order filled immediately completely or not at all
+- Market
+  filled immediately by the best available prices, buy spends the quote qty (quoteOrderQty)
+ */
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Display, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderKind {
+    #[strum(serialize = "LIMIT")]
+    #[default]
+    Limit,
+    #[strum(serialize = "LIMIT IOC")]
+    LimitIoc,
+    #[strum(serialize = "LIMIT FOK")]
+    LimitFok,
+    #[strum(serialize = "MARKET")]
+    Market,
+}
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, EnumString)]
 pub enum ReadMarketDepthNow {
     YES,
     NO,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, EnumString)]
-pub enum Trading {
-    Start,
-    Decline,
-    Hold,
-}
-
 #[derive(Debug, Clone, PartialEq, Eq, Hash, EnumString)]
 pub enum OrderBookCmd {
     StopAndInstantSell,
@@ -169,24 +271,6 @@ impl fmt::Display for Asset {
     }
 }
 
-impl PartialEq for Symbol {
-    fn eq(&self, other: &Self) -> bool {
-        self.to_string() == other.to_string()
-    }
-}
-
-impl PartialEq for BaseAsset {
-    fn eq(&self, other: &Self) -> bool {
-        self.to_string() == other.to_string()
-    }
-}
-
-impl PartialEq for QuoteAsset {
-    fn eq(&self, other: &Self) -> bool {
-        self.to_string() == other.to_string()
-    }
-}
-
 impl PartialEq<BaseAsset> for QuoteAsset {
     fn eq(&self, other: &BaseAsset) -> bool {
         self.to_string() == other.to_string()
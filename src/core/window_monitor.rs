@@ -1,21 +1,28 @@
 use crate::config::settings::ConfigStruct;
 use crate::core::calc::percentage_change_between_first_and_last_element;
+use crate::core::window_stats::{window_stats, WindowStats};
 use rust_decimal::Decimal;
 use std::cell::Cell;
 use std::collections::HashMap;
 use std::rc::Rc;
 
+/// Result of [`calculate_window`]: window percent change and which thresholds it reached.
 pub struct WindowStatus {
     pub symbols_window_with_percent_changes: Rc<Cell<HashMap<String, Decimal>>>,
     pub percent_change: Decimal,
     pub monitor_threshold_reached: bool,
     pub rise_threshold_reached: bool,
     pub drop_threshold_reached: bool,
+    // run up, drawdown, slope, ... of the window and whether they are within configured thresholds
+    pub stats: WindowStats,
+    pub stats_thresholds_reached: bool,
 }
 
+/// Checks a price window of the symbol against `symbol_monitor` thresholds and records its
+/// percent change in `symbols_window_with_percent_changes`.
 // this function is pure
 pub fn calculate_window(
-    config: ConfigStruct,
+    config: &ConfigStruct,
     symbol: &str,
     window_list: &[Decimal],
     symbols_window_with_percent_changes: Rc<Cell<HashMap<String, Decimal>>>,
@@ -66,12 +73,18 @@ pub fn calculate_window(
 
     symbols_window_with_percent_changes.set(new_symbols_window_map);
 
+    let stats = window_stats(window_list);
+    let stats_thresholds_reached =
+        stats.thresholds_reached(&config.symbol_monitor.window_stats_thresholds());
+
     let mut window_status = WindowStatus {
         symbols_window_with_percent_changes: Rc::new(Cell::new(Default::default())),
         percent_change: percent_change_window,
         monitor_threshold_reached: false,
         rise_threshold_reached: false,
         drop_threshold_reached: false,
+        stats_thresholds_reached,
+        stats,
     };
 
     if percent_change_window >= config_monitor_min_percent
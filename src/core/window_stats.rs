@@ -0,0 +1,259 @@
+use crate::config::settings::ConfigSymbolMonitor;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/*
+Statistics of the price window (one price per second, see PriceHistory):
+- percent_change: the first price vs the last one
+- max_run_up_percent: the biggest rise from a low to a later high
+- max_drawdown_percent: the biggest fall from a high to a later low (positive number)
+- slope_percent_per_sec: linear regression slope of prices, in percent of the first price per second
+- std_dev_percent: standard deviation of prices, in percent of their mean
+- direction_changes: how many times price turned from rising to falling or back (flat seconds are skipped)
+
+Window which spikes 5% and returns has small percent_change, but large run up and drawdown.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowStats {
+    pub percent_change: Decimal,
+    pub max_run_up_percent: Decimal,
+    pub max_drawdown_percent: Decimal,
+    pub slope_percent_per_sec: Decimal,
+    pub std_dev_percent: Decimal,
+    pub direction_changes: u64,
+}
+
+/*
+Optional [min, max] thresholds of window statistics (see config.toml), None means not checked.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WindowStatsThresholds {
+    pub run_up: Option<[Decimal; 2]>,
+    pub drawdown: Option<[Decimal; 2]>,
+    pub slope: Option<[Decimal; 2]>,
+    pub std_dev: Option<[Decimal; 2]>,
+    pub direction_changes: Option<[Decimal; 2]>,
+}
+
+fn to_decimal(v: f64) -> Decimal {
+    Decimal::from_f64(v).unwrap_or_default().round_dp(4)
+}
+
+pub fn window_stats(prices: &[Decimal]) -> WindowStats {
+    let (first, last) = match (prices.first(), prices.last()) {
+        (Some(f), Some(l)) if !f.is_zero() => (*f, *l),
+        _ => return WindowStats::default(),
+    };
+
+    let mut max_run_up = Decimal::ZERO;
+    let mut max_drawdown = Decimal::ZERO;
+    let mut low = first;
+    let mut high = first;
+
+    for price in prices {
+        low = low.min(*price);
+        high = high.max(*price);
+
+        if !low.is_zero() {
+            max_run_up = max_run_up.max((price - low) / low);
+        }
+        if !high.is_zero() {
+            max_drawdown = max_drawdown.max((high - price) / high);
+        }
+    }
+
+    // direction of every move (flat ones are skipped), then count turns
+    let directions: Vec<bool> = prices
+        .iter()
+        .zip(prices.iter().skip(1))
+        .filter(|(a, b)| a != b)
+        .map(|(a, b)| b > a)
+        .collect();
+    let direction_changes = directions
+        .iter()
+        .zip(directions.iter().skip(1))
+        .filter(|(a, b)| a != b)
+        .count();
+
+    let values: Vec<f64> = prices.iter().filter_map(|p| p.to_f64()).collect();
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+
+    // x is the second within the window
+    let mean_x = (n - 1.0) / 2.0;
+    let (covariance, variance_x) =
+        values
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(cov, var), (x, y)| {
+                let dx = x as f64 - mean_x;
+                (cov + dx * (y - mean), var + dx * dx)
+            });
+    let slope = match variance_x {
+        v if v > 0.0 => covariance / v,
+        _ => 0.0,
+    };
+    let first_f64 = first.to_f64().unwrap_or(1.0);
+
+    let std_dev = (values.iter().map(|y| (y - mean).powi(2)).sum::<f64>() / n).sqrt();
+
+    WindowStats {
+        percent_change: ((last - first) / first * Decimal::ONE_HUNDRED).round_dp(4),
+        max_run_up_percent: (max_run_up * Decimal::ONE_HUNDRED).round_dp(4),
+        max_drawdown_percent: (max_drawdown * Decimal::ONE_HUNDRED).round_dp(4),
+        slope_percent_per_sec: to_decimal(slope / first_f64 * 100.0),
+        std_dev_percent: match mean {
+            m if m > 0.0 => to_decimal(std_dev / m * 100.0),
+            _ => Decimal::ZERO,
+        },
+        direction_changes: direction_changes as u64,
+    }
+}
+
+impl WindowStats {
+    // every configured threshold has to be reached
+    pub fn thresholds_reached(&self, thresholds: &WindowStatsThresholds) -> bool {
+        let in_range = |value: Decimal, min_max: &Option<[Decimal; 2]>| match min_max {
+            Some([min, max]) => value >= *min && value <= *max,
+            None => true,
+        };
+
+        in_range(self.max_run_up_percent, &thresholds.run_up)
+            && in_range(self.max_drawdown_percent, &thresholds.drawdown)
+            && in_range(self.slope_percent_per_sec, &thresholds.slope)
+            && in_range(self.std_dev_percent, &thresholds.std_dev)
+            && in_range(
+                Decimal::from(self.direction_changes),
+                &thresholds.direction_changes,
+            )
+    }
+}
+
+// thresholds are lists in config: [] means not checked, otherwise [min, max]
+fn min_max(list: &[Decimal]) -> Option<[Decimal; 2]> {
+    match list {
+        [min, max] => Some([*min, *max]),
+        _ => None,
+    }
+}
+
+impl ConfigSymbolMonitor {
+    pub fn pre_window_stats_thresholds(&self) -> WindowStatsThresholds {
+        WindowStatsThresholds {
+            run_up: min_max(&self.pre_window_run_up_min_max_percent),
+            drawdown: min_max(&self.pre_window_drawdown_min_max_percent),
+            slope: min_max(&self.pre_window_slope_min_max_percent_per_sec),
+            std_dev: min_max(&self.pre_window_std_dev_min_max_percent),
+            direction_changes: min_max(&self.pre_window_direction_changes_min_max),
+        }
+    }
+
+    pub fn window_stats_thresholds(&self) -> WindowStatsThresholds {
+        WindowStatsThresholds {
+            run_up: min_max(&self.window_run_up_min_max_percent),
+            drawdown: min_max(&self.window_drawdown_min_max_percent),
+            slope: min_max(&self.window_slope_min_max_percent_per_sec),
+            std_dev: min_max(&self.window_std_dev_min_max_percent),
+            direction_changes: min_max(&self.window_direction_changes_min_max),
+        }
+    }
+
+    pub fn post_window_stats_thresholds(&self) -> WindowStatsThresholds {
+        WindowStatsThresholds {
+            run_up: min_max(&self.post_window_run_up_min_max_percent),
+            drawdown: min_max(&self.post_window_drawdown_min_max_percent),
+            slope: min_max(&self.post_window_slope_min_max_percent_per_sec),
+            std_dev: min_max(&self.post_window_std_dev_min_max_percent),
+            direction_changes: min_max(&self.post_window_direction_changes_min_max),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    // spikes up and down: 100 -> 105 -> 102 -> 108 -> 104
+    fn spiky_prices() -> Vec<Decimal> {
+        vec![dec!(100), dec!(105), dec!(102), dec!(108), dec!(104)]
+    }
+
+    #[test]
+    fn stats_of_spiky_window() {
+        let stats = window_stats(&spiky_prices());
+
+        assert_eq!(stats.percent_change, dec!(4));
+        // 100 -> 108
+        assert_eq!(stats.max_run_up_percent, dec!(8));
+        // 108 -> 104 is larger than 105 -> 102
+        assert_eq!(stats.max_drawdown_percent, dec!(3.7037));
+        // regression: covariance 11 / variance of seconds 10 = 1.1 per second, of the first price 100
+        assert_eq!(stats.slope_percent_per_sec, dec!(1.1));
+        assert_eq!(stats.std_dev_percent, dec!(2.6136));
+        // up, down, up, down
+        assert_eq!(stats.direction_changes, 3);
+    }
+
+    #[test]
+    fn flat_seconds_are_not_direction_changes() {
+        let stats = window_stats(&[dec!(1), dec!(2), dec!(2), dec!(2), dec!(1)]);
+
+        assert_eq!(stats.direction_changes, 1);
+    }
+
+    #[test]
+    fn flat_window_has_zero_stats() {
+        let stats = window_stats(&[dec!(5); 10]);
+
+        assert_eq!(stats, WindowStats::default());
+    }
+
+    #[test]
+    fn steady_fall_has_only_drawdown() {
+        let stats = window_stats(&[dec!(100), dec!(99), dec!(98)]);
+
+        assert_eq!(stats.percent_change, dec!(-2));
+        assert_eq!(stats.max_run_up_percent, Decimal::ZERO);
+        assert_eq!(stats.max_drawdown_percent, dec!(2));
+        assert_eq!(stats.slope_percent_per_sec, dec!(-1));
+        assert_eq!(stats.direction_changes, 0);
+    }
+
+    #[test]
+    fn empty_or_zero_window_has_default_stats() {
+        assert_eq!(window_stats(&[]), WindowStats::default());
+        assert_eq!(
+            window_stats(&[Decimal::ZERO, dec!(1)]),
+            WindowStats::default()
+        );
+    }
+
+    #[test]
+    fn thresholds_are_inclusive_and_optional() {
+        let stats = window_stats(&spiky_prices());
+
+        assert!(stats.thresholds_reached(&WindowStatsThresholds::default()));
+
+        let thresholds = WindowStatsThresholds {
+            run_up: Some([dec!(8), dec!(10)]),
+            direction_changes: Some([dec!(0), dec!(3)]),
+            ..Default::default()
+        };
+        assert!(stats.thresholds_reached(&thresholds));
+
+        let thresholds = WindowStatsThresholds {
+            direction_changes: Some([dec!(0), dec!(2)]),
+            ..thresholds
+        };
+        assert!(!stats.thresholds_reached(&thresholds));
+    }
+
+    #[test]
+    fn threshold_list_needs_min_and_max() {
+        assert_eq!(min_max(&[]), None);
+        assert_eq!(min_max(&[dec!(1)]), None);
+        assert_eq!(min_max(&[dec!(1), dec!(2)]), Some([dec!(1), dec!(2)]));
+    }
+}
@@ -1,10 +1,14 @@
-use std::collections::HashMap;
 use crate::core::types::Symbol;
 use rust_decimal::Decimal;
+use std::collections::HashMap;
 
+/// Exchange abstraction: list of tradable symbols and adjusting price/qty to exchange filters.
+///
+/// Implemented by [`crate::BinanceObj`] (exchangeInfo fetched by the running bot) and by
+/// [`crate::mock::scenario::MockScenario`] (symbols of the mock server).
 pub trait Exchange {
     fn get_all_valid_symbols(&self) -> HashMap<Symbol, bool>;
-    // return is (Price, Qty)
+    // return is (Price, Qty), 0 for the one which doesn't pass filters (or unknown symbol)
     fn process_symbol_price_and_qty(
         &self,
         symbol: Symbol,
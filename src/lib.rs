@@ -0,0 +1,49 @@
+//! Price volatility trading bot for Binance spot market.
+//!
+//! The binary (`src/main.rs`) is a thin command line layer over this library, other programs can
+//! use the same parts directly:
+//!
+//! - [`config`] - config file types ([`ConfigStruct`]), loading with validation
+//!   ([`load_config`]), hot reload and per-symbol overrides
+//! - [`exchange`] - exchange abstraction ([`exchange::Exchange`]), Binance implementation is in
+//!   [`binance`] (exchangeInfo, filters parsing, websocket monitors, orders)
+//! - [`core::window_monitor`], [`core::calc`] - price window calculations and PnL maths
+//! - [`core::trading`], [`core::structs`], [`core::types`] - trade types passed between monitor,
+//!   engine and executors
+//! - [`run`] - engine entrypoint: fetches exchangeInfo, starts symbol monitor, engine and config
+//!   watcher and never returns
+//!
+//! ```no_run
+//! use price_volatility_trading_bot::core::types::TradingMode;
+//! use price_volatility_trading_bot::{load_config, run};
+//!
+//! # async fn example() {
+//! let (config, _warnings) = load_config("config.toml").expect("invalid config");
+//! run(config, TradingMode::Simulation).await.expect("bot can't start");
+//! # }
+//! ```
+
+pub mod binance;
+pub mod config;
+pub mod core;
+pub mod exchange;
+pub mod mock;
+
+pub use crate::binance::object::BinanceObj;
+pub use crate::config::settings::{ConfigStruct, CONFIG_FILENAME};
+pub use crate::config::validation::{load_config, ConfigIssue};
+
+use crate::core::types::TradingMode;
+use std::sync::Arc;
+
+/// Starts the bot with already loaded config and runs it until the process ends.
+///
+/// Real trading needs `BOT_API_KEY` and `BOT_SECRET_KEY` env variables, error is returned without
+/// them. Real trading on Binance production is refused here (error), it has to be confirmed
+/// explicitly with [`BinanceObj::new`] and [`BinanceObj::start`].
+pub async fn run(config: ConfigStruct, trading_mode: TradingMode) -> Result<(), String> {
+    let binance_exchange = BinanceObj::new(config, trading_mode, false).await?;
+    Arc::new(binance_exchange).start().await;
+
+    Ok(())
+}
@@ -1,18 +1,111 @@
-use crate::binance::object::BinanceObj;
-use crate::config::settings::*;
-use std::fs;
+use log::{error, warn};
+use price_volatility_trading_bot::binance::record::run_record;
+use price_volatility_trading_bot::binance::symbols_list::run_symbols;
+use price_volatility_trading_bot::config::validation::run_check_config;
+use price_volatility_trading_bot::core::cli::{parse_cli_args, CliCommand};
+use price_volatility_trading_bot::core::report::run_report;
+use price_volatility_trading_bot::core::types::TradingMode;
+use price_volatility_trading_bot::mock::backtest::run_backtest;
+use price_volatility_trading_bot::mock::server::run_mock_server;
+use price_volatility_trading_bot::{load_config, BinanceObj};
 use std::sync::Arc;
 
-mod binance;
-mod config;
-mod core;
-mod exchange;
-
 #[tokio::main]
 async fn main() {
-    log4rs::init_file("log4rs.yaml", Default::default()).unwrap();
-    let config_data = fs::read_to_string(CONFIG_FILENAME).expect("cannot read main config file");
-    let config: ConfigStruct = toml::from_str(config_data.as_str()).unwrap();
-    let binance_exchange = BinanceObj::new(config.clone()).await;
+    let args = parse_cli_args();
+    let command = args.command();
+    log4rs::init_file(&args.log_config, Default::default()).unwrap();
+
+    // check-config has to work also with broken config, mock server doesn't need config at all
+    match command {
+        CliCommand::CheckConfig => {
+            if let Err(e) = run_check_config(&args.config) {
+                println!("{e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        CliCommand::MockServer {
+            ref scenario,
+            ref listen,
+        } => {
+            run_mock_server(scenario, listen);
+            return;
+        }
+        _ => (),
+    }
+
+    let mut config = match load_config(&args.config) {
+        Ok((config, warnings)) => {
+            for issue in warnings {
+                warn!("config: {issue}");
+            }
+            config
+        }
+        Err(issues) => {
+            for issue in issues {
+                if issue.is_error() {
+                    error!("config: {issue}");
+                } else {
+                    warn!("config: {issue}");
+                }
+            }
+            error!("invalid config file {}, refusing to start", args.config);
+            std::process::exit(1);
+        }
+    };
+    config.data_dir = args.data_dir.clone();
+
+    let (trading_mode, production_confirmed) = match command {
+        CliCommand::Run {
+            mode,
+            confirm_production,
+        } => (TradingMode::from(mode), confirm_production),
+        CliCommand::Simulate => (TradingMode::Simulation, false),
+        CliCommand::Record {
+            duration_secs,
+            symbols,
+        } => {
+            run_record(config, &args.data_dir, duration_secs, symbols).await;
+            return;
+        }
+        CliCommand::Backtest {
+            recording,
+            listen,
+            finish_secs,
+        } => {
+            run_backtest(config, &recording, &listen, finish_secs).await;
+            // bot threads are still running
+            std::process::exit(0);
+        }
+        CliCommand::Report {
+            json,
+            export_csv,
+            export_json,
+        } => {
+            if let Err(e) = run_report(&config.trade_history_path(), json, export_csv, export_json)
+            {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        CliCommand::Symbols { json, asset } => {
+            if let Err(e) = run_symbols(config, json, asset).await {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        CliCommand::CheckConfig | CliCommand::MockServer { .. } => unreachable!(),
+    };
+
+    let binance_exchange = match BinanceObj::new(config, trading_mode, production_confirmed).await {
+        Ok(b) => b.watch_config_file(&args.config),
+        Err(e) => {
+            error!("{e}, exiting");
+            std::process::exit(1);
+        }
+    };
     Arc::new(binance_exchange).start().await;
 }
@@ -0,0 +1,84 @@
+use crate::binance::object::BinanceObj;
+use crate::config::settings::ConfigStruct;
+use crate::core::report::run_report;
+use crate::core::types::{ExchangeEnvironment, TradingMode};
+use crate::mock::recording::read_recording;
+use crate::mock::server::serve_mock_scenario;
+use chrono::Utc;
+use log::{error, info};
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/*
+"backtest" subcommand: recording is replayed by mock server and the bot trades it in simulation mode
+(the same way as live data), trades go to a new trade history file in data dir and the report is
+printed when the recording ends (+ |finish_secs| for open trades).
+ */
+pub async fn run_backtest(config: ConfigStruct, recording: &str, listen: &str, finish_secs: u64) {
+    let scenario = match read_recording(recording, config.orderbook_monitor.comission_percent()) {
+        Ok(s) => s,
+        Err(e) => {
+            error!("backtest: {e}");
+            return;
+        }
+    };
+
+    let listener = match TcpListener::bind(listen) {
+        Ok(l) => l,
+        Err(e) => {
+            error!("backtest: cannot listen on {listen}: {e}");
+            return;
+        }
+    };
+
+    let duration_ms = scenario.steps.len() as u64 * scenario.tick_interval_ms;
+    let recording_name = Path::new(recording)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut config = config;
+    config.trade_history_file = format!(
+        "backtest-{recording_name}-{}.jsonl",
+        Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    let trade_history_file = config.trade_history_path();
+
+    info!(
+        "backtest: replaying {recording} ({} ticks, {}s) by mock server on {listen}",
+        scenario.steps.len(),
+        duration_ms / 1000
+    );
+    info!("backtest: trades are written to {trade_history_file}");
+
+    thread::spawn(move || serve_mock_scenario(scenario, listener));
+
+    config.exchange_environment = ExchangeEnvironment::Custom;
+    config.rest_api_endpoint = Some(format!("http://{listen}"));
+    config.ws_endpoint = Some(format!("ws://{listen}/ws"));
+
+    // config is not watched - backtest runs with the config it started with
+    let binance_exchange = match BinanceObj::new(config, TradingMode::Simulation, false).await {
+        Ok(b) => b,
+        Err(e) => {
+            error!("backtest: {e}");
+            return;
+        }
+    };
+    tokio::spawn(Arc::new(binance_exchange).start());
+
+    // blocking sleep - bot tasks keep runtime workers busy, timers wouldn't wake this task in time
+    thread::sleep(Duration::from_millis(duration_ms + finish_secs * 1000));
+    info!("backtest: finished");
+
+    if !Path::new(&trade_history_file).exists() {
+        println!("backtest: no trades were made");
+        return;
+    }
+
+    if let Err(e) = run_report(&trade_history_file, false, None, None) {
+        error!("backtest: {e}");
+    }
+}
@@ -0,0 +1,4 @@
+pub mod backtest;
+pub mod recording;
+pub mod scenario;
+pub mod server;
@@ -0,0 +1,86 @@
+use crate::mock::scenario::{
+    MockOrderFill, MockPrice, MockScenario, MockStep, MockStream, MockSymbol,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+/*
+Market data recorded by "record" subcommand (JSON lines). The first line lists recorded symbols, then
+there is one line per "!ticker@arr" event:
+
+{"symbols": [{"symbol": "XYZUSDT", "base_asset": "XYZ", "quote_asset": "USDT", "tick_size": "0.0001", ...}]}
+{"tick": {"time": 1700000000000, "prices": {"XYZUSDT": {"bid": "1.000", "ask": "1.001", "bid_qty": "10", "ask_qty": "20", "trades": 3}}}}
+
+|trades| is the number of trades since the previous tick. "backtest" subcommand replays the recording
+by mock server (one scenario step per recorded tick).
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingLine {
+    Symbols(Vec<MockSymbol>),
+    Tick(RecordedTick),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordedTick {
+    // event time (ms)
+    pub time: u64,
+    pub prices: BTreeMap<String, MockPrice>,
+}
+
+// mock scenario replaying the recording, ticks are as far apart as they were recorded (on average)
+pub fn read_recording(path: &str, commission_percent: Decimal) -> Result<MockScenario, String> {
+    let file = File::open(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+
+    let mut symbols = Vec::new();
+    let mut ticks: Vec<RecordedTick> = Vec::new();
+
+    for (n, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("cannot read {path}: {e}"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str(&line) {
+            Ok(RecordingLine::Symbols(s)) => symbols.extend(s),
+            Ok(RecordingLine::Tick(t)) => ticks.push(t),
+            Err(e) => return Err(format!("invalid recording {path}:{}: {e}", n + 1)),
+        }
+    }
+
+    if symbols.is_empty() || ticks.is_empty() {
+        return Err(format!("invalid recording {path}: no symbols or no ticks"));
+    }
+
+    let tick_interval_ms = match ticks.len() {
+        1 => 1000,
+        n => {
+            let duration = ticks[n - 1].time.saturating_sub(ticks[0].time);
+            (duration / (n as u64 - 1)).max(1)
+        }
+    };
+
+    Ok(MockScenario {
+        symbols,
+        steps: ticks
+            .into_iter()
+            .map(|t| MockStep {
+                ticks: 1,
+                prices: t.prices,
+                depth_stream: MockStream::Up,
+                ticker_stream: MockStream::Up,
+            })
+            .collect(),
+        tick_interval_ms,
+        depth_interval_ms: 100,
+        repeat: false,
+        order_fill: MockOrderFill::Filled,
+        commission_percent,
+        commission_asset: None,
+        commission_asset_price: Decimal::ZERO,
+        unfilled_limit_orders: 0,
+    })
+}
@@ -0,0 +1,222 @@
+use crate::core::types::Symbol;
+use crate::exchange::Exchange;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+/*
+Scripted scenario for the mock server (JSON file). Every |tick_interval_ms| the server moves to the
+next step (a step lasts |ticks| ticks), prices from the step are published via "!ticker@arr" and
+"<symbol>@depth@100ms" streams and returned by depth REST endpoint. Symbols missing in the step keep
+their previous prices. After the last step the scenario starts again (|repeat| = true) or stays at
+the last step.
+
+{
+  "symbols": [{"symbol": "XYZUSDT", "base_asset": "XYZ", "quote_asset": "USDT"}],
+  "steps": [
+    {"ticks": 10, "prices": {"XYZUSDT": {"bid": "1.000", "ask": "1.001"}}},
+    {"prices": {"XYZUSDT": {"bid": "1.050", "ask": "1.051", "trades": 50}}}
+  ]
+}
+ */
+#[derive(Deserialize, Debug, Clone)]
+pub struct MockScenario {
+    pub symbols: Vec<MockSymbol>,
+    pub steps: Vec<MockStep>,
+    #[serde(default = "default_tick_interval_ms")]
+    pub tick_interval_ms: u64,
+    #[serde(default = "default_depth_interval_ms")]
+    pub depth_interval_ms: u64,
+    #[serde(default)]
+    pub repeat: bool,
+    #[serde(default)]
+    pub order_fill: MockOrderFill,
+    // percent of the filled qty, taken in received asset
+    #[serde(default = "default_commission_percent")]
+    pub commission_percent: Decimal,
+    // commission is charged in this asset instead (BNB fee payment), its price in quote asset is
+    // |commission_asset_price|
+    #[serde(default)]
+    pub commission_asset: Option<String>,
+    #[serde(default)]
+    pub commission_asset_price: Decimal,
+    // first |unfilled_limit_orders| GTC LIMIT orders stay NEW whatever |order_fill| is (price ran away)
+    #[serde(default)]
+    pub unfilled_limit_orders: u64,
+}
+
+// what happens with orders placed on the mock server
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MockOrderFill {
+    // order is filled instantly
+    #[default]
+    Filled,
+    // order stays NEW until it's cancelled
+    New,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct MockSymbol {
+    pub symbol: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    #[serde(default = "default_tick_size")]
+    pub tick_size: Decimal,
+    #[serde(default = "default_step_size")]
+    pub step_size: Decimal,
+    #[serde(default = "default_step_size")]
+    pub min_qty: Decimal,
+    #[serde(default = "default_min_notional")]
+    pub min_notional: Decimal,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MockStep {
+    #[serde(default = "default_ticks")]
+    pub ticks: u64,
+    pub prices: BTreeMap<String, MockPrice>,
+    #[serde(default)]
+    pub depth_stream: MockStream,
+    #[serde(default)]
+    pub ticker_stream: MockStream,
+}
+
+// depth or ticker streams during the step - to test reconnects, REST fallback and watchdogs
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MockStream {
+    #[default]
+    Up,
+    // connected, but no updates are sent
+    Silent,
+    // connections are closed and refused
+    Down,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct MockPrice {
+    pub bid: Decimal,
+    pub ask: Decimal,
+    #[serde(default = "default_qty")]
+    pub bid_qty: Decimal,
+    #[serde(default = "default_qty")]
+    pub ask_qty: Decimal,
+    // trades made during every tick of this step
+    #[serde(default = "default_trades")]
+    pub trades: u64,
+}
+
+fn default_tick_interval_ms() -> u64 {
+    1000
+}
+
+fn default_depth_interval_ms() -> u64 {
+    100
+}
+
+fn default_commission_percent() -> Decimal {
+    dec!(0.1)
+}
+
+fn default_tick_size() -> Decimal {
+    dec!(0.0001)
+}
+
+fn default_step_size() -> Decimal {
+    dec!(0.1)
+}
+
+fn default_min_notional() -> Decimal {
+    dec!(5)
+}
+
+fn default_ticks() -> u64 {
+    1
+}
+
+fn default_qty() -> Decimal {
+    dec!(100000)
+}
+
+fn default_trades() -> u64 {
+    1
+}
+
+impl MockScenario {
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let data = fs::read_to_string(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+        let scenario: MockScenario =
+            serde_json::from_str(&data).map_err(|e| format!("invalid scenario {path}: {e}"))?;
+
+        if scenario.steps.is_empty() {
+            return Err(format!("invalid scenario {path}: no steps"));
+        }
+
+        for step in &scenario.steps {
+            for symbol in step.prices.keys() {
+                if !scenario.symbols.iter().any(|s| &s.symbol == symbol) {
+                    return Err(format!(
+                        "invalid scenario {path}: {symbol} is not listed in symbols"
+                    ));
+                }
+            }
+        }
+
+        Ok(scenario)
+    }
+
+    // step for the tick (counted from 0), None if the scenario is finished and not repeated
+    pub fn step_for_tick(&self, tick: u64) -> Option<&MockStep> {
+        let total: u64 = self.steps.iter().map(|s| s.ticks.max(1)).sum();
+
+        let mut tick = tick;
+        if tick >= total {
+            if !self.repeat {
+                return None;
+            }
+            tick %= total;
+        }
+
+        for step in &self.steps {
+            let ticks = step.ticks.max(1);
+            if tick < ticks {
+                return Some(step);
+            }
+            tick -= ticks;
+        }
+
+        None
+    }
+}
+
+// the mock server is the exchange - its filters are the ones of scenario symbols
+impl Exchange for MockScenario {
+    fn get_all_valid_symbols(&self) -> HashMap<Symbol, bool> {
+        self.symbols
+            .iter()
+            .map(|s| (Symbol(s.symbol.clone()), true))
+            .collect()
+    }
+
+    fn process_symbol_price_and_qty(
+        &self,
+        symbol: Symbol,
+        price: Decimal,
+        qty: Decimal,
+    ) -> (Decimal, Decimal) {
+        let Some(s) = self.symbols.iter().find(|s| s.symbol == symbol.to_string()) else {
+            return (Decimal::ZERO, Decimal::ZERO);
+        };
+
+        let price = (price / s.tick_size).floor() * s.tick_size;
+        let qty = match qty >= s.min_qty {
+            true => (qty / s.step_size).floor() * s.step_size,
+            false => Decimal::ZERO,
+        };
+
+        (price.normalize(), qty.normalize())
+    }
+}
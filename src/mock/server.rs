@@ -0,0 +1,1103 @@
+use crate::mock::scenario::{MockOrderFill, MockPrice, MockScenario, MockStream};
+use chrono::Utc;
+use log::{error, info, warn};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tungstenite::handshake::server::{Request, Response};
+use tungstenite::Message;
+
+const MAX_REQUEST_HEAD_LEN: usize = 16384;
+
+#[derive(Debug, Clone)]
+struct MockSymbolState {
+    price: MockPrice,
+    // levels removed from the book are sent with qty 0 in the next depth update
+    removed_bids: Vec<Decimal>,
+    removed_asks: Vec<Decimal>,
+    open: Decimal,
+    high: Decimal,
+    low: Decimal,
+    num_trades: u64,
+    volume: Decimal,
+    quote_volume: Decimal,
+    update_id: u64,
+}
+
+#[derive(Debug, Clone)]
+struct MockOrder {
+    symbol: String,
+    order_id: u64,
+    client_order_id: String,
+    side: String,
+    type_name: String,
+    time_in_force: String,
+    price: Decimal,
+    stop_price: Decimal,
+    orig_qty: Decimal,
+    executed_qty: Decimal,
+    cummulative_quote_qty: Decimal,
+    status: String,
+    time: i64,
+    fills: Vec<Value>,
+    order_list_id: i64,
+}
+
+// OCO: take profit (LIMIT_MAKER) and stop (STOP_LOSS_LIMIT) orders, when one is filled the other expires
+#[derive(Debug, Clone)]
+struct MockOrderList {
+    symbol: String,
+    order_list_id: u64,
+    order_ids: Vec<u64>,
+    // EXECUTING or ALL_DONE
+    status: String,
+    time: i64,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    tick: u64,
+    finished: bool,
+    symbols: BTreeMap<String, MockSymbolState>,
+    orders: HashMap<u64, MockOrder>,
+    next_order_id: u64,
+    order_lists: HashMap<u64, MockOrderList>,
+    next_order_list_id: u64,
+    // GTC LIMIT orders placed so far
+    limit_orders: u64,
+    depth_stream: MockStream,
+    ticker_stream: MockStream,
+}
+
+type SharedMockState = Arc<Mutex<MockState>>;
+
+struct HttpResponse {
+    code: u16,
+    body: Value,
+}
+
+impl HttpResponse {
+    fn ok(body: Value) -> Self {
+        Self { code: 200, body }
+    }
+
+    fn error(code: u16, api_code: i64, msg: &str) -> Self {
+        Self {
+            code,
+            body: json!({"code": api_code, "msg": msg}),
+        }
+    }
+}
+
+fn now_ms() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+//
+// BEGIN: scenario driver
+//
+
+fn apply_step(scenario: &MockScenario, state: &mut MockState) {
+    let step = match scenario.step_for_tick(state.tick) {
+        Some(s) => s,
+        None => {
+            if !state.finished {
+                info!("mock: scenario finished, prices stay at the last step");
+                state.finished = true;
+            }
+            return;
+        }
+    };
+
+    if state.depth_stream != step.depth_stream {
+        info!("mock: depth streams are {:?} now", step.depth_stream);
+        state.depth_stream = step.depth_stream;
+    }
+
+    if state.ticker_stream != step.ticker_stream {
+        info!("mock: ticker stream is {:?} now", step.ticker_stream);
+        state.ticker_stream = step.ticker_stream;
+    }
+
+    for (symbol, price) in &step.prices {
+        let s = state.symbols.get_mut(symbol).unwrap();
+
+        if s.price.bid != price.bid {
+            s.removed_bids.push(s.price.bid);
+        }
+        if s.price.ask != price.ask {
+            s.removed_asks.push(s.price.ask);
+        }
+
+        s.price = price.clone();
+        s.high = s.high.max(price.bid);
+        s.low = s.low.min(price.bid);
+    }
+
+    // trades are counted every tick of the step, symbols missing in the step have no trades
+    for (symbol, s) in state.symbols.iter_mut() {
+        let trades = step.prices.get(symbol).map(|p| p.trades).unwrap_or(0);
+
+        s.num_trades += trades;
+        s.volume += Decimal::from(trades);
+        s.quote_volume += Decimal::from(trades) * s.price.bid;
+    }
+
+    trigger_order_lists(scenario, state);
+}
+
+fn scenario_driver(scenario: MockScenario, state: SharedMockState) {
+    loop {
+        thread::sleep(Duration::from_millis(scenario.tick_interval_ms));
+
+        let mut state = state.lock().unwrap();
+        state.tick += 1;
+        apply_step(&scenario, &mut state);
+    }
+}
+
+fn depth_driver(depth_interval_ms: u64, state: SharedMockState) {
+    loop {
+        thread::sleep(Duration::from_millis(depth_interval_ms));
+
+        for s in state.lock().unwrap().symbols.values_mut() {
+            s.update_id += 1;
+        }
+    }
+}
+
+//
+// END: scenario driver
+//
+
+//
+// BEGIN: REST API
+//
+
+fn query_params(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn decimal_param(params: &HashMap<String, String>, name: &str) -> Option<Decimal> {
+    params.get(name).and_then(|v| v.parse::<Decimal>().ok())
+}
+
+fn exchange_info(scenario: &MockScenario) -> HttpResponse {
+    let symbols: Vec<Value> = scenario
+        .symbols
+        .iter()
+        .map(|s| {
+            json!({
+                "symbol": s.symbol,
+                "status": "TRADING",
+                "baseAsset": s.base_asset,
+                "quoteAsset": s.quote_asset,
+                "permissions": ["SPOT"],
+                "filters": [
+                    {
+                        "filterType": "PRICE_FILTER",
+                        "minPrice": s.tick_size.to_string(),
+                        "maxPrice": "1000000.00000000",
+                        "tickSize": s.tick_size.to_string()
+                    },
+                    {
+                        "filterType": "LOT_SIZE",
+                        "minQty": s.min_qty.to_string(),
+                        "maxQty": "9000000.00000000",
+                        "stepSize": s.step_size.to_string()
+                    },
+                    {
+                        "filterType": "MIN_NOTIONAL",
+                        "minNotional": s.min_notional.to_string(),
+                        "applyToMarket": true,
+                        "avgPriceMins": 5
+                    },
+                    {
+                        "filterType": "MARKET_LOT_SIZE",
+                        "minQty": "0.00000000",
+                        "maxQty": "9000000.00000000",
+                        "stepSize": "0.00000000"
+                    }
+                ]
+            })
+        })
+        .collect();
+
+    HttpResponse::ok(json!({
+        "timezone": "UTC",
+        "serverTime": now_ms(),
+        "rateLimits": [],
+        "exchangeFilters": [],
+        "symbols": symbols
+    }))
+}
+
+fn depth_snapshot(state: &SharedMockState, params: &HashMap<String, String>) -> HttpResponse {
+    let state = state.lock().unwrap();
+    let symbol = params.get("symbol").cloned().unwrap_or_default();
+
+    match state.symbols.get(&symbol) {
+        Some(s) => HttpResponse::ok(json!({
+            "lastUpdateId": s.update_id,
+            "bids": [[s.price.bid.to_string(), s.price.bid_qty.to_string()]],
+            "asks": [[s.price.ask.to_string(), s.price.ask_qty.to_string()]]
+        })),
+        None => HttpResponse::error(400, -1121, "Invalid symbol."),
+    }
+}
+
+fn order_json(order: &MockOrder) -> Value {
+    json!({
+        "symbol": order.symbol,
+        "orderId": order.order_id,
+        "clientOrderId": order.client_order_id,
+        "transactTime": order.time,
+        "price": order.price.to_string(),
+        "origQty": order.orig_qty.to_string(),
+        "executedQty": order.executed_qty.to_string(),
+        "cummulativeQuoteQty": order.cummulative_quote_qty.to_string(),
+        "status": order.status,
+        "timeInForce": order.time_in_force,
+        "type": order.type_name,
+        "side": order.side,
+        "stopPrice": order.stop_price.to_string(),
+        "icebergQty": "0.00000000",
+        "time": order.time,
+        "updateTime": order.time,
+        "isWorking": order.status == "NEW",
+        "origQuoteOrderQty": "0.00000000",
+        "orderListId": order.order_list_id,
+        "fills": order.fills
+    })
+}
+
+// fills the whole order for |price|, commission is taken from the received asset (or |commission_asset|)
+fn fill_order(scenario: &MockScenario, order: &mut MockOrder, price: Decimal) {
+    let qty = order.orig_qty;
+    let quote_qty = qty * price;
+    let commission_rate = scenario.commission_percent / Decimal::ONE_HUNDRED;
+    let symbol_info = scenario
+        .symbols
+        .iter()
+        .find(|s| s.symbol == order.symbol)
+        .unwrap();
+
+    let (commission, commission_asset) = match (&scenario.commission_asset, order.side.as_str()) {
+        (Some(asset), _) if !scenario.commission_asset_price.is_zero() => (
+            (quote_qty * commission_rate / scenario.commission_asset_price).round_dp(8),
+            asset.clone(),
+        ),
+        (_, "BUY") => (qty * commission_rate, symbol_info.base_asset.clone()),
+        _ => (quote_qty * commission_rate, symbol_info.quote_asset.clone()),
+    };
+
+    order.executed_qty = qty;
+    order.cummulative_quote_qty = quote_qty;
+    order.status = "FILLED".to_string();
+    order.fills = vec![json!({
+        "price": price.to_string(),
+        "qty": qty.to_string(),
+        "commission": commission.normalize().to_string(),
+        "commissionAsset": commission_asset,
+        "tradeId": order.order_id
+    })];
+}
+
+fn new_order(
+    scenario: &MockScenario,
+    state: &SharedMockState,
+    params: &HashMap<String, String>,
+) -> HttpResponse {
+    let mut state = state.lock().unwrap();
+    let symbol = params.get("symbol").cloned().unwrap_or_default();
+
+    let symbol_info = match scenario.symbols.iter().find(|s| s.symbol == symbol) {
+        Some(s) => s.clone(),
+        None => return HttpResponse::error(400, -1121, "Invalid symbol."),
+    };
+    let current = state.symbols.get(&symbol).unwrap().price.clone();
+
+    let side = params.get("side").cloned().unwrap_or_default();
+    let type_name = params
+        .get("type")
+        .cloned()
+        .unwrap_or_else(|| "LIMIT".to_string());
+
+    let market_price = match side.as_str() {
+        "BUY" => current.ask,
+        "SELL" => current.bid,
+        _ => return HttpResponse::error(400, -1102, "Mandatory parameter 'side' was not sent."),
+    };
+
+    let price = match type_name.as_str() {
+        "MARKET" => market_price,
+        _ => match decimal_param(params, "price") {
+            Some(p) => p,
+            None => {
+                return HttpResponse::error(400, -1102, "Mandatory parameter 'price' was not sent.")
+            }
+        },
+    };
+
+    let qty = match (
+        decimal_param(params, "quantity"),
+        decimal_param(params, "quoteOrderQty"),
+    ) {
+        (Some(q), _) => q,
+        (None, Some(quote_qty)) if !price.is_zero() => {
+            let steps = (quote_qty / price / symbol_info.step_size)
+                .round_dp_with_strategy(0, RoundingStrategy::ToZero);
+            steps * symbol_info.step_size
+        }
+        _ => {
+            return HttpResponse::error(400, -1102, "Mandatory parameter 'quantity' was not sent.")
+        }
+    };
+
+    state.next_order_id += 1;
+    let order_id = state.next_order_id;
+
+    let mut order = MockOrder {
+        symbol: symbol.clone(),
+        order_id,
+        client_order_id: params
+            .get("newClientOrderId")
+            .cloned()
+            .unwrap_or_else(|| format!("mock{order_id}")),
+        side: side.clone(),
+        type_name: type_name.clone(),
+        time_in_force: params
+            .get("timeInForce")
+            .cloned()
+            .unwrap_or_else(|| "GTC".to_string()),
+        price,
+        stop_price: Decimal::ZERO,
+        orig_qty: qty,
+        executed_qty: Decimal::ZERO,
+        cummulative_quote_qty: Decimal::ZERO,
+        status: "NEW".to_string(),
+        time: now_ms(),
+        fills: Vec::new(),
+        order_list_id: -1,
+    };
+
+    // IOC/FOK orders don't stay on the book - with |order_fill| "new" they are filled only if they cross
+    // the current price, otherwise they are expired
+    let immediate = type_name == "LIMIT" && order.time_in_force != "GTC";
+    let marketable = match side.as_str() {
+        "BUY" => price >= current.ask,
+        _ => price <= current.bid,
+    };
+
+    let runaway = if type_name == "LIMIT" && !immediate {
+        state.limit_orders += 1;
+        state.limit_orders <= scenario.unfilled_limit_orders
+    } else {
+        false
+    };
+
+    // market orders are always filled
+    if (scenario.order_fill == MockOrderFill::Filled && !runaway)
+        || type_name == "MARKET"
+        || (immediate && marketable)
+    {
+        fill_order(scenario, &mut order, price);
+    } else if immediate {
+        order.status = "EXPIRED".to_string();
+    }
+
+    info!(
+        "mock: {symbol} {side} {type_name} order {order_id}: qty: {qty}, price: {price}, status: {}",
+        order.status
+    );
+
+    let response = order_json(&order);
+    state.orders.insert(order_id, order);
+
+    HttpResponse::ok(response)
+}
+
+fn find_order<'a>(
+    state: &'a mut MockState,
+    params: &HashMap<String, String>,
+) -> Option<&'a mut MockOrder> {
+    let symbol = params.get("symbol").cloned().unwrap_or_default();
+    let order_id = params.get("orderId").and_then(|v| v.parse::<u64>().ok());
+    let client_order_id = params.get("origClientOrderId");
+
+    state.orders.values_mut().find(|o| {
+        o.symbol == symbol
+            && (Some(o.order_id) == order_id || Some(&o.client_order_id) == client_order_id)
+    })
+}
+
+fn order_status(state: &SharedMockState, params: &HashMap<String, String>) -> HttpResponse {
+    let mut state = state.lock().unwrap();
+
+    match find_order(&mut state, params) {
+        Some(order) => HttpResponse::ok(order_json(order)),
+        None => HttpResponse::error(400, -2013, "Order does not exist."),
+    }
+}
+
+fn cancel_order(state: &SharedMockState, params: &HashMap<String, String>) -> HttpResponse {
+    let mut state = state.lock().unwrap();
+
+    match find_order(&mut state, params) {
+        Some(order) if order.status == "NEW" || order.status == "PARTIALLY_FILLED" => {
+            order.status = "CANCELED".to_string();
+            info!("mock: {} order {} cancelled", order.symbol, order.order_id);
+
+            let mut response = order_json(order);
+            response["origClientOrderId"] = json!(order.client_order_id);
+            HttpResponse::ok(response)
+        }
+        _ => HttpResponse::error(400, -2011, "Unknown order sent."),
+    }
+}
+
+// trades of the order - fills with their commission
+fn my_trades(state: &SharedMockState, params: &HashMap<String, String>) -> HttpResponse {
+    let mut state = state.lock().unwrap();
+
+    let order = match find_order(&mut state, params) {
+        Some(order) => order,
+        None => return HttpResponse::ok(json!([])),
+    };
+
+    let trades: Vec<Value> = order
+        .fills
+        .iter()
+        .map(|fill| {
+            json!({
+                "symbol": order.symbol,
+                "id": fill["tradeId"],
+                "orderId": order.order_id,
+                "orderListId": order.order_list_id,
+                "price": fill["price"],
+                "qty": fill["qty"],
+                "quoteQty": order.cummulative_quote_qty.to_string(),
+                "commission": fill["commission"],
+                "commissionAsset": fill["commissionAsset"],
+                "time": order.time,
+                "isBuyer": order.side == "BUY",
+                "isMaker": false,
+                "isBestMatch": true
+            })
+        })
+        .collect();
+
+    HttpResponse::ok(json!(trades))
+}
+
+// last price of a scenario symbol (bid) or of the commission asset
+fn ticker_price(
+    scenario: &MockScenario,
+    state: &SharedMockState,
+    params: &HashMap<String, String>,
+) -> HttpResponse {
+    let state = state.lock().unwrap();
+    let symbol = params.get("symbol").cloned().unwrap_or_default();
+
+    if let Some(s) = state.symbols.get(&symbol) {
+        return HttpResponse::ok(json!({"symbol": symbol, "price": s.price.bid.to_string()}));
+    }
+
+    let commission_symbol = scenario.commission_asset.as_ref().and_then(|asset| {
+        scenario
+            .symbols
+            .iter()
+            .map(|s| format!("{asset}{}", s.quote_asset))
+            .find(|s| *s == symbol)
+    });
+
+    match commission_symbol {
+        Some(_) => HttpResponse::ok(json!({
+            "symbol": symbol,
+            "price": scenario.commission_asset_price.to_string()
+        })),
+        None => HttpResponse::error(400, -1121, "Invalid symbol."),
+    }
+}
+
+fn order_list_json(state: &MockState, list: &MockOrderList) -> Value {
+    let orders: Vec<&MockOrder> = list
+        .order_ids
+        .iter()
+        .filter_map(|id| state.orders.get(id))
+        .collect();
+
+    json!({
+        "orderListId": list.order_list_id,
+        "contingencyType": "OCO",
+        "listStatusType": if list.status == "EXECUTING" { "EXEC_STARTED" } else { "ALL_DONE" },
+        "listOrderStatus": list.status,
+        "listClientOrderId": format!("mocklist{}", list.order_list_id),
+        "transactionTime": list.time,
+        "symbol": list.symbol,
+        "orders": orders.iter().map(|o| json!({
+            "symbol": o.symbol,
+            "orderId": o.order_id,
+            "clientOrderId": o.client_order_id
+        })).collect::<Vec<Value>>(),
+        "orderReports": orders.iter().map(|o| order_json(o)).collect::<Vec<Value>>()
+    })
+}
+
+/*
+OCO sell: take profit LIMIT_MAKER at |price| and STOP_LOSS_LIMIT at |stopPrice| with limit |stopLimitPrice|.
+Like on Binance, take profit which would match immediately and stop which would trigger immediately are
+rejected.
+ */
+fn new_oco(
+    scenario: &MockScenario,
+    state: &SharedMockState,
+    params: &HashMap<String, String>,
+) -> HttpResponse {
+    let mut state = state.lock().unwrap();
+    let symbol = params.get("symbol").cloned().unwrap_or_default();
+
+    if !scenario.symbols.iter().any(|s| s.symbol == symbol) {
+        return HttpResponse::error(400, -1121, "Invalid symbol.");
+    }
+    if params.get("side").map(String::as_str) != Some("SELL") {
+        return HttpResponse::error(400, -1013, "mock: only SELL OCO is supported.");
+    }
+
+    let (qty, price, stop_price, stop_limit_price) = match (
+        decimal_param(params, "quantity"),
+        decimal_param(params, "price"),
+        decimal_param(params, "stopPrice"),
+        decimal_param(params, "stopLimitPrice"),
+    ) {
+        (Some(q), Some(p), Some(sp), Some(slp)) => (q, p, sp, slp),
+        _ => return HttpResponse::error(400, -1102, "Mandatory parameter was not sent."),
+    };
+
+    let bid = state.symbols.get(&symbol).unwrap().price.bid;
+    if price <= bid || stop_price >= bid {
+        return HttpResponse::error(400, -2010, "Order would trigger immediately.");
+    }
+
+    state.next_order_list_id += 1;
+    let order_list_id = state.next_order_list_id;
+    let mut order_ids = Vec::new();
+
+    for (type_name, order_price, order_stop_price) in [
+        ("STOP_LOSS_LIMIT", stop_limit_price, stop_price),
+        ("LIMIT_MAKER", price, Decimal::ZERO),
+    ] {
+        state.next_order_id += 1;
+        let order_id = state.next_order_id;
+        order_ids.push(order_id);
+
+        let order = MockOrder {
+            symbol: symbol.clone(),
+            order_id,
+            client_order_id: format!("mock{order_id}"),
+            side: "SELL".to_string(),
+            type_name: type_name.to_string(),
+            time_in_force: "GTC".to_string(),
+            price: order_price,
+            stop_price: order_stop_price,
+            orig_qty: qty,
+            executed_qty: Decimal::ZERO,
+            cummulative_quote_qty: Decimal::ZERO,
+            status: "NEW".to_string(),
+            time: now_ms(),
+            fills: Vec::new(),
+            order_list_id: order_list_id as i64,
+        };
+        state.orders.insert(order_id, order);
+    }
+
+    let list = MockOrderList {
+        symbol: symbol.clone(),
+        order_list_id,
+        order_ids,
+        status: "EXECUTING".to_string(),
+        time: now_ms(),
+    };
+
+    info!(
+        "mock: {symbol} OCO {order_list_id}: qty: {qty}, take profit: {price}, stop: {stop_price} (limit: {stop_limit_price})"
+    );
+
+    let response = order_list_json(&state, &list);
+    state.order_lists.insert(order_list_id, list);
+
+    HttpResponse::ok(response)
+}
+
+// take profit is filled when bid reaches it, triggered stop is filled by bid if it's not below its limit
+fn trigger_order_lists(scenario: &MockScenario, state: &mut MockState) {
+    let executing: Vec<u64> = state
+        .order_lists
+        .values()
+        .filter(|l| l.status == "EXECUTING")
+        .map(|l| l.order_list_id)
+        .collect();
+
+    for order_list_id in executing {
+        let list = state.order_lists.get(&order_list_id).unwrap().clone();
+        let bid = state.symbols.get(&list.symbol).unwrap().price.bid;
+
+        let filled = list.order_ids.iter().find_map(|id| {
+            let order = state.orders.get(id)?;
+            match order.type_name.as_str() {
+                "LIMIT_MAKER" if bid >= order.price => Some((*id, order.price)),
+                "STOP_LOSS_LIMIT" if bid <= order.stop_price && bid >= order.price => {
+                    Some((*id, bid))
+                }
+                _ => None,
+            }
+        });
+
+        let Some((filled_id, price)) = filled else {
+            continue;
+        };
+
+        for id in &list.order_ids {
+            let order = state.orders.get_mut(id).unwrap();
+            match *id == filled_id {
+                true => fill_order(scenario, order, price),
+                false => order.status = "EXPIRED".to_string(),
+            }
+        }
+        state.order_lists.get_mut(&order_list_id).unwrap().status = "ALL_DONE".to_string();
+
+        info!(
+            "mock: {} OCO {order_list_id}: order {filled_id} filled, price: {price}",
+            list.symbol
+        );
+    }
+}
+
+fn find_order_list(state: &MockState, params: &HashMap<String, String>) -> Option<u64> {
+    let order_list_id = params.get("orderListId")?.parse::<u64>().ok()?;
+
+    state
+        .order_lists
+        .contains_key(&order_list_id)
+        .then_some(order_list_id)
+}
+
+fn order_list_status(state: &SharedMockState, params: &HashMap<String, String>) -> HttpResponse {
+    let state = state.lock().unwrap();
+
+    match find_order_list(&state, params) {
+        Some(id) => HttpResponse::ok(order_list_json(&state, &state.order_lists[&id])),
+        None => HttpResponse::error(400, -2018, "Order list does not exist."),
+    }
+}
+
+fn cancel_order_list(state: &SharedMockState, params: &HashMap<String, String>) -> HttpResponse {
+    let mut state = state.lock().unwrap();
+
+    let list = match find_order_list(&state, params) {
+        Some(id) if state.order_lists[&id].status == "EXECUTING" => state.order_lists[&id].clone(),
+        _ => return HttpResponse::error(400, -2011, "Unknown order sent."),
+    };
+
+    for id in &list.order_ids {
+        state.orders.get_mut(id).unwrap().status = "CANCELED".to_string();
+    }
+    let list = state.order_lists.get_mut(&list.order_list_id).unwrap();
+    list.status = "ALL_DONE".to_string();
+    info!("mock: {} OCO {} cancelled", list.symbol, list.order_list_id);
+
+    let list = list.clone();
+    HttpResponse::ok(order_list_json(&state, &list))
+}
+
+fn route(
+    scenario: &MockScenario,
+    state: &SharedMockState,
+    method: &str,
+    path: &str,
+    params: &HashMap<String, String>,
+) -> HttpResponse {
+    match (method, path) {
+        ("GET", "/api/v3/ping") => HttpResponse::ok(json!({})),
+        ("GET", "/api/v3/time") => HttpResponse::ok(json!({ "serverTime": now_ms() })),
+        ("GET", "/api/v3/exchangeInfo") => exchange_info(scenario),
+        ("GET", "/api/v3/depth") => depth_snapshot(state, params),
+        ("POST", "/api/v3/order") => new_order(scenario, state, params),
+        ("GET", "/api/v3/order") => order_status(state, params),
+        ("DELETE", "/api/v3/order") => cancel_order(state, params),
+        ("GET", "/api/v3/myTrades") => my_trades(state, params),
+        ("GET", "/api/v3/ticker/price") => ticker_price(scenario, state, params),
+        ("POST", "/api/v3/order/oco") => new_oco(scenario, state, params),
+        ("GET", "/api/v3/orderList") => order_list_status(state, params),
+        ("DELETE", "/api/v3/orderList") => cancel_order_list(state, params),
+        _ => HttpResponse::error(404, -1000, "Unknown endpoint."),
+    }
+}
+
+fn handle_http(
+    scenario: &MockScenario,
+    state: &SharedMockState,
+    stream: TcpStream,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    // parameters can be sent in the body too
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut params = query_params(query);
+    params.extend(query_params(&String::from_utf8_lossy(&body)));
+
+    let response = route(scenario, state, method, path, &params);
+    let body = response.body.to_string();
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.code,
+        if response.code == 200 { "OK" } else { "Error" },
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+//
+// END: REST API
+//
+
+//
+// BEGIN: websocket streams
+//
+
+fn ticker_all_event(state: &MockState) -> Value {
+    let event_time = now_ms();
+
+    let tickers: Vec<Value> = state
+        .symbols
+        .iter()
+        .map(|(symbol, s)| {
+            let price_change = s.price.bid - s.open;
+            let price_change_percent = match s.open.is_zero() {
+                true => Decimal::ZERO,
+                false => (price_change / s.open * Decimal::ONE_HUNDRED).round_dp(3),
+            };
+
+            json!({
+                "e": "24hrTicker",
+                "E": event_time,
+                "s": symbol,
+                "p": price_change.to_string(),
+                "P": price_change_percent.to_string(),
+                "w": s.price.bid.to_string(),
+                "x": s.open.to_string(),
+                "c": s.price.bid.to_string(),
+                "Q": "1",
+                "b": s.price.bid.to_string(),
+                "B": s.price.bid_qty.to_string(),
+                "a": s.price.ask.to_string(),
+                "A": s.price.ask_qty.to_string(),
+                "o": s.open.to_string(),
+                "h": s.high.to_string(),
+                "l": s.low.to_string(),
+                "v": s.volume.to_string(),
+                "q": s.quote_volume.to_string(),
+                "O": event_time - 86400000,
+                "C": event_time,
+                "F": 0,
+                "L": s.num_trades,
+                "n": s.num_trades
+            })
+        })
+        .collect();
+
+    Value::Array(tickers)
+}
+
+// Some(message) only if there is a new update since |last_update_id|
+fn depth_update_event(
+    state: &mut MockState,
+    symbol: &str,
+    last_update_id: &mut u64,
+) -> Option<Value> {
+    let s = state.symbols.get_mut(symbol)?;
+
+    if s.update_id <= *last_update_id {
+        return None;
+    }
+
+    let mut bids = vec![json!([
+        s.price.bid.to_string(),
+        s.price.bid_qty.to_string()
+    ])];
+    for price in s.removed_bids.drain(..) {
+        bids.push(json!([price.to_string(), "0"]));
+    }
+
+    let mut asks = vec![json!([
+        s.price.ask.to_string(),
+        s.price.ask_qty.to_string()
+    ])];
+    for price in s.removed_asks.drain(..) {
+        asks.push(json!([price.to_string(), "0"]));
+    }
+
+    let event = json!({
+        "e": "depthUpdate",
+        "E": now_ms(),
+        "s": symbol,
+        "U": *last_update_id + 1,
+        "u": s.update_id,
+        "b": bids,
+        "a": asks
+    });
+
+    *last_update_id = s.update_id;
+
+    Some(event)
+}
+
+#[allow(clippy::result_large_err)]
+fn handle_websocket(
+    scenario: &MockScenario,
+    state: &SharedMockState,
+    stream: TcpStream,
+) -> Result<(), String> {
+    let mut path = String::new();
+    let mut socket = tungstenite::accept_hdr(stream, |request: &Request, response: Response| {
+        path = request.uri().path().to_string();
+        Ok(response)
+    })
+    .map_err(|e| format!("websocket handshake: {e}"))?;
+
+    let stream_name = path.rsplit('/').next().unwrap_or_default().to_string();
+    info!("mock: websocket client connected to {path}");
+
+    if stream_name == "!ticker@arr" {
+        loop {
+            let ticker_stream = state.lock().unwrap().ticker_stream;
+            if ticker_stream == MockStream::Down {
+                let _ = socket.close(None);
+                let _ = socket.flush();
+                return Err(format!("{path}: ticker stream is down"));
+            }
+
+            if ticker_stream == MockStream::Up {
+                let event = ticker_all_event(&state.lock().unwrap());
+                socket
+                    .send(Message::text(event.to_string()))
+                    .map_err(|e| format!("{path}: {e}"))?;
+            }
+
+            thread::sleep(Duration::from_millis(scenario.tick_interval_ms));
+        }
+    }
+
+    if let Some(symbol) = stream_name
+        .strip_suffix("@depth@100ms")
+        .or_else(|| stream_name.strip_suffix("@depth"))
+    {
+        let symbol = symbol.to_uppercase();
+        let mut last_update_id = 0;
+
+        if !state.lock().unwrap().symbols.contains_key(&symbol) {
+            let _ = socket.close(None);
+            return Err(format!("{path}: unknown symbol {symbol}"));
+        }
+
+        loop {
+            let depth_stream = state.lock().unwrap().depth_stream;
+            if depth_stream == MockStream::Down {
+                let _ = socket.close(None);
+                let _ = socket.flush();
+                return Err(format!("{path}: depth stream is down"));
+            }
+
+            let event = match depth_stream {
+                MockStream::Up => {
+                    depth_update_event(&mut state.lock().unwrap(), &symbol, &mut last_update_id)
+                }
+                _ => None,
+            };
+
+            if let Some(event) = event {
+                socket
+                    .send(Message::text(event.to_string()))
+                    .map_err(|e| format!("{path}: {e}"))?;
+            }
+
+            thread::sleep(Duration::from_millis(scenario.depth_interval_ms));
+        }
+    }
+
+    let _ = socket.close(None);
+    Err(format!("{path}: unknown stream"))
+}
+
+//
+// END: websocket streams
+//
+
+// request head is only peeked, so websocket handshake can read it again
+fn peek_request_head(stream: &TcpStream) -> Option<String> {
+    let mut buf = vec![0; MAX_REQUEST_HEAD_LEN];
+    let mut previous_len = 0;
+
+    for _ in 0..100 {
+        let len = stream.peek(&mut buf).ok()?;
+        let head = String::from_utf8_lossy(&buf[..len]).to_string();
+
+        if len == 0 {
+            return None;
+        }
+
+        if head.contains("\r\n\r\n") || len == MAX_REQUEST_HEAD_LEN {
+            return Some(head);
+        }
+
+        if len == previous_len {
+            thread::sleep(Duration::from_millis(10));
+        }
+        previous_len = len;
+    }
+
+    None
+}
+
+fn handle_connection(scenario: &MockScenario, state: &SharedMockState, stream: TcpStream) {
+    let head = match peek_request_head(&stream) {
+        Some(h) => h.to_lowercase(),
+        None => return,
+    };
+
+    if head.contains("upgrade: websocket") {
+        if let Err(e) = handle_websocket(scenario, state, stream) {
+            warn!("mock: websocket client disconnected: {e}");
+        }
+        return;
+    }
+
+    if let Err(e) = handle_http(scenario, state, stream) {
+        warn!("mock: http request failed: {e}");
+    }
+}
+
+// "mock-server" subcommand: local stand-in for Binance REST API and websocket streams
+pub fn run_mock_server(scenario_file: &str, listen: &str) {
+    let scenario = match MockScenario::from_file(scenario_file) {
+        Ok(s) => s,
+        Err(e) => {
+            error!("mock: {e}");
+            return;
+        }
+    };
+
+    let listener = match TcpListener::bind(listen) {
+        Ok(l) => l,
+        Err(e) => {
+            error!("mock: cannot listen on {listen}: {e}");
+            return;
+        }
+    };
+
+    info!("mock: Binance mock server listening on {listen}, scenario: {scenario_file}");
+    info!("mock: REST API: http://{listen}, websocket: ws://{listen}/ws");
+
+    serve_mock_scenario(scenario, listener);
+}
+
+// serves the scenario on already bound listener, never returns
+pub fn serve_mock_scenario(scenario: MockScenario, listener: TcpListener) {
+    let mut state = MockState::default();
+    for symbol in &scenario.symbols {
+        let price = scenario
+            .steps
+            .iter()
+            .find_map(|step| step.prices.get(&symbol.symbol))
+            .cloned()
+            .unwrap_or(MockPrice {
+                bid: Decimal::ONE,
+                ask: Decimal::ONE,
+                bid_qty: Decimal::ZERO,
+                ask_qty: Decimal::ZERO,
+                trades: 0,
+            });
+
+        state.symbols.insert(
+            symbol.symbol.clone(),
+            MockSymbolState {
+                open: price.bid,
+                high: price.bid,
+                low: price.bid,
+                price,
+                removed_bids: Vec::new(),
+                removed_asks: Vec::new(),
+                num_trades: 0,
+                volume: Decimal::ZERO,
+                quote_volume: Decimal::ZERO,
+                update_id: 1,
+            },
+        );
+    }
+    apply_step(&scenario, &mut state);
+
+    let state: SharedMockState = Arc::new(Mutex::new(state));
+
+    {
+        let scenario = scenario.clone();
+        let state = state.clone();
+        thread::spawn(move || scenario_driver(scenario, state));
+    }
+
+    {
+        let depth_interval_ms = scenario.depth_interval_ms;
+        let state = state.clone();
+        thread::spawn(move || depth_driver(depth_interval_ms, state));
+    }
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(s) => s,
+            Err(e) => {
+                warn!("mock: connection failed: {e}");
+                continue;
+            }
+        };
+
+        let scenario = scenario.clone();
+        let state = state.clone();
+        thread::spawn(move || handle_connection(&scenario, &state, stream));
+    }
+}
@@ -0,0 +1,109 @@
+// Mock server ("mock-server" subcommand) answers the bot's own exchangeInfo client and the binance crate
+// orders the way Binance does.
+
+use binance::account::Account;
+use binance::api::Binance;
+use price_volatility_trading_bot::binance::endpoints::binance_api_config;
+use price_volatility_trading_bot::binance::symbols_list::list_symbols;
+use price_volatility_trading_bot::core::types::{ExchangeEnvironment, Symbol};
+use price_volatility_trading_bot::exchange::Exchange;
+use price_volatility_trading_bot::mock::scenario::MockScenario;
+use price_volatility_trading_bot::mock::server::serve_mock_scenario;
+use price_volatility_trading_bot::{load_config, ConfigStruct};
+use rust_decimal_macros::dec;
+use std::net::TcpListener;
+use std::thread;
+
+const SCENARIO: &str = r#"{
+  "symbols": [
+    {"symbol": "XYZUSDT", "base_asset": "XYZ", "quote_asset": "USDT", "tick_size": "0.001", "min_notional": "10"}
+  ],
+  "steps": [
+    {"ticks": 1000, "prices": {"XYZUSDT": {"bid": "1.000", "ask": "1.001"}}}
+  ]
+}"#;
+
+// mock server on a free port, config pointing to it
+fn start_mock() -> ConfigStruct {
+    let scenario: MockScenario = serde_json::from_str(SCENARIO).unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || serve_mock_scenario(scenario, listener));
+
+    let (mut config, _warnings) = load_config("config.toml").unwrap();
+    config.exchange_environment = ExchangeEnvironment::Custom;
+    config.rest_api_endpoint = Some(format!("http://{address}"));
+    config.ws_endpoint = Some(format!("ws://{address}/ws"));
+
+    config
+}
+
+#[tokio::test]
+async fn exchange_info_lists_scenario_symbols() {
+    let config = start_mock();
+
+    let entries = list_symbols(&config, None).await.unwrap();
+
+    assert_eq!(entries.len(), 1);
+    let entry = &entries[0];
+    assert_eq!(entry.inclusion.symbol, "XYZUSDT");
+    assert_eq!(entry.inclusion.base_asset, "XYZ");
+    assert_eq!(entry.inclusion.quote_asset, "USDT");
+    assert_eq!(entry.tick_size, dec!(0.001));
+    assert_eq!(entry.min_notional, dec!(10));
+
+    let entries = list_symbols(&config, Some("BTC")).await.unwrap();
+    assert!(entries.is_empty());
+}
+
+#[test]
+fn order_round_trip() {
+    let config = start_mock();
+    let account: Account = Binance::new_with_config(
+        Some("key".to_string()),
+        Some("secret".to_string()),
+        &binance_api_config(&config),
+    );
+
+    // filled instantly at the ask
+    let buy = account.market_buy("XYZUSDT", 20.0).unwrap();
+    assert_eq!(buy.status, "FILLED");
+    assert_eq!(buy.executed_qty, 20.0);
+    assert!(buy.cummulative_quote_qty > 0.0);
+
+    let status = account.order_status("XYZUSDT", buy.order_id).unwrap();
+    assert_eq!(status.order_id, buy.order_id);
+    assert_eq!(status.status, "FILLED");
+    assert_eq!(status.side, "BUY");
+    assert_eq!(status.executed_qty.parse::<f64>().unwrap(), 20.0);
+
+    let sell = account.market_sell("XYZUSDT", 20.0).unwrap();
+    assert_eq!(sell.status, "FILLED");
+    assert_ne!(sell.order_id, buy.order_id);
+}
+
+#[test]
+fn scenario_filters_adjust_price_and_qty() {
+    let scenario: MockScenario = serde_json::from_str(SCENARIO).unwrap();
+
+    let symbols = scenario.get_all_valid_symbols();
+    assert_eq!(symbols.get(&Symbol("XYZUSDT".to_string())), Some(&true));
+
+    assert_eq!(
+        scenario.process_symbol_price_and_qty(
+            Symbol("XYZUSDT".to_string()),
+            dec!(1.23456),
+            dec!(7.89)
+        ),
+        (dec!(1.234), dec!(7.8))
+    );
+    // below LOT_SIZE min qty, unknown symbol
+    assert_eq!(
+        scenario.process_symbol_price_and_qty(Symbol("XYZUSDT".to_string()), dec!(1), dec!(0.05)),
+        (dec!(1), dec!(0))
+    );
+    assert_eq!(
+        scenario.process_symbol_price_and_qty(Symbol("ABCUSDT".to_string()), dec!(1), dec!(1)),
+        (dec!(0), dec!(0))
+    );
+}